@@ -0,0 +1,187 @@
+use p34::{BigUInt, BigUInt4096};
+
+/// Deterministic xorshift generator so failures are reproducible.
+fn random_biguint<const N: usize>(state: &mut u64) -> BigUInt<N> {
+    let mut result = BigUInt::<N>::new();
+    for limb in result.data.iter_mut() {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *limb = *state;
+    }
+    result
+}
+
+fn max<const N: usize>() -> BigUInt<N> {
+    BigUInt {
+        data: [u64::MAX; N],
+    }
+}
+
+/// Values worth checking at every size: zero, one, all ones, a single top
+/// bit, a single limb and random operands.
+fn samples<const N: usize>(seed: u64) -> Vec<BigUInt<N>> {
+    let mut top_bit = BigUInt::<N>::new();
+    top_bit.set_bit(64 * N - 1, true);
+    let mut values = vec![
+        BigUInt::new(),
+        BigUInt::from(1u8),
+        BigUInt::from(u64::MAX),
+        top_bit,
+        max(),
+    ];
+    let mut state = seed;
+    values.extend((0..4).map(|_| random_biguint(&mut state)));
+    values
+}
+
+/// The shared suite, run below for the compatibility type and for other sizes.
+fn check_arithmetic<const N: usize>(seed: u64) {
+    let one = BigUInt::<N>::from(1u8);
+    for a in samples::<N>(seed) {
+        assert_eq!(a + BigUInt::new(), a);
+        assert_eq!(a * one, a);
+        assert_eq!(a.wrapping_sub(&a), BigUInt::new());
+        assert_eq!(a.checked_sub(&(a + one)).is_none(), a != max());
+        for b in samples::<N>(seed ^ 0x9e3779b97f4a7c15) {
+            let (sum, carry) = a.overflowing_add(&b);
+            assert_eq!(sum, a + b);
+            assert_eq!(sum - b, a);
+            assert_eq!(a.checked_add(&b).is_none(), carry);
+            assert_eq!(a.checked_sub(&b).is_none(), a < b);
+            assert_eq!(a + b, b + a);
+            assert_eq!(a * b, b * a);
+
+            let (low, high) = a.overflowing_mul(&b);
+            assert_eq!(low, a * b);
+            assert_eq!(a.checked_mul(&b).is_none(), !high.is_zero());
+            if !b.is_zero() {
+                let (quotient, remainder) = low.div_rem(&b);
+                assert!(remainder < b);
+                if high.is_zero() {
+                    assert_eq!((quotient, remainder), (a, BigUInt::new()));
+                }
+            }
+
+            let mut c = a;
+            c += b;
+            c -= b;
+            c *= one;
+            assert_eq!(c, a);
+        }
+    }
+}
+
+fn check_parse_and_display<const N: usize>(seed: u64) {
+    for a in samples::<N>(seed) {
+        let text = a.to_string();
+        assert_eq!(text.len(), 2 + 16 * N);
+        assert_eq!(text.parse::<BigUInt<N>>(), Ok(a));
+        assert_eq!(text.trim_start_matches("0x").parse::<BigUInt<N>>(), Ok(a));
+    }
+    assert_eq!("".parse::<BigUInt<N>>(), Ok(BigUInt::new()));
+    assert_eq!("0x1".parse::<BigUInt<N>>(), Ok(BigUInt::from(1u8)));
+    assert!("0xg".parse::<BigUInt<N>>().is_err());
+}
+
+#[test]
+fn test_compat_type_is_64_limbs() {
+    let a = BigUInt4096::new();
+    assert_eq!(a.data.len(), 64);
+    assert_eq!(a, BigUInt4096::default());
+}
+
+#[test]
+fn test_arithmetic_all_sizes() {
+    check_arithmetic::<1>(1);
+    check_arithmetic::<2>(2);
+    check_arithmetic::<5>(3);
+    check_arithmetic::<16>(4);
+    check_arithmetic::<64>(5);
+}
+
+#[test]
+fn test_parse_and_display_all_sizes() {
+    check_parse_and_display::<1>(6);
+    check_parse_and_display::<3>(7);
+    check_parse_and_display::<16>(8);
+    check_parse_and_display::<64>(9);
+    // Short strings used to panic: the padding width assumed 64 hex digits.
+    let a: BigUInt4096 = "0x1234".parse().unwrap();
+    assert_eq!(a, BigUInt4096::from(0x1234u16));
+}
+
+#[test]
+fn test_compat_operations() {
+    let mut state = 10;
+    let a = random_biguint::<64>(&mut state);
+    let b = random_biguint::<64>(&mut state);
+    assert_eq!(a.wrapping_mul_by_u64(3), a + a + a);
+    assert_eq!(a.strict_sub(&BigUInt4096::new()), a);
+    assert_eq!(
+        BigUInt4096::from(3u8).strict_mul(&BigUInt4096::from(5u8)),
+        BigUInt4096::from(15u8)
+    );
+    assert_eq!(a.checked_mul(&b), None);
+    let (shifted, overflow) = BigUInt4096::from(1u8).widening_shl(4095);
+    assert!(shifted.bit(4095));
+    assert!(overflow.is_zero());
+}
+
+/// Embeds 16-limb operands into 64 limbs and checks that the wide operation
+/// reproduces the narrow result together with its carry, borrow or high half.
+#[test]
+fn test_embedded_16_limb_ops_agree_with_64_limb_ops() {
+    let mut state = 11;
+    for _ in 0..16 {
+        let a = random_biguint::<16>(&mut state);
+        let b = random_biguint::<16>(&mut state);
+        let (wide_a, wide_b): (BigUInt4096, BigUInt4096) = (a.widen(), b.widen());
+
+        let (sum, carry) = a.overflowing_add(&b);
+        let wide_sum = wide_a + wide_b;
+        assert_eq!(wide_sum.wrapping_narrow::<16>(), sum);
+        assert_eq!(wide_sum.data[16], carry as u64);
+        assert!(wide_sum.data[17..].iter().all(|&limb| limb == 0));
+
+        let (difference, borrow) = a.overflowing_sub(&b);
+        assert_eq!((wide_a - wide_b).wrapping_narrow::<16>(), difference);
+        assert_eq!(wide_a.checked_sub(&wide_b).is_none(), borrow);
+
+        let (low, high) = a.overflowing_mul(&b);
+        let wide_product = wide_a * wide_b;
+        assert_eq!(
+            wide_product.wrapping_narrow::<32>(),
+            BigUInt::from_halves(&low, &high)
+        );
+        assert!(wide_product.data[32..].iter().all(|&limb| limb == 0));
+
+        if !b.is_zero() {
+            let (quotient, remainder) = a.div_rem(&b);
+            assert_eq!(
+                wide_a.div_rem(&wide_b),
+                (quotient.widen(), remainder.widen())
+            );
+        }
+        assert_eq!(wide_a.cmp(&wide_b), a.cmp(&b));
+        assert_eq!(wide_a.to_string()[2 + 16 * 48..], a.to_string()[2..]);
+    }
+}
+
+/// The same property one level down, so the 64-limb type is also checked as
+/// the narrow side.
+#[test]
+fn test_embedded_64_limb_ops_agree_with_128_limb_ops() {
+    let mut state = 12;
+    for _ in 0..4 {
+        let a = random_biguint::<64>(&mut state);
+        let b = random_biguint::<64>(&mut state);
+        let (wide_a, wide_b): (BigUInt<128>, BigUInt<128>) = (a.widen(), b.widen());
+        let (sum, carry) = a.overflowing_add(&b);
+        let wide_sum = wide_a + wide_b;
+        assert_eq!(wide_sum.wrapping_narrow::<64>(), sum);
+        assert_eq!(wide_sum.data[64], carry as u64);
+        let (low, high) = a.overflowing_mul(&b);
+        assert_eq!(wide_a * wide_b, BigUInt::from_halves(&low, &high));
+    }
+}
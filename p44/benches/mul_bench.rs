@@ -0,0 +1,87 @@
+#![feature(test)]
+extern crate test;
+use p44::biguint::BigUInt;
+use p44::limb;
+use test::{black_box, Bencher};
+
+fn operand<const N: usize>(seed: u64) -> BigUInt<N> {
+    let mut result = BigUInt::<N>::new();
+    let mut state = seed;
+    for limb in result.data.iter_mut() {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        *limb = state;
+    }
+    result
+}
+
+macro_rules! mul_benches {
+    ($($name:ident, $N:expr, $method:ident);*$(;)?) => {
+        $(
+            #[bench]
+            fn $name(b: &mut Bencher) {
+                let x = black_box(operand::<$N>(1));
+                let y = black_box(operand::<$N>(2));
+                b.iter(|| {
+                    black_box(x.$method(&y, &mut BigUInt::<$N>::new()));
+                });
+            }
+        )*
+    };
+}
+
+mul_benches!(
+    bench_mul_shift_add_1024, 16, carrying_mul_shift_add;
+    bench_mul_schoolbook_1024, 16, carrying_mul_schoolbook;
+    bench_mul_karatsuba_1024, 16, carrying_mul_karatsuba;
+    bench_mul_toom3_1024, 16, carrying_mul_toom3;
+    bench_mul_1024, 16, carrying_mul;
+    bench_mul_shift_add_2048, 32, carrying_mul_shift_add;
+    bench_mul_schoolbook_2048, 32, carrying_mul_schoolbook;
+    bench_mul_karatsuba_2048, 32, carrying_mul_karatsuba;
+    bench_mul_toom3_2048, 32, carrying_mul_toom3;
+    bench_mul_2048, 32, carrying_mul;
+    bench_mul_shift_add_4096, 64, carrying_mul_shift_add;
+    bench_mul_schoolbook_4096, 64, carrying_mul_schoolbook;
+    bench_mul_karatsuba_4096, 64, carrying_mul_karatsuba;
+    bench_mul_toom3_4096, 64, carrying_mul_toom3;
+    bench_mul_4096, 64, carrying_mul;
+    bench_mul_shift_add_8192, 128, carrying_mul_shift_add;
+    bench_mul_schoolbook_8192, 128, carrying_mul_schoolbook;
+    bench_mul_karatsuba_8192, 128, carrying_mul_karatsuba;
+    bench_mul_toom3_8192, 128, carrying_mul_toom3;
+    bench_mul_8192, 128, carrying_mul;
+);
+
+/// Schoolbook products built from one multiply-accumulate row per limb, to
+/// compare the limb backends directly.
+macro_rules! row_benches {
+    ($($name:ident, $N:expr, $row:path);*$(;)?) => {
+        $(
+            #[bench]
+            fn $name(b: &mut Bencher) {
+                let x = black_box(operand::<$N>(1));
+                let y = black_box(operand::<$N>(2));
+                b.iter(|| {
+                    let mut out = [0u64; 2 * $N];
+                    for (i, &limb) in x.data.iter().enumerate() {
+                        out[i + $N] = $row(&mut out[i..i + $N], &y.data, limb);
+                    }
+                    black_box(out)
+                });
+            }
+        )*
+    };
+}
+
+row_benches!(
+    bench_rows_portable_1024, 16, limb::portable::mul_add_row;
+    bench_rows_dispatch_1024, 16, limb::mul_add_row;
+    bench_rows_portable_2048, 32, limb::portable::mul_add_row;
+    bench_rows_dispatch_2048, 32, limb::mul_add_row;
+    bench_rows_portable_4096, 64, limb::portable::mul_add_row;
+    bench_rows_dispatch_4096, 64, limb::mul_add_row;
+    bench_rows_portable_8192, 128, limb::portable::mul_add_row;
+    bench_rows_dispatch_8192, 128, limb::mul_add_row;
+);
@@ -0,0 +1,8 @@
+fn main() {
+    // Older nightlies only provide the limb intrinsics behind
+    // `#![feature(bigint_helper_methods)]`; newer toolchains reject that
+    // feature name because the methods are stable.
+    let ac = autocfg::new();
+    ac.emit_expression_cfg("1u64.carrying_mul(1, 0)", "stable_bigint_helpers");
+    autocfg::rerun_path("build.rs");
+}
@@ -0,0 +1,512 @@
+//! Fixed-width signed integers stored in two's complement over `BigUInt<N>`.
+
+use crate::biguint::{BigUInt, TryFromBigUIntError};
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::ops::{AddAssign, DivAssign, MulAssign, RemAssign, SubAssign};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BigInt<const N: usize>(BigUInt<N>);
+
+/// The error returned when parsing a `BigInt` fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseBigIntError {
+    /// The magnitude is not a valid hexadecimal number.
+    Invalid(std::num::ParseIntError),
+    /// The value does not fit in the signed range.
+    Overflow,
+}
+
+impl std::fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseBigIntError::Invalid(err) => write!(f, "{}", err),
+            ParseBigIntError::Overflow => write!(f, "number too large to fit in target type"),
+        }
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+impl From<std::num::ParseIntError> for ParseBigIntError {
+    fn from(err: std::num::ParseIntError) -> Self {
+        ParseBigIntError::Invalid(err)
+    }
+}
+
+impl<const N: usize> BigInt<N> {
+    pub const MIN: Self = {
+        let mut data = [0; N];
+        data[N - 1] = 1 << 63;
+        BigInt(BigUInt { data })
+    };
+
+    pub const MAX: Self = {
+        let mut data = [u64::MAX; N];
+        data[N - 1] = u64::MAX >> 1;
+        BigInt(BigUInt { data })
+    };
+
+    pub fn new() -> Self {
+        BigInt(BigUInt::<N>::new())
+    }
+
+    /// Reinterprets two's complement limbs as a signed value.
+    pub fn from_bits(bits: BigUInt<N>) -> Self {
+        BigInt(bits)
+    }
+
+    /// Returns the two's complement limbs.
+    pub fn to_bits(&self) -> BigUInt<N> {
+        self.0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0.data[N - 1] >> 63 == 1
+    }
+
+    pub fn is_positive(&self) -> bool {
+        !self.is_negative() && !self.0.is_zero()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Returns the absolute value as an unsigned integer; never overflows.
+    pub fn unsigned_abs(&self) -> BigUInt<N> {
+        if self.is_negative() {
+            BigUInt::<N>::new().wrapping_sub(&self.0)
+        } else {
+            self.0
+        }
+    }
+
+    /// Builds a value from a sign and magnitude, wrapping if it does not fit.
+    fn from_sign_magnitude(negative: bool, magnitude: BigUInt<N>) -> (Self, bool) {
+        let result = if negative {
+            BigInt(BigUInt::<N>::new().wrapping_sub(&magnitude))
+        } else {
+            BigInt(magnitude)
+        };
+        let overflow = !magnitude.is_zero() && result.is_negative() != negative;
+        (result, overflow)
+    }
+
+    pub fn overflowing_add(&self, other: &Self) -> (Self, bool) {
+        let result = BigInt(self.0.wrapping_add(&other.0));
+        let overflow =
+            self.is_negative() == other.is_negative() && result.is_negative() != self.is_negative();
+        (result, overflow)
+    }
+
+    pub fn wrapping_add(&self, other: &Self) -> Self {
+        let (result, _) = self.overflowing_add(other);
+        result
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let (result, overflow) = self.overflowing_add(other);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_add(&self, other: &Self) -> Self {
+        let (result, overflow) = self.overflowing_add(other);
+        if overflow {
+            panic!("Overflow in addition");
+        }
+        result
+    }
+
+    pub fn overflowing_sub(&self, other: &Self) -> (Self, bool) {
+        let result = BigInt(self.0.wrapping_sub(&other.0));
+        let overflow =
+            self.is_negative() != other.is_negative() && result.is_negative() != self.is_negative();
+        (result, overflow)
+    }
+
+    pub fn wrapping_sub(&self, other: &Self) -> Self {
+        let (result, _) = self.overflowing_sub(other);
+        result
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let (result, overflow) = self.overflowing_sub(other);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_sub(&self, other: &Self) -> Self {
+        let (result, overflow) = self.overflowing_sub(other);
+        if overflow {
+            panic!("Overflow in subtraction");
+        }
+        result
+    }
+
+    pub fn overflowing_mul(&self, other: &Self) -> (Self, bool) {
+        let (magnitude, high) = self.unsigned_abs().overflowing_mul(&other.unsigned_abs());
+        let negative = self.is_negative() != other.is_negative();
+        let (_, overflow) = BigInt::from_sign_magnitude(negative, magnitude);
+        // The low limbs of a two's complement product do not depend on the signs.
+        let result = BigInt(self.0.wrapping_mul(&other.0));
+        (result, overflow || !high.is_zero())
+    }
+
+    pub fn wrapping_mul(&self, other: &Self) -> Self {
+        BigInt(self.0.wrapping_mul(&other.0))
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let (result, overflow) = self.overflowing_mul(other);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_mul(&self, other: &Self) -> Self {
+        let (result, overflow) = self.overflowing_mul(other);
+        if overflow {
+            panic!("Overflow in multiplication");
+        }
+        result
+    }
+
+    /// Truncating division, rounding the quotient towards zero.
+    /// Panics if `other` is zero; `MIN / -1` wraps to `MIN`.
+    pub fn overflowing_div(&self, other: &Self) -> (Self, bool) {
+        let (quotient, _) = self.unsigned_abs().div_rem(&other.unsigned_abs());
+        BigInt::from_sign_magnitude(self.is_negative() != other.is_negative(), quotient)
+    }
+
+    pub fn wrapping_div(&self, other: &Self) -> Self {
+        let (result, _) = self.overflowing_div(other);
+        result
+    }
+
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        let (result, overflow) = self.overflowing_div(other);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_div(&self, other: &Self) -> Self {
+        let (result, overflow) = self.overflowing_div(other);
+        if overflow {
+            panic!("Overflow in division");
+        }
+        result
+    }
+
+    /// Remainder of truncating division, with the sign of `self`.
+    /// Panics if `other` is zero; `MIN % -1` is zero.
+    pub fn overflowing_rem(&self, other: &Self) -> (Self, bool) {
+        let (_, remainder) = self.unsigned_abs().div_rem(&other.unsigned_abs());
+        let (result, _) = BigInt::from_sign_magnitude(self.is_negative(), remainder);
+        let overflow = *self == BigInt::MIN && *other == BigInt::from(-1i8);
+        (result, overflow)
+    }
+
+    pub fn wrapping_rem(&self, other: &Self) -> Self {
+        let (result, _) = self.overflowing_rem(other);
+        result
+    }
+
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        let (result, overflow) = self.overflowing_rem(other);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_rem(&self, other: &Self) -> Self {
+        let (result, overflow) = self.overflowing_rem(other);
+        if overflow {
+            panic!("Overflow in remainder");
+        }
+        result
+    }
+
+    /// Non-negative remainder, as used for modular reduction.
+    /// Panics if `other` is zero.
+    pub fn rem_euclid(&self, other: &Self) -> Self {
+        let remainder = self.wrapping_rem(other);
+        if remainder.is_negative() {
+            BigInt(remainder.0.wrapping_add(&other.unsigned_abs()))
+        } else {
+            remainder
+        }
+    }
+
+    pub fn checked_rem_euclid(&self, other: &Self) -> Option<Self> {
+        if other.is_zero() {
+            None
+        } else {
+            Some(self.rem_euclid(other))
+        }
+    }
+
+    pub fn overflowing_neg(&self) -> (Self, bool) {
+        let result = BigInt(BigUInt::<N>::new().wrapping_sub(&self.0));
+        (result, *self == BigInt::MIN)
+    }
+
+    pub fn wrapping_neg(&self) -> Self {
+        let (result, _) = self.overflowing_neg();
+        result
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        let (result, overflow) = self.overflowing_neg();
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_neg(&self) -> Self {
+        let (result, overflow) = self.overflowing_neg();
+        if overflow {
+            panic!("Overflow in negation");
+        }
+        result
+    }
+
+    pub fn overflowing_abs(&self) -> (Self, bool) {
+        if self.is_negative() {
+            self.overflowing_neg()
+        } else {
+            (*self, false)
+        }
+    }
+
+    pub fn wrapping_abs(&self) -> Self {
+        let (result, _) = self.overflowing_abs();
+        result
+    }
+
+    pub fn checked_abs(&self) -> Option<Self> {
+        let (result, overflow) = self.overflowing_abs();
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// Absolute value. Panics for `MIN`.
+    pub fn abs(&self) -> Self {
+        let (result, overflow) = self.overflowing_abs();
+        if overflow {
+            panic!("Overflow in absolute value");
+        }
+        result
+    }
+}
+
+impl<const N: usize> PartialOrd for BigInt<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for BigInt<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            // With equal signs, two's complement order matches unsigned order.
+            _ => self.0.cmp(&other.0),
+        }
+    }
+}
+
+macro_rules! impl_from_signed {
+    ($($t:ty),*) => {
+        $(
+            impl<const N: usize> From<$t> for BigInt<N> {
+                fn from(value: $t) -> Self {
+                    let magnitude = BigUInt::<N>::from(value.unsigned_abs());
+                    let (result, _) = BigInt::from_sign_magnitude(value < 0, magnitude);
+                    result
+                }
+            }
+        )*
+    };
+}
+
+impl_from_signed!(i8, i16, i32, i64, i128);
+
+impl<const N: usize> TryFrom<BigUInt<N>> for BigInt<N> {
+    type Error = TryFromBigUIntError;
+
+    fn try_from(value: BigUInt<N>) -> Result<Self, Self::Error> {
+        let result = BigInt(value);
+        if result.is_negative() {
+            Err(TryFromBigUIntError(()))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl<const N: usize> TryFrom<BigInt<N>> for BigUInt<N> {
+    type Error = TryFromBigUIntError;
+
+    fn try_from(value: BigInt<N>) -> Result<Self, Self::Error> {
+        if value.is_negative() {
+            Err(TryFromBigUIntError(()))
+        } else {
+            Ok(value.0)
+        }
+    }
+}
+
+impl<const N: usize> FromStr for BigInt<N> {
+    type Err = ParseBigIntError;
+
+    /// Parses an optionally signed hexadecimal string such as `-0x1f`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let magnitude = digits.parse::<BigUInt<N>>()?;
+        let (result, overflow) = BigInt::from_sign_magnitude(negative, magnitude);
+        if overflow {
+            Err(ParseBigIntError::Overflow)
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl<const N: usize> std::fmt::Display for BigInt<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_negative() {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.unsigned_abs())
+    }
+}
+
+impl<const N: usize> Neg for BigInt<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.wrapping_neg()
+    }
+}
+
+impl<const N: usize> Neg for &BigInt<N> {
+    type Output = BigInt<N>;
+
+    fn neg(self) -> Self::Output {
+        self.wrapping_neg()
+    }
+}
+
+/// Implements a binary operator and its assigning form for all
+/// combinations of owned and borrowed operands.
+macro_rules! impl_binary_op {
+    ($($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $impl:ident);*$(;)?) => {
+        $(
+            impl<const N: usize> $trait for BigInt<N> {
+                type Output = Self;
+
+                fn $method(self, other: Self) -> Self::Output {
+                    self.$impl(&other)
+                }
+            }
+
+            impl<const N: usize> $trait<&Self> for BigInt<N> {
+                type Output = Self;
+
+                fn $method(self, other: &Self) -> Self::Output {
+                    self.$impl(other)
+                }
+            }
+
+            impl<const N: usize> $trait for &BigInt<N> {
+                type Output = BigInt<N>;
+
+                fn $method(self, other: Self) -> Self::Output {
+                    self.$impl(other)
+                }
+            }
+
+            impl<const N: usize> $trait<BigInt<N>> for &BigInt<N> {
+                type Output = BigInt<N>;
+
+                fn $method(self, other: BigInt<N>) -> Self::Output {
+                    self.$impl(&other)
+                }
+            }
+
+            impl<const N: usize> $assign_trait for BigInt<N> {
+                fn $assign_method(&mut self, other: Self) {
+                    *self = self.$impl(&other);
+                }
+            }
+
+            impl<const N: usize> $assign_trait<&Self> for BigInt<N> {
+                fn $assign_method(&mut self, other: &Self) {
+                    *self = self.$impl(other);
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_op!(
+    Add, add, AddAssign, add_assign, wrapping_add;
+    Sub, sub, SubAssign, sub_assign, wrapping_sub;
+    Mul, mul, MulAssign, mul_assign, wrapping_mul;
+    Div, div, DivAssign, div_assign, strict_div;
+    Rem, rem, RemAssign, rem_assign, strict_rem;
+);
+
+/// Macro to create BigInt<N> types from list of (type_name, N) pairs.
+macro_rules! new_bigints {
+    ($($type_name:ident, $N:expr);*$(;)?) => {
+        $(
+            /// Signed bigint type
+            /// ```
+            #[doc = concat!("let a = p44::bigint::", stringify!($type_name), "::from(-5i8);")]
+            #[doc = concat!("let b = p44::bigint::", stringify!($type_name), "::from(3i8);")]
+            #[doc = concat!("let c = p44::bigint::", stringify!($type_name), "::from(-2i8);")]
+            /// assert_eq!(c, a + b);
+            /// ```
+            pub type $type_name = BigInt<$N>;
+        )*
+    };
+}
+
+new_bigints!(
+    BigInt1024, 16;
+    BigInt2048, 32;
+    BigInt4096, 64;
+    BigInt8192, 128;
+);
@@ -1,7 +1,24 @@
-use std::ops::{Add, Mul, Sub};
-use std::ops::{AddAssign, MulAssign, SubAssign};
+use crate::limb;
+use std::cmp::Ordering;
+use std::num::ParseIntError;
+use std::ops::{Add, Div, Mul, Rem, Shl, Shr, Sub};
+use std::ops::{AddAssign, DivAssign, MulAssign, RemAssign, ShlAssign, ShrAssign, SubAssign};
 use std::str::FromStr;
 
+mod bytes;
+mod convert;
+pub(crate) mod div;
+mod gcd;
+pub(crate) mod modular;
+pub(crate) mod mul;
+mod pow;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod shift;
+
+pub use bytes::DerError;
+pub use convert::TryFromBigUIntError;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct BigUInt<const N: usize> {
     pub data: [u64; N],
@@ -13,31 +30,148 @@ impl<const N: usize> Default for BigUInt<N> {
     }
 }
 
+impl<const N: usize> PartialOrd for BigUInt<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for BigUInt<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.const_cmp(other)
+    }
+}
+
+/// Construction, comparison and add/sub arithmetic are `const fn`, so moduli
+/// and other constants can be computed in `const` items. Loops are written
+/// with `while`, as iterators are not available in const contexts, and limb
+/// arithmetic goes through the selected `limb` backend, whose single-limb
+/// functions are `const` in both.
 impl<const N: usize> BigUInt<N> {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         BigUInt { data: [0; N] }
     }
 
-    pub fn carrying_add(&self, other: &Self, mut carry: bool) -> (Self, bool) {
+    pub const fn from_u64(x: u64) -> Self {
+        let mut result = BigUInt::<N>::new();
+        result.data[0] = x;
+        result
+    }
+
+    /// Parses a hexadecimal literal with a `0x` prefix or a decimal literal,
+    /// ignoring `_` separators. Panics if the literal is malformed or does not
+    /// fit, which is a compile error in const contexts; see [`biguint!`](macro@crate::biguint).
+    pub const fn from_str_literal(literal: &str) -> Self {
+        let bytes = literal.as_bytes();
+        let hex = bytes.len() >= 2 && bytes[0] == b'0' && bytes[1] == b'x';
+        let (mut i, radix) = if hex { (2, 16) } else { (0, 10) };
+        if i == bytes.len() {
+            panic!("Empty BigUInt literal");
+        }
         let mut result = BigUInt::<N>::new();
+        while i < bytes.len() {
+            let byte = bytes[i];
+            i += 1;
+            let digit = match byte {
+                b'_' => continue,
+                b'0'..=b'9' => byte - b'0',
+                b'a'..=b'f' => byte - b'a' + 10,
+                b'A'..=b'F' => byte - b'A' + 10,
+                _ => panic!("Invalid digit in BigUInt literal"),
+            };
+            if digit >= radix {
+                panic!("Invalid digit in BigUInt literal");
+            }
+            let carry;
+            (result, carry) = result.carrying_mul_by_u64(radix as u64, digit as u64);
+            if carry != 0 {
+                panic!("Overflow in BigUInt literal");
+            }
+        }
+        result
+    }
 
-        for i in 0..N {
-            (result.data[i], carry) = self.data[i].carrying_add(other.data[i], carry);
+    /// `Ord::cmp` as a `const fn`.
+    pub const fn const_cmp(&self, other: &Self) -> Ordering {
+        let mut i = N;
+        while i > 0 {
+            i -= 1;
+            if self.data[i] != other.data[i] {
+                return if self.data[i] < other.data[i] {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// `PartialEq::eq` as a `const fn`.
+    pub const fn const_eq(&self, other: &Self) -> bool {
+        matches!(self.const_cmp(other), Ordering::Equal)
+    }
+
+    pub fn leading_zeros(&self) -> u32 {
+        match self.data.iter().rposition(|&limb| limb != 0) {
+            Some(i) => (64 * (N - 1 - i)) as u32 + self.data[i].leading_zeros(),
+            None => 64 * N as u32,
+        }
+    }
+
+    pub fn trailing_zeros(&self) -> u32 {
+        match self.data.iter().position(|&limb| limb != 0) {
+            Some(i) => 64 * i as u32 + self.data[i].trailing_zeros(),
+            None => 64 * N as u32,
+        }
+    }
+
+    /// Returns the number of bits needed to represent the value.
+    pub fn bits(&self) -> usize {
+        64 * N - self.leading_zeros() as usize
+    }
+
+    /// Returns bit `i`, counting from the least significant bit.
+    pub const fn bit(&self, i: usize) -> bool {
+        (self.data[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    /// Sets bit `i`, counting from the least significant bit.
+    pub const fn set_bit(&mut self, i: usize, value: bool) {
+        if value {
+            self.data[i / 64] |= 1 << (i % 64);
+        } else {
+            self.data[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    /// Returns `true` if exactly one bit is set.
+    pub fn is_power_of_two(&self) -> bool {
+        self.data.iter().map(|limb| limb.count_ones()).sum::<u32>() == 1
+    }
+
+    pub const fn carrying_add(&self, other: &Self, mut carry: bool) -> (Self, bool) {
+        let mut result = BigUInt::<N>::new();
+
+        let mut i = 0;
+        while i < N {
+            (result.data[i], carry) = limb::carrying_add(self.data[i], other.data[i], carry);
+            i += 1;
         }
 
         (result, carry)
     }
 
-    pub fn overflowing_add(&self, other: &Self) -> (Self, bool) {
+    pub const fn overflowing_add(&self, other: &Self) -> (Self, bool) {
         self.carrying_add(other, false)
     }
 
-    pub fn wrapping_add(&self, other: &Self) -> Self {
+    pub const fn wrapping_add(&self, other: &Self) -> Self {
         let (result, _) = self.overflowing_add(other);
         result
     }
 
-    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+    pub const fn checked_add(&self, other: &Self) -> Option<Self> {
         let (result, overflow) = self.overflowing_add(other);
         if overflow {
             None
@@ -46,7 +180,7 @@ impl<const N: usize> BigUInt<N> {
         }
     }
 
-    pub fn strict_add(&self, other: &Self) -> Self {
+    pub const fn strict_add(&self, other: &Self) -> Self {
         let (result, overflow) = self.overflowing_add(other);
         if overflow {
             panic!("Overflow in addition");
@@ -54,26 +188,28 @@ impl<const N: usize> BigUInt<N> {
         result
     }
 
-    pub fn borrowing_sub(&self, other: &Self, mut borrow: bool) -> (Self, bool) {
+    pub const fn borrowing_sub(&self, other: &Self, mut borrow: bool) -> (Self, bool) {
         let mut result = BigUInt::<N>::new();
 
-        for i in 0..N {
-            (result.data[i], borrow) = self.data[i].borrowing_sub(other.data[i], borrow);
+        let mut i = 0;
+        while i < N {
+            (result.data[i], borrow) = limb::borrowing_sub(self.data[i], other.data[i], borrow);
+            i += 1;
         }
 
         (result, borrow)
     }
 
-    pub fn overflowing_sub(&self, other: &Self) -> (Self, bool) {
+    pub const fn overflowing_sub(&self, other: &Self) -> (Self, bool) {
         self.borrowing_sub(other, false)
     }
 
-    pub fn wrapping_sub(&self, other: &Self) -> Self {
+    pub const fn wrapping_sub(&self, other: &Self) -> Self {
         let (result, _) = self.overflowing_sub(other);
         result
     }
 
-    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+    pub const fn checked_sub(&self, other: &Self) -> Option<Self> {
         let (result, overflow) = self.overflowing_sub(other);
         if overflow {
             None
@@ -82,7 +218,7 @@ impl<const N: usize> BigUInt<N> {
         }
     }
 
-    pub fn strict_sub(&self, other: &Self) -> Self {
+    pub const fn strict_sub(&self, other: &Self) -> Self {
         let (result, overflow) = self.overflowing_sub(other);
         if overflow {
             panic!("Overflow in subtraction");
@@ -90,21 +226,23 @@ impl<const N: usize> BigUInt<N> {
         result
     }
 
-    pub fn carrying_mul_by_u64(&self, other: u64, mut carry: u64) -> (Self, u64) {
+    pub const fn carrying_mul_by_u64(&self, other: u64, mut carry: u64) -> (Self, u64) {
         let mut result = BigUInt::<N>::new();
 
-        for i in 0..N {
-            (result.data[i], carry) = self.data[i].carrying_mul(other, carry);
+        let mut i = 0;
+        while i < N {
+            (result.data[i], carry) = limb::carrying_mul(self.data[i], other, carry);
+            i += 1;
         }
 
         (result, carry)
     }
 
-    fn overflowing_mul_by_u64(&self, other: u64) -> (Self, u64) {
+    const fn overflowing_mul_by_u64(&self, other: u64) -> (Self, u64) {
         self.carrying_mul_by_u64(other, 0)
     }
 
-    pub fn wrapping_mul_by_u64(&self, other: u64) -> Self {
+    pub const fn wrapping_mul_by_u64(&self, other: u64) -> Self {
         let (result, _) = self.overflowing_mul_by_u64(other);
         result
     }
@@ -126,41 +264,16 @@ impl<const N: usize> BigUInt<N> {
         (result, overflow)
     }
 
-    pub fn widening_shl(&self, shift_in_bits: usize) -> (Self, Self) {
-        let mut result;
-        let mut overflow;
-        let mut carry: u64 = 0;
-        let mut temp: u64;
-
-        let shift_in_blocks = shift_in_bits / 64;
-        let shift_in_bits = shift_in_bits % 64;
-
-        (result, overflow) = self.overflowing_shift_left(shift_in_blocks);
-
-        for i in 0..N {
-            temp = carry;
-            carry = result.data[i] >> (64 - shift_in_bits);
-            result.data[i] <<= shift_in_bits;
-            result.data[i] |= temp;
-        }
-        carry = 0;
-        for i in 0..N {
-            temp = carry;
-            carry = overflow.data[i] >> (64 - shift_in_bits);
-            overflow.data[i] <<= shift_in_bits;
-            overflow.data[i] |= temp;
-        }
-
-        (result, overflow)
-    }
-
     fn from_u64_shifted(block: u64, shift_in_blocks: usize) -> Self {
         let mut result = BigUInt::<N>::new();
         result.data[shift_in_blocks] = block;
         result
     }
 
-    pub fn carrying_mul(&self, other: &Self, carry: &mut Self) -> (Self, Self) {
+    /// Multiplies by shifting and adding one partial product per limb.
+    /// This is the original quadratic implementation, kept as a reference
+    /// for tests and benchmarks.
+    pub fn carrying_mul_shift_add(&self, other: &Self, carry: &mut Self) -> (Self, Self) {
         let mut result = BigUInt::<N>::new();
         let mut overflow;
         let mut temp_r: BigUInt<N>;
@@ -182,6 +295,58 @@ impl<const N: usize> BigUInt<N> {
         (result, temp_carry)
     }
 
+    /// Splits a `2 * N` limb product into low and high halves, adding `carry`.
+    fn from_product(product: &[u64], carry: &Self) -> (Self, Self) {
+        let mut low = BigUInt::<N>::new();
+        let mut high = BigUInt::<N>::new();
+        low.data.copy_from_slice(&product[..N]);
+        high.data.copy_from_slice(&product[N..]);
+        let (low, overflow) = low.overflowing_add(carry);
+        let high = high.strict_add(&BigUInt::<N>::from_u64_shifted(overflow as u64, 0));
+        (low, high)
+    }
+
+    fn carrying_mul_with(
+        &self,
+        other: &Self,
+        carry: &Self,
+        mul: fn(&mut [u64], &[u64], &[u64]),
+    ) -> (Self, Self) {
+        if N < mul::KARATSUBA_THRESHOLD {
+            // Small products fit on the stack. With `mul_into` or schoolbook
+            // nothing below the threshold allocates, which keeps field
+            // arithmetic off the heap; Karatsuba and Toom-3 still allocate
+            // their partial products.
+            let mut product = [0; 2 * mul::KARATSUBA_THRESHOLD];
+            mul(&mut product[..2 * N], &self.data, &other.data);
+            return BigUInt::<N>::from_product(&product[..2 * N], carry);
+        }
+        let mut product = vec![0; 2 * N];
+        mul(&mut product, &self.data, &other.data);
+        BigUInt::<N>::from_product(&product, carry)
+    }
+
+    /// Multiplies with the quadratic schoolbook algorithm.
+    pub fn carrying_mul_schoolbook(&self, other: &Self, carry: &mut Self) -> (Self, Self) {
+        self.carrying_mul_with(other, carry, mul::mul_schoolbook)
+    }
+
+    /// Multiplies with Karatsuba, recursing into the fastest algorithm for each half.
+    pub fn carrying_mul_karatsuba(&self, other: &Self, carry: &mut Self) -> (Self, Self) {
+        self.carrying_mul_with(other, carry, mul::mul_karatsuba)
+    }
+
+    /// Multiplies with Toom-3, recursing into the fastest algorithm for each third.
+    pub fn carrying_mul_toom3(&self, other: &Self, carry: &mut Self) -> (Self, Self) {
+        self.carrying_mul_with(other, carry, mul::mul_toom3)
+    }
+
+    /// Returns the low and high halves of `self * other + carry`, picking
+    /// schoolbook, Karatsuba or Toom-3 multiplication from `N`.
+    pub fn carrying_mul(&self, other: &Self, carry: &mut Self) -> (Self, Self) {
+        self.carrying_mul_with(other, carry, mul::mul_into)
+    }
+
     pub fn overflowing_mul(&self, other: &Self) -> (Self, Self) {
         let mut carry = BigUInt::<N>::new();
         self.carrying_mul(other, &mut carry)
@@ -210,20 +375,32 @@ impl<const N: usize> BigUInt<N> {
     }
 }
 
+/// `ParseIntError` has no public constructor, so these borrow the errors
+/// `u64` reports for the same problems.
+pub(crate) fn invalid_digit() -> ParseIntError {
+    u64::from_str_radix("-", 16).unwrap_err()
+}
+
+fn too_large() -> ParseIntError {
+    u64::from_str_radix("10000000000000000", 16).unwrap_err()
+}
+
 impl<const N: usize> FromStr for BigUInt<N> {
-    type Err = std::num::ParseIntError;
+    type Err = ParseIntError;
+
+    /// Parses a hexadecimal string with an optional `0x` prefix. Leading
+    /// zeros are ignored, and values that do not fit in `N` limbs are an error.
+    /// A `+` sign is an error too. The chunked parser this replaced let
+    /// `u64::from_str_radix` take one only where it began a 16-digit chunk.
     fn from_str(hex_str: &str) -> Result<Self, Self::Err> {
-        if hex_str.is_empty() {
-            return Ok(BigUInt::<N>::default());
-        }
+        let digits = hex_str.trim_start_matches("0x").trim_start_matches('0');
         let mut result = BigUInt::<N>::new();
-        let hex_str = hex_str.trim_start_matches("0x");
-        let hex_str = format!("{:0>64}", hex_str);
-        for i in 0..N {
-            let start = hex_str.len() - (i + 1) * 16;
-            let end = hex_str.len() - i * 16;
-            let block_str = &hex_str[start..end];
-            result.data[i] = u64::from_str_radix(block_str, 16)?;
+        for (k, byte) in digits.bytes().rev().enumerate() {
+            let digit = (byte as char).to_digit(16).ok_or_else(invalid_digit)?;
+            if k >= 16 * N {
+                return Err(too_large());
+            }
+            result.data[k / 16] |= (digit as u64) << (4 * (k % 16));
         }
         Ok(result)
     }
@@ -361,6 +538,138 @@ impl<const N: usize> MulAssign<&Self> for BigUInt<N> {
     }
 }
 
+impl<const N: usize> Div for BigUInt<N> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self::Output {
+        self.strict_div(&other)
+    }
+}
+
+impl<const N: usize> Div<&Self> for BigUInt<N> {
+    type Output = Self;
+
+    fn div(self, other: &Self) -> Self::Output {
+        self.strict_div(other)
+    }
+}
+
+impl<const N: usize> Div for &BigUInt<N> {
+    type Output = BigUInt<N>;
+
+    fn div(self, other: Self) -> Self::Output {
+        self.strict_div(other)
+    }
+}
+
+impl<const N: usize> Div<BigUInt<N>> for &BigUInt<N> {
+    type Output = BigUInt<N>;
+
+    fn div(self, other: BigUInt<N>) -> Self::Output {
+        self.strict_div(&other)
+    }
+}
+
+impl<const N: usize> DivAssign for BigUInt<N> {
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+
+impl<const N: usize> DivAssign<&Self> for BigUInt<N> {
+    fn div_assign(&mut self, other: &Self) {
+        *self = *self / *other;
+    }
+}
+
+impl<const N: usize> Rem for BigUInt<N> {
+    type Output = Self;
+
+    fn rem(self, other: Self) -> Self::Output {
+        self.strict_rem(&other)
+    }
+}
+
+impl<const N: usize> Rem<&Self> for BigUInt<N> {
+    type Output = Self;
+
+    fn rem(self, other: &Self) -> Self::Output {
+        self.strict_rem(other)
+    }
+}
+
+impl<const N: usize> Rem for &BigUInt<N> {
+    type Output = BigUInt<N>;
+
+    fn rem(self, other: Self) -> Self::Output {
+        self.strict_rem(other)
+    }
+}
+
+impl<const N: usize> Rem<BigUInt<N>> for &BigUInt<N> {
+    type Output = BigUInt<N>;
+
+    fn rem(self, other: BigUInt<N>) -> Self::Output {
+        self.strict_rem(&other)
+    }
+}
+
+impl<const N: usize> RemAssign for BigUInt<N> {
+    fn rem_assign(&mut self, other: Self) {
+        *self = *self % other;
+    }
+}
+
+impl<const N: usize> RemAssign<&Self> for BigUInt<N> {
+    fn rem_assign(&mut self, other: &Self) {
+        *self = *self % *other;
+    }
+}
+
+impl<const N: usize> Shl<usize> for BigUInt<N> {
+    type Output = Self;
+
+    fn shl(self, shift: usize) -> Self::Output {
+        self.strict_shl(shift)
+    }
+}
+
+impl<const N: usize> Shl<usize> for &BigUInt<N> {
+    type Output = BigUInt<N>;
+
+    fn shl(self, shift: usize) -> Self::Output {
+        self.strict_shl(shift)
+    }
+}
+
+impl<const N: usize> ShlAssign<usize> for BigUInt<N> {
+    fn shl_assign(&mut self, shift: usize) {
+        *self = *self << shift;
+    }
+}
+
+impl<const N: usize> Shr<usize> for BigUInt<N> {
+    type Output = Self;
+
+    fn shr(self, shift: usize) -> Self::Output {
+        self.strict_shr(shift)
+    }
+}
+
+impl<const N: usize> Shr<usize> for &BigUInt<N> {
+    type Output = BigUInt<N>;
+
+    fn shr(self, shift: usize) -> Self::Output {
+        self.strict_shr(shift)
+    }
+}
+
+impl<const N: usize> ShrAssign<usize> for BigUInt<N> {
+    fn shr_assign(&mut self, shift: usize) {
+        *self = *self >> shift;
+    }
+}
+
 impl<const N: usize> std::fmt::Display for BigUInt<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut hex_str = String::new();
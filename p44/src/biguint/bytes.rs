@@ -0,0 +1,164 @@
+//! Byte-string encodings of `BigUInt<N>`: big- and little-endian bytes in
+//! fixed, padded and minimal lengths, and DER INTEGERs.
+
+use super::{BigUInt, TryFromBigUIntError};
+
+/// ASN.1 tag of a primitive INTEGER.
+const DER_INTEGER_TAG: u8 = 0x02;
+
+/// The error returned when decoding a DER INTEGER fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DerError {
+    /// The input is not a single, complete INTEGER.
+    Malformed,
+    /// The length or value is not in its shortest form.
+    NonCanonical,
+    /// The INTEGER is negative.
+    Negative,
+    /// The value does not fit in the target type.
+    Overflow,
+}
+
+impl std::fmt::Display for DerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DerError::Malformed => write!(f, "malformed DER INTEGER"),
+            DerError::NonCanonical => write!(f, "non-canonical DER encoding"),
+            DerError::Negative => write!(f, "negative DER INTEGER"),
+            DerError::Overflow => write!(f, "number too large to fit in target type"),
+        }
+    }
+}
+
+impl std::error::Error for DerError {}
+
+impl<const N: usize> BigUInt<N> {
+    /// Returns the value as `8 * N` big-endian bytes.
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.data
+            .iter()
+            .rev()
+            .flat_map(|limb| limb.to_be_bytes())
+            .collect()
+    }
+
+    /// Returns the value as `8 * N` little-endian bytes.
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.data
+            .iter()
+            .flat_map(|limb| limb.to_le_bytes())
+            .collect()
+    }
+
+    /// Returns the big-endian bytes without leading zeros. Zero is empty.
+    pub fn to_be_bytes_minimal(&self) -> Vec<u8> {
+        let mut bytes = self.to_le_bytes_minimal();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Returns the little-endian bytes without trailing zeros. Zero is empty.
+    pub fn to_le_bytes_minimal(&self) -> Vec<u8> {
+        let mut bytes = self.to_le_bytes();
+        bytes.truncate(self.bits().div_ceil(8));
+        bytes
+    }
+
+    /// Returns exactly `len` big-endian bytes, or `None` if the value needs more.
+    pub fn to_be_bytes_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let mut bytes = self.to_le_bytes_padded(len)?;
+        bytes.reverse();
+        Some(bytes)
+    }
+
+    /// Returns exactly `len` little-endian bytes, or `None` if the value needs more.
+    pub fn to_le_bytes_padded(&self, len: usize) -> Option<Vec<u8>> {
+        if self.bits() > 8 * len {
+            return None;
+        }
+        let mut bytes = self.to_le_bytes();
+        bytes.resize(len, 0);
+        Some(bytes)
+    }
+
+    /// Decodes big-endian bytes of any length. Leading zeros are allowed,
+    /// but the value itself must fit in `N` limbs.
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self, TryFromBigUIntError> {
+        Self::from_le_byte_iter(bytes.iter().rev().copied())
+    }
+
+    /// Decodes little-endian bytes of any length. Trailing zeros are allowed,
+    /// but the value itself must fit in `N` limbs.
+    pub fn from_le_bytes(bytes: &[u8]) -> Result<Self, TryFromBigUIntError> {
+        Self::from_le_byte_iter(bytes.iter().copied())
+    }
+
+    fn from_le_byte_iter(bytes: impl Iterator<Item = u8>) -> Result<Self, TryFromBigUIntError> {
+        let mut result = BigUInt::<N>::new();
+        for (i, byte) in bytes.enumerate() {
+            if i < 8 * N {
+                result.data[i / 8] |= (byte as u64) << (8 * (i % 8));
+            } else if byte != 0 {
+                return Err(TryFromBigUIntError(()));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Encodes the value as a DER INTEGER, including tag and length.
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut content = self.to_be_bytes_minimal();
+        // INTEGERs are two's complement, so a set top bit needs a zero prefix.
+        if content.first().is_none_or(|&byte| byte & 0x80 != 0) {
+            content.insert(0, 0);
+        }
+        let mut result = vec![DER_INTEGER_TAG];
+        if content.len() < 0x80 {
+            result.push(content.len() as u8);
+        } else {
+            let len_bytes = content.len().to_be_bytes();
+            let skip = len_bytes.iter().take_while(|&&byte| byte == 0).count();
+            result.push(0x80 | (len_bytes.len() - skip) as u8);
+            result.extend_from_slice(&len_bytes[skip..]);
+        }
+        result.extend_from_slice(&content);
+        result
+    }
+
+    /// Decodes a DER INTEGER that makes up the whole input. Rejects negative
+    /// values and any encoding that BER allows but DER does not.
+    pub fn from_der(der: &[u8]) -> Result<Self, DerError> {
+        let [DER_INTEGER_TAG, first_len, rest @ ..] = der else {
+            return Err(DerError::Malformed);
+        };
+        let (len, content) = if first_len & 0x80 == 0 {
+            (*first_len as usize, rest)
+        } else {
+            let len_size = (first_len & 0x7f) as usize;
+            // 0x80 is the BER indefinite length, which DER forbids.
+            if len_size == 0 || len_size > size_of::<usize>() || len_size > rest.len() {
+                return Err(DerError::Malformed);
+            }
+            let (len_bytes, content) = rest.split_at(len_size);
+            if len_bytes[0] == 0 {
+                return Err(DerError::NonCanonical);
+            }
+            let len = len_bytes
+                .iter()
+                .fold(0usize, |len, &byte| (len << 8) | byte as usize);
+            if len < 0x80 {
+                return Err(DerError::NonCanonical);
+            }
+            (len, content)
+        };
+        if content.len() != len {
+            return Err(DerError::Malformed);
+        }
+        match content {
+            [] => Err(DerError::Malformed),
+            [first, ..] if first & 0x80 != 0 => Err(DerError::Negative),
+            [0, second, ..] if second & 0x80 == 0 => Err(DerError::NonCanonical),
+            _ => BigUInt::from_be_bytes(content).map_err(|_| DerError::Overflow),
+        }
+    }
+}
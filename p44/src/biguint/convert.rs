@@ -0,0 +1,143 @@
+//! Conversions between `BigUInt` sizes and primitive integers.
+
+use super::{mul, BigUInt};
+
+/// The error returned when a value does not fit in the target integer type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TryFromBigUIntError(pub(crate) ());
+
+impl std::fmt::Display for TryFromBigUIntError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "out of range integral type conversion attempted")
+    }
+}
+
+impl std::error::Error for TryFromBigUIntError {}
+
+impl<const N: usize> BigUInt<N> {
+    /// Zero-extends to a type with at least as many limbs.
+    pub fn widen<const M: usize>(&self) -> BigUInt<M> {
+        const { assert!(M >= N, "widen target must not be smaller than the source") };
+        let mut result = BigUInt::<M>::new();
+        result.data[..N].copy_from_slice(&self.data);
+        result
+    }
+
+    /// Keeps the low `M` limbs, discarding the rest.
+    pub fn wrapping_narrow<const M: usize>(&self) -> BigUInt<M> {
+        let mut result = BigUInt::<M>::new();
+        let len = M.min(N);
+        result.data[..len].copy_from_slice(&self.data[..len]);
+        result
+    }
+
+    /// Converts to `M` limbs, or `None` if the value does not fit.
+    pub fn checked_narrow<const M: usize>(&self) -> Option<BigUInt<M>> {
+        if self.data.iter().skip(M).any(|&limb| limb != 0) {
+            None
+        } else {
+            Some(self.wrapping_narrow())
+        }
+    }
+
+    /// Concatenates two halves of `H` limbs each into a value of `N == 2 * H` limbs.
+    pub fn from_halves<const H: usize>(low: &BigUInt<H>, high: &BigUInt<H>) -> Self {
+        const { assert!(N == 2 * H, "from_halves target must be twice the half size") };
+        let mut result = BigUInt::<N>::new();
+        result.data[..H].copy_from_slice(&low.data);
+        result.data[H..].copy_from_slice(&high.data);
+        result
+    }
+
+    /// Splits into low and high halves of `H` limbs each, where `N == 2 * H`.
+    pub fn split_halves<const H: usize>(&self) -> (BigUInt<H>, BigUInt<H>) {
+        const {
+            assert!(
+                N == 2 * H,
+                "split_halves target must be half the source size"
+            )
+        };
+        let mut low = BigUInt::<H>::new();
+        let mut high = BigUInt::<H>::new();
+        low.data.copy_from_slice(&self.data[..H]);
+        high.data.copy_from_slice(&self.data[H..]);
+        (low, high)
+    }
+
+    /// Returns the full product as a value of `M == 2 * N` limbs.
+    pub fn widening_mul<const M: usize>(&self, other: &Self) -> BigUInt<M> {
+        const {
+            assert!(
+                M == 2 * N,
+                "widening_mul target must be twice the operand size"
+            )
+        };
+        let mut result = BigUInt::<M>::new();
+        mul::mul_into(&mut result.data, &self.data, &other.data);
+        result
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl<const N: usize> From<$t> for BigUInt<N> {
+                fn from(value: $t) -> Self {
+                    const { assert!(64 * N >= <$t>::BITS as usize, "BigUInt is too small") };
+                    let mut result = BigUInt::<N>::new();
+                    let value = value as u128;
+                    result.data[0] = value as u64;
+                    if N > 1 {
+                        result.data[1] = (value >> 64) as u64;
+                    }
+                    result
+                }
+            }
+
+            impl<const N: usize> TryFrom<BigUInt<N>> for $t {
+                type Error = TryFromBigUIntError;
+
+                fn try_from(value: BigUInt<N>) -> Result<Self, Self::Error> {
+                    if value.data.iter().skip(2).any(|&limb| limb != 0) {
+                        return Err(TryFromBigUIntError(()));
+                    }
+                    let low = value.data.first().copied().unwrap_or(0) as u128;
+                    let high = value.data.get(1).copied().unwrap_or(0) as u128;
+                    <$t>::try_from(low | (high << 64)).map_err(|_| TryFromBigUIntError(()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive!(u8, u16, u32, u64, u128);
+
+/// Implements lossless `From` and checked `TryFrom` between pairs of sizes.
+macro_rules! impl_resize {
+    ($($small:expr => $large:expr),*$(,)?) => {
+        $(
+            impl From<BigUInt<$small>> for BigUInt<$large> {
+                fn from(value: BigUInt<$small>) -> Self {
+                    value.widen()
+                }
+            }
+
+            impl TryFrom<BigUInt<$large>> for BigUInt<$small> {
+                type Error = TryFromBigUIntError;
+
+                fn try_from(value: BigUInt<$large>) -> Result<Self, Self::Error> {
+                    value.checked_narrow().ok_or(TryFromBigUIntError(()))
+                }
+            }
+        )*
+    };
+}
+
+impl_resize!(
+    16 => 32,
+    16 => 64,
+    16 => 128,
+    32 => 64,
+    32 => 128,
+    64 => 128,
+);
@@ -0,0 +1,148 @@
+//! Long division on little-endian `u64` limb slices (Knuth, TAOCP vol. 2, 4.3.1 D).
+
+use super::BigUInt;
+use crate::limb;
+
+/// Returns the number of limbs up to and including the highest non-zero one.
+pub(crate) fn significant_len(x: &[u64]) -> usize {
+    x.iter().rposition(|&limb| limb != 0).map_or(0, |i| i + 1)
+}
+
+/// Returns the quotient and remainder of `u / v`, both `u.len()` limbs long.
+/// Panics if `v` is zero.
+pub(crate) fn div_rem_limbs(u: &[u64], v: &[u64]) -> (Vec<u64>, Vec<u64>) {
+    let n = significant_len(v);
+    assert!(n > 0, "Division by zero");
+    let mut quotient = vec![0; u.len()];
+    let mut remainder = vec![0; u.len()];
+    let len = significant_len(u);
+
+    if len < n {
+        remainder.copy_from_slice(u);
+        return (quotient, remainder);
+    }
+
+    if n == 1 {
+        let divisor = v[0] as u128;
+        let mut rem = 0u128;
+        for i in (0..len).rev() {
+            let cur = (rem << 64) | u[i] as u128;
+            quotient[i] = (cur / divisor) as u64;
+            rem = cur % divisor;
+        }
+        remainder[0] = rem as u64;
+        return (quotient, remainder);
+    }
+
+    // Normalize so that the top limb of the divisor has its high bit set.
+    let shift = v[n - 1].leading_zeros();
+    let vn = shl_bits(&v[..n], shift, n);
+    let mut un = shl_bits(&u[..len], shift, len + 1);
+    let top = vn[n - 1] as u128;
+    let next = vn[n - 2] as u128;
+
+    for j in (0..=len - n).rev() {
+        let num = ((un[j + n] as u128) << 64) | un[j + n - 1] as u128;
+        let mut qhat = num / top;
+        let mut rhat = num % top;
+        while qhat > u64::MAX as u128 || qhat * next > ((rhat << 64) | un[j + n - 2] as u128) {
+            qhat -= 1;
+            rhat += top;
+            if rhat > u64::MAX as u128 {
+                break;
+            }
+        }
+
+        // Multiply and subtract qhat * vn from the current window of un.
+        let mut carry = 0u128;
+        let mut borrow = false;
+        for i in 0..n {
+            let product = qhat * vn[i] as u128 + carry;
+            carry = product >> 64;
+            (un[i + j], borrow) = limb::borrowing_sub(un[i + j], product as u64, borrow);
+        }
+        (un[j + n], borrow) = limb::borrowing_sub(un[j + n], carry as u64, borrow);
+
+        // qhat was one too large: add the divisor back.
+        if borrow {
+            qhat -= 1;
+            let mut carry = false;
+            for i in 0..n {
+                (un[i + j], carry) = limb::carrying_add(un[i + j], vn[i], carry);
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u64);
+        }
+        quotient[j] = qhat as u64;
+    }
+
+    for i in 0..n {
+        remainder[i] = if shift == 0 {
+            un[i]
+        } else {
+            (un[i] >> shift) | (un[i + 1] << (64 - shift))
+        };
+    }
+    (quotient, remainder)
+}
+
+/// Returns `x << shift` (with `shift < 64`) zero-extended to `len` limbs.
+fn shl_bits(x: &[u64], shift: u32, len: usize) -> Vec<u64> {
+    let mut result = vec![0; len];
+    let mut carry = 0;
+    for (i, &limb) in x.iter().enumerate() {
+        result[i] = (limb << shift) | carry;
+        carry = if shift == 0 { 0 } else { limb >> (64 - shift) };
+    }
+    if x.len() < len {
+        result[x.len()] = carry;
+    }
+    result
+}
+
+impl<const N: usize> BigUInt<N> {
+    pub const fn is_zero(&self) -> bool {
+        let mut i = 0;
+        while i < N {
+            if self.data[i] != 0 {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    pub fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = div_rem_limbs(&self.data, &other.data);
+        let mut quotient = BigUInt::<N>::new();
+        let mut remainder = BigUInt::<N>::new();
+        quotient.data.copy_from_slice(&q);
+        remainder.data.copy_from_slice(&r);
+        Some((quotient, remainder))
+    }
+
+    pub fn div_rem(&self, other: &Self) -> (Self, Self) {
+        match self.checked_div_rem(other) {
+            Some(result) => result,
+            None => panic!("Division by zero"),
+        }
+    }
+
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(quotient, _)| quotient)
+    }
+
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(_, remainder)| remainder)
+    }
+
+    pub fn strict_div(&self, other: &Self) -> Self {
+        self.div_rem(other).0
+    }
+
+    pub fn strict_rem(&self, other: &Self) -> Self {
+        self.div_rem(other).1
+    }
+}
@@ -0,0 +1,61 @@
+//! Greatest common divisor and least common multiple of `BigUInt<N>`.
+
+use super::BigUInt;
+
+impl<const N: usize> BigUInt<N> {
+    /// Returns the greatest common divisor by Stein's binary algorithm.
+    /// `gcd(0, 0)` is zero.
+    pub fn gcd(&self, other: &Self) -> Self {
+        if self.is_zero() {
+            return *other;
+        }
+        if other.is_zero() {
+            return *self;
+        }
+        let a_zeros = self.trailing_zeros() as usize;
+        let b_zeros = other.trailing_zeros() as usize;
+        let mut a = self >> a_zeros;
+        let mut b = other >> b_zeros;
+        // Both are odd here, so their difference is even and nonzero until they meet.
+        while a != b {
+            if a > b {
+                std::mem::swap(&mut a, &mut b);
+            }
+            b = b.wrapping_sub(&a);
+            b >>= b.trailing_zeros() as usize;
+        }
+        a << a_zeros.min(b_zeros)
+    }
+
+    /// Returns the least common multiple, and whether it overflowed.
+    /// `lcm(x, 0)` is zero.
+    pub fn overflowing_lcm(&self, other: &Self) -> (Self, bool) {
+        if self.is_zero() || other.is_zero() {
+            return (BigUInt::<N>::new(), false);
+        }
+        let (low, high) = self.strict_div(&self.gcd(other)).overflowing_mul(other);
+        (low, !high.is_zero())
+    }
+
+    pub fn wrapping_lcm(&self, other: &Self) -> Self {
+        let (result, _) = self.overflowing_lcm(other);
+        result
+    }
+
+    pub fn checked_lcm(&self, other: &Self) -> Option<Self> {
+        let (result, overflow) = self.overflowing_lcm(other);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_lcm(&self, other: &Self) -> Self {
+        let (result, overflow) = self.overflowing_lcm(other);
+        if overflow {
+            panic!("Overflow in least common multiple");
+        }
+        result
+    }
+}
@@ -0,0 +1,114 @@
+//! Modular arithmetic on `BigUInt<N>`, reducing full products by long division.
+
+use super::{div, mul, BigUInt};
+
+impl<const N: usize> BigUInt<N> {
+    /// Returns `(self + other) % modulus` for operands already reduced modulo `modulus`.
+    pub fn add_mod(&self, other: &Self, modulus: &Self) -> Self {
+        let (sum, overflow) = self.overflowing_add(other);
+        if overflow || sum >= *modulus {
+            sum.wrapping_sub(modulus)
+        } else {
+            sum
+        }
+    }
+
+    /// Returns `(self - other) % modulus` for operands already reduced modulo `modulus`.
+    pub fn sub_mod(&self, other: &Self, modulus: &Self) -> Self {
+        let (difference, borrow) = self.overflowing_sub(other);
+        if borrow {
+            difference.wrapping_add(modulus)
+        } else {
+            difference
+        }
+    }
+
+    /// Returns `(self * other) % modulus`. Panics if `modulus` is zero.
+    pub fn mul_mod(&self, other: &Self, modulus: &Self) -> Self {
+        let mut product = vec![0; 2 * N];
+        mul::mul_into(&mut product, &self.data, &other.data);
+        let (_, remainder) = div::div_rem_limbs(&product, &modulus.data);
+        let mut result = BigUInt::<N>::new();
+        result.data.copy_from_slice(&remainder[..N]);
+        result
+    }
+
+    /// Returns `self.pow(exponent) % modulus` by left-to-right square and multiply.
+    /// Panics if `modulus` is zero.
+    pub fn pow_mod(&self, exponent: &Self, modulus: &Self) -> Self {
+        let base = self.strict_rem(modulus);
+        let mut result = BigUInt::<N>::from(1u8).strict_rem(modulus);
+        for i in (0..exponent.bits()).rev() {
+            result = result.mul_mod(&result, modulus);
+            if exponent.bit(i) {
+                result = result.mul_mod(&base, modulus);
+            }
+        }
+        result
+    }
+
+    /// Returns `self.pow(exponent) % modulus` for a secret `exponent`. It runs
+    /// over fixed 4-bit windows covering all of `modulus.bits()`, squaring
+    /// four times and multiplying once per window, and reads the table entry
+    /// for each window by scanning every entry with a mask, so neither the
+    /// operations nor the memory accessed depend on the exponent bits. The
+    /// division that reduces each product still takes time that depends on
+    /// its operands. Panics if `modulus` is zero or `exponent` has more bits
+    /// than `modulus`.
+    pub fn pow_mod_secret(&self, exponent: &Self, modulus: &Self) -> Self {
+        const WINDOW: usize = 4;
+        assert!(
+            exponent.bits() <= modulus.bits(),
+            "Exponent is longer than modulus"
+        );
+        let mut table = [BigUInt::<N>::from(1u8).strict_rem(modulus); 1 << WINDOW];
+        let base = self.strict_rem(modulus);
+        for i in 1..table.len() {
+            table[i] = table[i - 1].mul_mod(&base, modulus);
+        }
+        let mut result = table[0];
+        for window in (0..modulus.bits().div_ceil(WINDOW)).rev() {
+            for _ in 0..WINDOW {
+                result = result.mul_mod(&result, modulus);
+            }
+            let shift = window * WINDOW;
+            let digit = (exponent.data[shift / 64] >> (shift % 64)) as usize & (table.len() - 1);
+            let mut entry = table[0];
+            for (i, candidate) in table.iter().enumerate() {
+                entry = select(&entry, candidate, i == digit);
+            }
+            result = result.mul_mod(&entry, modulus);
+        }
+        result
+    }
+
+    /// Returns the inverse of `self` modulo `modulus` by the extended Euclidean
+    /// algorithm, or `None` if they are not coprime. Panics if `modulus` is zero.
+    pub fn inv_mod(&self, modulus: &Self) -> Option<Self> {
+        let one = BigUInt::<N>::from(1u8);
+        // Invariant: r_i = t_i * self (mod modulus), with t_i kept reduced.
+        let (mut r0, mut r1) = (*modulus, self.strict_rem(modulus));
+        let (mut t0, mut t1) = (BigUInt::<N>::new(), one.strict_rem(modulus));
+        while !r1.is_zero() {
+            let (q, r) = r0.div_rem(&r1);
+            let t = t0.sub_mod(&q.mul_mod(&t1, modulus), modulus);
+            (r0, r1) = (r1, r);
+            (t0, t1) = (t1, t);
+        }
+        if r0 == one {
+            Some(t0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns `b` if `choice` is set and `a` otherwise, without branching on `choice`.
+pub(crate) fn select<const N: usize>(a: &BigUInt<N>, b: &BigUInt<N>, choice: bool) -> BigUInt<N> {
+    let mask = (choice as u64).wrapping_neg();
+    let mut result = BigUInt::<N>::new();
+    for i in 0..N {
+        result.data[i] = a.data[i] ^ (mask & (a.data[i] ^ b.data[i]));
+    }
+    result
+}
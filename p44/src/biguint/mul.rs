@@ -0,0 +1,286 @@
+//! Full-product multiplication on little-endian `u64` limb slices.
+//!
+//! `mul_into` picks schoolbook, Karatsuba or Toom-3 from the operand length,
+//! and the recursive steps re-dispatch on the length of their sub-products.
+
+use crate::limb;
+use std::cmp::Ordering;
+
+// Both thresholds are untuned textbook values. On x86_64 with the MULX rows,
+// `mul_bench` has schoolbook ahead of Karatsuba and Toom-3 at every size it
+// covers, up to 128 limbs, so the crossovers are at least that high there.
+
+/// Operands with fewer limbs than this use the schoolbook base case.
+pub(crate) const KARATSUBA_THRESHOLD: usize = 32;
+/// Operands with at least this many limbs use Toom-3.
+pub(crate) const TOOM3_THRESHOLD: usize = 128;
+
+/// Writes `a * b` into `out`, choosing the algorithm from the operand length.
+/// `a` and `b` must have the same length and `out` must be twice as long.
+pub(crate) fn mul_into(out: &mut [u64], a: &[u64], b: &[u64]) {
+    let n = a.len();
+    if n < KARATSUBA_THRESHOLD {
+        mul_schoolbook(out, a, b);
+    } else if n < TOOM3_THRESHOLD {
+        mul_karatsuba(out, a, b);
+    } else {
+        mul_toom3(out, a, b);
+    }
+}
+
+/// Quadratic base case. `out` must hold `a.len() + b.len()` limbs.
+pub(crate) fn mul_schoolbook(out: &mut [u64], a: &[u64], b: &[u64]) {
+    debug_assert_eq!(out.len(), a.len() + b.len());
+    out.fill(0);
+    for (i, &x) in a.iter().enumerate() {
+        out[i + b.len()] = limb::mul_add_row(&mut out[i..i + b.len()], b, x);
+    }
+}
+
+/// Karatsuba multiplication of two equal-length operands.
+pub(crate) fn mul_karatsuba(out: &mut [u64], a: &[u64], b: &[u64]) {
+    let n = a.len();
+    debug_assert_eq!(b.len(), n);
+    debug_assert_eq!(out.len(), 2 * n);
+    if n < 4 {
+        return mul_schoolbook(out, a, b);
+    }
+
+    let m = n / 2;
+    let h = n - m;
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+
+    out.fill(0);
+    let mut z0 = vec![0; 2 * m];
+    mul_into(&mut z0, a0, b0);
+    let mut z2 = vec![0; 2 * h];
+    mul_into(&mut z2, a1, b1);
+
+    // (a0 + a1) * (b0 + b1) - z0 - z2 == a0 * b1 + a1 * b0
+    let sa = add_padded(a1, a0, h + 1);
+    let sb = add_padded(b1, b0, h + 1);
+    let mut z1 = vec![0; 2 * (h + 1)];
+    mul_into(&mut z1, &sa, &sb);
+    sub_assign(&mut z1, &z0);
+    sub_assign(&mut z1, &z2);
+
+    add_at(out, 0, &z0);
+    add_at(out, m, &z1);
+    add_at(out, 2 * m, &z2);
+}
+
+/// Toom-3 multiplication of two equal-length operands, evaluating at
+/// 0, 1, -1, -2 and infinity and interpolating with Bodrato's sequence.
+pub(crate) fn mul_toom3(out: &mut [u64], a: &[u64], b: &[u64]) {
+    let n = a.len();
+    debug_assert_eq!(b.len(), n);
+    debug_assert_eq!(out.len(), 2 * n);
+    if n < 9 {
+        return mul_karatsuba(out, a, b);
+    }
+
+    let k = n.div_ceil(3);
+    let (a0, a1, a2) = (&a[..k], &a[k..2 * k], &a[2 * k..]);
+    let (b0, b1, b2) = (&b[..k], &b[k..2 * k], &b[2 * k..]);
+
+    let r0 = Signed::mul(&Signed::from(a0), &Signed::from(b0));
+    let r_inf = Signed::mul(&Signed::from(a2), &Signed::from(b2));
+    let (pa1, pam1, pam2) = evaluate(a0, a1, a2);
+    let (pb1, pbm1, pbm2) = evaluate(b0, b1, b2);
+    let r1 = Signed::mul(&pa1, &pb1);
+    let rm1 = Signed::mul(&pam1, &pbm1);
+    let rm2 = Signed::mul(&pam2, &pbm2);
+
+    let mut r3 = rm2.sub(&r1).div3();
+    let r1 = r1.sub(&rm1).div2();
+    let r2 = rm1.sub(&r0);
+    r3 = r2.sub(&r3).div2().add(&r_inf.shl1());
+    let r2 = r2.add(&r1).sub(&r_inf);
+    let r1 = r1.sub(&r3);
+
+    out.fill(0);
+    for (i, r) in [r0, r1, r2, r3, r_inf].iter().enumerate() {
+        debug_assert!(!r.neg || r.is_zero());
+        add_at(out, i * k, &r.mag);
+    }
+}
+
+/// Returns the values of `p0 + p1 x + p2 x^2` at 1, -1 and -2.
+fn evaluate(p0: &[u64], p1: &[u64], p2: &[u64]) -> (Signed, Signed, Signed) {
+    let p0 = Signed::from(p0);
+    let p1 = Signed::from(p1);
+    let p2 = Signed::from(p2);
+    let t = p0.add(&p2);
+    let at1 = t.add(&p1);
+    let atm1 = t.sub(&p1);
+    let atm2 = atm1.add(&p2).shl1().sub(&p0);
+    (at1, atm1, atm2)
+}
+
+/// Returns `x + y` zero-extended to `len` limbs. `x` must be at least as long as `y`.
+fn add_padded(x: &[u64], y: &[u64], len: usize) -> Vec<u64> {
+    let mut result = vec![0; len];
+    result[..x.len()].copy_from_slice(x);
+    add_at(&mut result, 0, y);
+    result
+}
+
+/// Adds `x` into `acc` starting at limb `offset`, propagating the carry.
+/// Limbs of `x` that fall past the end of `acc` must be zero.
+pub(crate) fn add_at(acc: &mut [u64], offset: usize, x: &[u64]) {
+    let mut carry = false;
+    let mut i = offset;
+    for &limb in x {
+        if i >= acc.len() {
+            debug_assert!(limb == 0 && !carry);
+            return;
+        }
+        (acc[i], carry) = limb::carrying_add(acc[i], limb, carry);
+        i += 1;
+    }
+    while carry && i < acc.len() {
+        (acc[i], carry) = acc[i].overflowing_add(1);
+        i += 1;
+    }
+    debug_assert!(!carry);
+}
+
+/// Subtracts `x` from `acc` in place. `acc` must not be smaller than `x`.
+pub(crate) fn sub_assign(acc: &mut [u64], x: &[u64]) {
+    let mut borrow = false;
+    let mut i = 0;
+    for &limb in x {
+        if i >= acc.len() {
+            debug_assert!(limb == 0 && !borrow);
+            return;
+        }
+        (acc[i], borrow) = limb::borrowing_sub(acc[i], limb, borrow);
+        i += 1;
+    }
+    while borrow && i < acc.len() {
+        (acc[i], borrow) = acc[i].overflowing_sub(1);
+        i += 1;
+    }
+    debug_assert!(!borrow);
+}
+
+fn cmp_limbs(x: &[u64], y: &[u64]) -> Ordering {
+    let len = x.len().max(y.len());
+    for i in (0..len).rev() {
+        let a = x.get(i).copied().unwrap_or(0);
+        let b = y.get(i).copied().unwrap_or(0);
+        match a.cmp(&b) {
+            Ordering::Equal => continue,
+            ord => return ord,
+        }
+    }
+    Ordering::Equal
+}
+
+/// Sign-magnitude intermediate value used by the Toom-3 interpolation.
+#[derive(Clone, Debug)]
+struct Signed {
+    neg: bool,
+    mag: Vec<u64>,
+}
+
+impl From<&[u64]> for Signed {
+    fn from(limbs: &[u64]) -> Self {
+        Signed {
+            neg: false,
+            mag: limbs.to_vec(),
+        }
+    }
+}
+
+impl Signed {
+    fn is_zero(&self) -> bool {
+        self.mag.iter().all(|&limb| limb == 0)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        if self.neg == other.neg {
+            let (long, short) = if self.mag.len() >= other.mag.len() {
+                (&self.mag, &other.mag)
+            } else {
+                (&other.mag, &self.mag)
+            };
+            Signed {
+                neg: self.neg,
+                mag: add_padded(long, short, long.len() + 1),
+            }
+        } else {
+            let (big, small, neg) = match cmp_limbs(&self.mag, &other.mag) {
+                Ordering::Less => (&other.mag, &self.mag, other.neg),
+                _ => (&self.mag, &other.mag, self.neg),
+            };
+            let mut mag = big.clone();
+            sub_assign(&mut mag, small);
+            Signed { neg, mag }
+        }
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.add(&Signed {
+            neg: !other.neg,
+            mag: other.mag.clone(),
+        })
+    }
+
+    fn shl1(&self) -> Self {
+        let mut mag = Vec::with_capacity(self.mag.len() + 1);
+        let mut carry = 0;
+        for &limb in &self.mag {
+            mag.push((limb << 1) | carry);
+            carry = limb >> 63;
+        }
+        mag.push(carry);
+        Signed { neg: self.neg, mag }
+    }
+
+    fn div2(&self) -> Self {
+        let mut mag = self.mag.clone();
+        let mut carry = 0;
+        for limb in mag.iter_mut().rev() {
+            let next = *limb << 63;
+            *limb = (*limb >> 1) | carry;
+            carry = next;
+        }
+        debug_assert_eq!(carry, 0);
+        Signed { neg: self.neg, mag }
+    }
+
+    fn div3(&self) -> Self {
+        let mut mag = self.mag.clone();
+        let mut rem = 0u128;
+        for limb in mag.iter_mut().rev() {
+            let cur = (rem << 64) | *limb as u128;
+            *limb = (cur / 3) as u64;
+            rem = cur % 3;
+        }
+        debug_assert_eq!(rem, 0);
+        Signed { neg: self.neg, mag }
+    }
+
+    fn significant_len(&self) -> usize {
+        self.mag
+            .iter()
+            .rposition(|&limb| limb != 0)
+            .map_or(0, |i| i + 1)
+    }
+
+    fn mul(x: &Self, y: &Self) -> Self {
+        let len = x.significant_len().max(y.significant_len());
+        let mut a = x.mag.clone();
+        let mut b = y.mag.clone();
+        a.resize(len, 0);
+        b.resize(len, 0);
+        let mut mag = vec![0; 2 * len];
+        mul_into(&mut mag, &a, &b);
+        Signed {
+            neg: x.neg != y.neg,
+            mag,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Powers, integer roots and powers of two for `BigUInt<N>`.
+
+use super::BigUInt;
+
+impl<const N: usize> BigUInt<N> {
+    /// Returns `self.pow(exponent)` wrapped to `N` limbs, and whether it overflowed.
+    pub fn overflowing_pow(&self, exponent: u32) -> (Self, bool) {
+        let mut result = BigUInt::<N>::from(1u8);
+        let mut overflow = false;
+        // Left to right, no intermediate power exceeds the final one, so an
+        // overflow anywhere means the exact result overflows too.
+        for i in (0..u32::BITS - exponent.leading_zeros()).rev() {
+            let (low, high) = result.overflowing_mul(&result);
+            overflow |= !high.is_zero();
+            result = low;
+            if (exponent >> i) & 1 == 1 {
+                let (low, high) = result.overflowing_mul(self);
+                overflow |= !high.is_zero();
+                result = low;
+            }
+        }
+        (result, overflow)
+    }
+
+    pub fn wrapping_pow(&self, exponent: u32) -> Self {
+        let (result, _) = self.overflowing_pow(exponent);
+        result
+    }
+
+    pub fn checked_pow(&self, exponent: u32) -> Option<Self> {
+        let (result, overflow) = self.overflowing_pow(exponent);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_pow(&self, exponent: u32) -> Self {
+        let (result, overflow) = self.overflowing_pow(exponent);
+        if overflow {
+            panic!("Overflow in exponentiation");
+        }
+        result
+    }
+
+    /// Returns the largest `r` with `r * r <= self`.
+    pub fn isqrt(&self) -> Self {
+        self.nth_root(2)
+    }
+
+    /// Returns the largest `r` with `r.pow(n) <= self`, or `None` if `n` is zero.
+    pub fn checked_nth_root(&self, n: u32) -> Option<Self> {
+        if n == 0 {
+            return None;
+        }
+        if n == 1 || self.is_zero() {
+            return Some(*self);
+        }
+        if n as usize >= self.bits() {
+            return Some(BigUInt::<N>::from(1u8));
+        }
+        // Newton's iteration decreases monotonically from any start above the root.
+        let mut x = BigUInt::<N>::new();
+        x.set_bit(self.bits().div_ceil(n as usize), true);
+        let n_big = BigUInt::<N>::from(n);
+        loop {
+            // x^(n-1) may overflow once x is far above the root; the quotient is then zero.
+            let quotient = match x.checked_pow(n - 1) {
+                Some(power) => self.strict_div(&power),
+                None => BigUInt::<N>::new(),
+            };
+            let y = x
+                .wrapping_mul_by_u64(n as u64 - 1)
+                .strict_add(&quotient)
+                .strict_div(&n_big);
+            if y >= x {
+                return Some(x);
+            }
+            x = y;
+        }
+    }
+
+    /// Returns the largest `r` with `r.pow(n) <= self`. Panics if `n` is zero.
+    pub fn nth_root(&self, n: u32) -> Self {
+        match self.checked_nth_root(n) {
+            Some(result) => result,
+            None => panic!("Root of order zero"),
+        }
+    }
+
+    /// Returns the smallest power of two not below `self`, or `None` if it
+    /// does not fit in `N` limbs.
+    pub fn checked_next_power_of_two(&self) -> Option<Self> {
+        if self.is_power_of_two() {
+            return Some(*self);
+        }
+        let mut result = BigUInt::<N>::new();
+        let bits = self.bits();
+        if bits == 64 * N {
+            return None;
+        }
+        result.set_bit(bits, true);
+        Some(result)
+    }
+
+    /// Like `checked_next_power_of_two`, but returns zero on overflow.
+    pub fn wrapping_next_power_of_two(&self) -> Self {
+        self.checked_next_power_of_two().unwrap_or_default()
+    }
+
+    pub fn strict_next_power_of_two(&self) -> Self {
+        match self.checked_next_power_of_two() {
+            Some(result) => result,
+            None => panic!("Overflow in next power of two"),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Optional serde support. Human-readable formats use the `0x`-prefixed hex
+//! string of `Display`; binary formats use the minimal big-endian bytes.
+
+use super::BigUInt;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+impl<const N: usize> Serialize for BigUInt<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.to_be_bytes_minimal())
+        }
+    }
+}
+
+struct BigUIntVisitor<const N: usize>;
+
+impl<const N: usize> Visitor<'_> for BigUIntVisitor<N> {
+    type Value = BigUInt<N>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "a hex string or big-endian bytes of at most {} bytes",
+            8 * N
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        let digits = value.trim_start_matches("0x").trim_start_matches('0');
+        if digits.len() > 16 * N {
+            return Err(E::invalid_length(digits.len(), &self));
+        }
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        BigUInt::from_be_bytes(value).map_err(E::custom)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for BigUInt<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BigUIntVisitor)
+        } else {
+            deserializer.deserialize_bytes(BigUIntVisitor)
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Shifts and rotations of `BigUInt<N>` by bit counts.
+//!
+//! As for the primitive integers, shifting by `64 * N` bits or more is an
+//! overflow: `overflowing_*` and `wrapping_*` reduce the count modulo
+//! `64 * N`, `checked_*` return `None` and `strict_*` and the `<<` and `>>`
+//! operators panic. The `widening_*` shifts keep every bit instead.
+
+use super::BigUInt;
+
+impl<const N: usize> BigUInt<N> {
+    /// Returns `self >> shift` for `shift < 64 * N`.
+    fn shr_bits(&self, shift: usize) -> Self {
+        let blocks = shift / 64;
+        let bits = shift % 64;
+        let mut result = BigUInt::<N>::new();
+        for i in 0..N - blocks {
+            result.data[i] = self.data[i + blocks] >> bits;
+            if bits > 0 && i + blocks + 1 < N {
+                result.data[i] |= self.data[i + blocks + 1] << (64 - bits);
+            }
+        }
+        result
+    }
+
+    /// Returns `self << shift` for `shift < 64 * N`, dropping the bits shifted out.
+    fn shl_bits(&self, shift: usize) -> Self {
+        let blocks = shift / 64;
+        let bits = shift % 64;
+        let mut result = BigUInt::<N>::new();
+        for i in blocks..N {
+            result.data[i] = self.data[i - blocks] << bits;
+            if bits > 0 && i > blocks {
+                result.data[i] |= self.data[i - blocks - 1] >> (64 - bits);
+            }
+        }
+        result
+    }
+
+    /// Returns `self << (shift % (64 * N))`, and whether `shift` was at least `64 * N`.
+    pub fn overflowing_shl(&self, shift: usize) -> (Self, bool) {
+        (self.shl_bits(shift % (64 * N)), shift >= 64 * N)
+    }
+
+    pub fn wrapping_shl(&self, shift: usize) -> Self {
+        let (result, _) = self.overflowing_shl(shift);
+        result
+    }
+
+    pub fn checked_shl(&self, shift: usize) -> Option<Self> {
+        let (result, overflow) = self.overflowing_shl(shift);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_shl(&self, shift: usize) -> Self {
+        let (result, overflow) = self.overflowing_shl(shift);
+        if overflow {
+            panic!("Overflow in shift left");
+        }
+        result
+    }
+
+    /// Returns `self >> (shift % (64 * N))`, and whether `shift` was at least `64 * N`.
+    pub fn overflowing_shr(&self, shift: usize) -> (Self, bool) {
+        (self.shr_bits(shift % (64 * N)), shift >= 64 * N)
+    }
+
+    pub fn wrapping_shr(&self, shift: usize) -> Self {
+        let (result, _) = self.overflowing_shr(shift);
+        result
+    }
+
+    pub fn checked_shr(&self, shift: usize) -> Option<Self> {
+        let (result, overflow) = self.overflowing_shr(shift);
+        if overflow {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    pub fn strict_shr(&self, shift: usize) -> Self {
+        let (result, overflow) = self.overflowing_shr(shift);
+        if overflow {
+            panic!("Overflow in shift right");
+        }
+        result
+    }
+
+    /// Returns `self << shift` as the low and high halves of a `2N`-limb
+    /// value. Bits shifted past `128 * N` are dropped.
+    pub fn widening_shl(&self, shift: usize) -> (Self, Self) {
+        let blocks = shift / 64;
+        let bits = shift % 64;
+        // Limb `j` of the shifted value, combining the two source limbs that
+        // overlap it. A zero `bits` must not shift by 64.
+        let limb = |j: usize| -> u64 {
+            let Some(i) = j.checked_sub(blocks) else {
+                return 0;
+            };
+            let mut result = if i < N { self.data[i] << bits } else { 0 };
+            if bits > 0 && (1..=N).contains(&i) {
+                result |= self.data[i - 1] >> (64 - bits);
+            }
+            result
+        };
+        let mut result = BigUInt::<N>::new();
+        let mut overflow = BigUInt::<N>::new();
+        for j in 0..N {
+            result.data[j] = limb(j);
+            overflow.data[j] = limb(N + j);
+        }
+        (result, overflow)
+    }
+
+    /// Returns `self >> shift` together with the bits shifted out, which
+    /// are the high half of `(self << 64 * N) >> shift`. Bits shifted past
+    /// that low half are dropped.
+    pub fn widening_shr(&self, shift: usize) -> (Self, Self) {
+        if shift <= 64 * N {
+            let (underflow, result) = self.widening_shl(64 * N - shift);
+            (result, underflow)
+        } else {
+            let underflow = self.checked_shr(shift - 64 * N).unwrap_or_default();
+            (BigUInt::<N>::new(), underflow)
+        }
+    }
+
+    /// Rotates left by `shift % (64 * N)` bits.
+    pub fn rotate_left(&self, shift: usize) -> Self {
+        let (mut result, overflow) = self.widening_shl(shift % (64 * N));
+        for (limb, wrapped) in result.data.iter_mut().zip(overflow.data) {
+            *limb |= wrapped;
+        }
+        result
+    }
+
+    /// Rotates right by `shift % (64 * N)` bits.
+    pub fn rotate_right(&self, shift: usize) -> Self {
+        self.rotate_left(64 * N - shift % (64 * N))
+    }
+}
@@ -0,0 +1,300 @@
+//! Arbitrary-precision unsigned integers on the heap, sharing the limb
+//! algorithms of the fixed-width `BigUInt<N>`.
+
+use crate::biguint::{div, invalid_digit, mul, BigUInt, TryFromBigUIntError};
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+use std::ops::{AddAssign, DivAssign, MulAssign, RemAssign, SubAssign};
+use std::str::FromStr;
+
+/// An unsigned integer that grows as needed and never overflows.
+/// The limbs are little-endian and never have trailing zero limbs,
+/// so zero has no limbs at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DynUInt {
+    data: Vec<u64>,
+}
+
+impl DynUInt {
+    pub fn new() -> Self {
+        DynUInt { data: Vec::new() }
+    }
+
+    /// Creates a value from little-endian limbs, dropping leading zero limbs.
+    pub fn from_limbs(limbs: &[u64]) -> Self {
+        let mut result = DynUInt {
+            data: limbs.to_vec(),
+        };
+        result.normalize();
+        result
+    }
+
+    /// Returns the little-endian limbs, without leading zero limbs.
+    pub fn limbs(&self) -> &[u64] {
+        &self.data
+    }
+
+    fn normalize(&mut self) {
+        self.data.truncate(div::significant_len(&self.data));
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the number of bits needed to represent the value.
+    pub fn bits(&self) -> usize {
+        match self.data.last() {
+            Some(top) => 64 * self.data.len() - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// Returns bit `i`, counting from the least significant bit.
+    pub fn bit(&self, i: usize) -> bool {
+        self.data
+            .get(i / 64)
+            .is_some_and(|limb| (limb >> (i % 64)) & 1 == 1)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if *self < *other {
+            return None;
+        }
+        let mut result = self.clone();
+        mul::sub_assign(&mut result.data, &other.data);
+        result.normalize();
+        Some(result)
+    }
+
+    pub fn strict_sub(&self, other: &Self) -> Self {
+        match self.checked_sub(other) {
+            Some(result) => result,
+            None => panic!("Overflow in subtraction"),
+        }
+    }
+
+    pub fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div::div_rem_limbs(&self.data, &other.data);
+        Some((
+            DynUInt::from_limbs(&quotient),
+            DynUInt::from_limbs(&remainder),
+        ))
+    }
+
+    pub fn div_rem(&self, other: &Self) -> (Self, Self) {
+        match self.checked_div_rem(other) {
+            Some(result) => result,
+            None => panic!("Division by zero"),
+        }
+    }
+
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(quotient, _)| quotient)
+    }
+
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(_, remainder)| remainder)
+    }
+
+    /// Returns `self.pow(exponent) % modulus`. Panics if `modulus` is zero.
+    pub fn pow_mod(&self, exponent: &Self, modulus: &Self) -> Self {
+        let base = self % modulus;
+        let mut result = &DynUInt::from(1u8) % modulus;
+        for i in (0..exponent.bits()).rev() {
+            result = &(&result * &result) % modulus;
+            if exponent.bit(i) {
+                result = &(&result * &base) % modulus;
+            }
+        }
+        result
+    }
+
+    /// Converts to a fixed-width `BigUInt<N>`, or `None` if the value does not fit.
+    pub fn to_biguint<const N: usize>(&self) -> Option<BigUInt<N>> {
+        if self.data.len() > N {
+            return None;
+        }
+        let mut result = BigUInt::<N>::new();
+        result.data[..self.data.len()].copy_from_slice(&self.data);
+        Some(result)
+    }
+
+    fn add_impl(&self, other: &Self) -> Self {
+        let (long, short) = if self.data.len() >= other.data.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut data = long.data.clone();
+        data.push(0);
+        mul::add_at(&mut data, 0, &short.data);
+        DynUInt::from_limbs(&data)
+    }
+
+    fn mul_impl(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return DynUInt::new();
+        }
+        // The multiplication kernels expect operands of equal length.
+        let len = self.data.len().max(other.data.len());
+        let mut a = self.data.clone();
+        let mut b = other.data.clone();
+        a.resize(len, 0);
+        b.resize(len, 0);
+        let mut product = vec![0; 2 * len];
+        mul::mul_into(&mut product, &a, &b);
+        DynUInt::from_limbs(&product)
+    }
+}
+
+impl PartialOrd for DynUInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DynUInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Normalized values with more limbs are larger.
+        self.data
+            .len()
+            .cmp(&other.data.len())
+            .then_with(|| self.data.iter().rev().cmp(other.data.iter().rev()))
+    }
+}
+
+impl<const N: usize> From<BigUInt<N>> for DynUInt {
+    fn from(value: BigUInt<N>) -> Self {
+        DynUInt::from_limbs(&value.data)
+    }
+}
+
+impl<const N: usize> From<&BigUInt<N>> for DynUInt {
+    fn from(value: &BigUInt<N>) -> Self {
+        DynUInt::from_limbs(&value.data)
+    }
+}
+
+impl<const N: usize> TryFrom<DynUInt> for BigUInt<N> {
+    type Error = TryFromBigUIntError;
+
+    fn try_from(value: DynUInt) -> Result<Self, Self::Error> {
+        value.to_biguint().ok_or(TryFromBigUIntError(()))
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for DynUInt {
+                fn from(value: $t) -> Self {
+                    let value = value as u128;
+                    DynUInt::from_limbs(&[value as u64, (value >> 64) as u64])
+                }
+            }
+
+            impl TryFrom<DynUInt> for $t {
+                type Error = TryFromBigUIntError;
+
+                fn try_from(value: DynUInt) -> Result<Self, Self::Error> {
+                    let value: BigUInt<2> = value.try_into()?;
+                    value.try_into()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive!(u8, u16, u32, u64, u128);
+
+impl FromStr for DynUInt {
+    type Err = std::num::ParseIntError;
+
+    /// Parses a hexadecimal string with an optional `0x` prefix.
+    fn from_str(hex_str: &str) -> Result<Self, Self::Err> {
+        let digits = hex_str.trim_start_matches("0x");
+        let mut data = vec![0u64; digits.len().div_ceil(16)];
+        for (k, byte) in digits.bytes().rev().enumerate() {
+            let digit = (byte as char).to_digit(16).ok_or_else(invalid_digit)?;
+            data[k / 16] |= (digit as u64) << (4 * (k % 16));
+        }
+        Ok(DynUInt::from_limbs(&data))
+    }
+}
+
+impl std::fmt::Display for DynUInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((top, rest)) = self.data.split_last() else {
+            return write!(f, "0x0");
+        };
+        write!(f, "0x{:x}", top)?;
+        for limb in rest.iter().rev() {
+            write!(f, "{:016x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+/// Implements a binary operator and its assigning form for all
+/// combinations of owned and borrowed operands.
+macro_rules! impl_binary_op {
+    ($($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $impl:expr);*$(;)?) => {
+        $(
+            impl $trait<&DynUInt> for &DynUInt {
+                type Output = DynUInt;
+
+                fn $method(self, other: &DynUInt) -> Self::Output {
+                    $impl(self, other)
+                }
+            }
+
+            impl $trait for DynUInt {
+                type Output = Self;
+
+                fn $method(self, other: Self) -> Self::Output {
+                    $impl(&self, &other)
+                }
+            }
+
+            impl $trait<&Self> for DynUInt {
+                type Output = Self;
+
+                fn $method(self, other: &Self) -> Self::Output {
+                    $impl(&self, other)
+                }
+            }
+
+            impl $trait<DynUInt> for &DynUInt {
+                type Output = DynUInt;
+
+                fn $method(self, other: DynUInt) -> Self::Output {
+                    $impl(self, &other)
+                }
+            }
+
+            impl $assign_trait for DynUInt {
+                fn $assign_method(&mut self, other: Self) {
+                    *self = $impl(self, &other);
+                }
+            }
+
+            impl $assign_trait<&Self> for DynUInt {
+                fn $assign_method(&mut self, other: &Self) {
+                    *self = $impl(self, other);
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_op!(
+    Add, add, AddAssign, add_assign, DynUInt::add_impl;
+    Sub, sub, SubAssign, sub_assign, DynUInt::strict_sub;
+    Mul, mul, MulAssign, mul_assign, DynUInt::mul_impl;
+    Div, div, DivAssign, div_assign, |a: &DynUInt, b: &DynUInt| a.div_rem(b).0;
+    Rem, rem, RemAssign, rem_assign, |a: &DynUInt, b: &DynUInt| a.div_rem(b).1;
+);
@@ -0,0 +1,293 @@
+//! Short-Weierstrass curves `y^2 = x^3 + ax + b` over `FieldElement`s.
+//!
+//! Points use projective coordinates `(X : Y : Z)` with `x = X / Z` and
+//! `y = Y / Z`, and the complete addition formulas of Renes, Costello and
+//! Batina ("Complete addition formulas for prime order elliptic curves",
+//! 2016), which have no special cases for doubling or the identity. Scalar
+//! multiplication is a Montgomery ladder over every scalar bit, so its
+//! running time does not depend on the scalar.
+
+use crate::biguint::BigUInt;
+use crate::field::{FieldElement, FieldParams};
+use std::fmt::Debug;
+use std::ops::{Add, Neg, Sub};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EcError {
+    /// The coordinates or encoding do not describe a point on the curve.
+    InvalidPoint,
+    /// The scalar is zero or not below the group order.
+    InvalidScalar,
+    /// The signature does not match the message.
+    InvalidSignature,
+}
+
+impl std::fmt::Display for EcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EcError::InvalidPoint => write!(f, "invalid curve point"),
+            EcError::InvalidScalar => write!(f, "invalid scalar"),
+            EcError::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl std::error::Error for EcError {}
+
+/// A prime-order short-Weierstrass curve, given as a marker type.
+pub trait Curve<const N: usize>: Copy + Debug + Eq {
+    /// The field the coordinates live in.
+    type Base: FieldParams<N>;
+    /// The field of scalars, whose modulus is the group order.
+    type Scalar: FieldParams<N>;
+    const A: BigUInt<N>;
+    const B: BigUInt<N>;
+    /// Affine coordinates of the generator.
+    const GENERATOR: (BigUInt<N>, BigUInt<N>);
+}
+
+type Coordinate<C, const N: usize> = FieldElement<<C as Curve<N>>::Base, N>;
+
+/// A point in projective coordinates. The identity is `(0 : 1 : 0)`.
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectivePoint<C: Curve<N>, const N: usize> {
+    x: Coordinate<C, N>,
+    y: Coordinate<C, N>,
+    z: Coordinate<C, N>,
+}
+
+impl<C: Curve<N>, const N: usize> ProjectivePoint<C, N> {
+    pub fn identity() -> Self {
+        ProjectivePoint {
+            x: FieldElement::zero(),
+            y: FieldElement::one(),
+            z: FieldElement::zero(),
+        }
+    }
+
+    pub fn generator() -> Self {
+        let (x, y) = C::GENERATOR;
+        Self::from_affine(&x, &y).expect("curve generator must be on the curve")
+    }
+
+    /// Returns the point with affine coordinates `(x, y)`, checking that it
+    /// is on the curve.
+    pub fn from_affine(x: &BigUInt<N>, y: &BigUInt<N>) -> Result<Self, EcError> {
+        let x = FieldElement::from_biguint(x).ok_or(EcError::InvalidPoint)?;
+        let y = FieldElement::from_biguint(y).ok_or(EcError::InvalidPoint)?;
+        let a = FieldElement::from_biguint_reduced(&C::A);
+        let b = FieldElement::from_biguint_reduced(&C::B);
+        if y.square() != (x.square() + a) * x + b {
+            return Err(EcError::InvalidPoint);
+        }
+        Ok(ProjectivePoint {
+            x,
+            y,
+            z: FieldElement::one(),
+        })
+    }
+
+    /// Returns the affine coordinates, or `None` for the identity.
+    pub fn to_affine(&self) -> Option<(BigUInt<N>, BigUInt<N>)> {
+        let z_inv = self.z.invert()?;
+        Some(((self.x * z_inv).to_biguint(), (self.y * z_inv).to_biguint()))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// Decodes an uncompressed SEC1 point `0x04 || x || y`, with each
+    /// coordinate as `8 * N` big-endian bytes.
+    pub fn from_uncompressed(bytes: &[u8]) -> Result<Self, EcError> {
+        match bytes.split_first() {
+            Some((0x04, coordinates)) if coordinates.len() == 16 * N => {
+                let (x, y) = coordinates.split_at(8 * N);
+                let x = BigUInt::from_be_bytes(x).map_err(|_| EcError::InvalidPoint)?;
+                let y = BigUInt::from_be_bytes(y).map_err(|_| EcError::InvalidPoint)?;
+                Self::from_affine(&x, &y)
+            }
+            _ => Err(EcError::InvalidPoint),
+        }
+    }
+
+    /// Encodes as an uncompressed SEC1 point, or `None` for the identity.
+    pub fn to_uncompressed(&self) -> Option<Vec<u8>> {
+        let (x, y) = self.to_affine()?;
+        let mut result = vec![0x04];
+        result.extend_from_slice(&x.to_be_bytes());
+        result.extend_from_slice(&y.to_be_bytes());
+        Some(result)
+    }
+
+    /// Returns `a` and `3 * b` as field elements.
+    fn formula_constants() -> (Coordinate<C, N>, Coordinate<C, N>) {
+        let b = FieldElement::from_biguint_reduced(&C::B);
+        (FieldElement::from_biguint_reduced(&C::A), b + b + b)
+    }
+
+    /// Complete addition, Algorithm 1 of Renes-Costello-Batina. Also
+    /// available as the `+` operator.
+    pub fn add_point(&self, other: &Self) -> Self {
+        let (a, b3) = Self::formula_constants();
+        let (x1, y1, z1) = (self.x, self.y, self.z);
+        let (x2, y2, z2) = (other.x, other.y, other.z);
+
+        let mut t0 = x1 * x2;
+        let mut t1 = y1 * y2;
+        let mut t2 = z1 * z2;
+        let mut t3 = x1 + y1;
+        let mut t4 = x2 + y2;
+        t3 = t3 * t4;
+        t4 = t0 + t1;
+        t3 = t3 - t4;
+        t4 = x1 + z1;
+        let mut t5 = x2 + z2;
+        t4 = t4 * t5;
+        t5 = t0 + t2;
+        t4 = t4 - t5;
+        t5 = y1 + z1;
+        let mut x3 = y2 + z2;
+        t5 = t5 * x3;
+        x3 = t1 + t2;
+        t5 = t5 - x3;
+        let mut z3 = a * t4;
+        x3 = b3 * t2;
+        z3 = x3 + z3;
+        x3 = t1 - z3;
+        z3 = t1 + z3;
+        let mut y3 = x3 * z3;
+        t1 = t0 + t0;
+        t1 = t1 + t0;
+        t2 = a * t2;
+        t4 = b3 * t4;
+        t1 = t1 + t2;
+        t2 = t0 - t2;
+        t2 = a * t2;
+        t4 = t4 + t2;
+        t0 = t1 * t4;
+        y3 = y3 + t0;
+        t0 = t5 * t4;
+        x3 = t3 * x3;
+        x3 = x3 - t0;
+        t0 = t3 * t1;
+        z3 = t5 * z3;
+        z3 = z3 + t0;
+
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Doubling, Algorithm 3 of Renes-Costello-Batina.
+    pub fn double(&self) -> Self {
+        let (a, b3) = Self::formula_constants();
+        let (x, y, z) = (self.x, self.y, self.z);
+
+        let mut t0 = x * x;
+        let t1 = y * y;
+        let mut t2 = z * z;
+        let mut t3 = x * y;
+        t3 = t3 + t3;
+        let mut z3 = x * z;
+        z3 = z3 + z3;
+        let mut x3 = a * z3;
+        let mut y3 = b3 * t2;
+        y3 = x3 + y3;
+        x3 = t1 - y3;
+        y3 = t1 + y3;
+        y3 = x3 * y3;
+        x3 = t3 * x3;
+        z3 = b3 * z3;
+        t2 = a * t2;
+        t3 = t0 - t2;
+        t3 = a * t3;
+        t3 = t3 + z3;
+        z3 = t0 + t0;
+        t0 = z3 + t0;
+        t0 = t0 + t2;
+        t0 = t0 * t3;
+        y3 = y3 + t0;
+        t2 = y * z;
+        t2 = t2 + t2;
+        t0 = t2 * t3;
+        x3 = x3 - t0;
+        z3 = t2 * t1;
+        z3 = z3 + z3;
+        z3 = z3 + z3;
+
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Returns `b` if `choice` is set and `a` otherwise, in constant time.
+    pub fn conditional_select(a: &Self, b: &Self, choice: bool) -> Self {
+        ProjectivePoint {
+            x: FieldElement::conditional_select(&a.x, &b.x, choice),
+            y: FieldElement::conditional_select(&a.y, &b.y, choice),
+            z: FieldElement::conditional_select(&a.z, &b.z, choice),
+        }
+    }
+
+    /// Returns `scalar * self` by a Montgomery ladder over all `64 * N` bits.
+    pub fn mul(&self, scalar: &BigUInt<N>) -> Self {
+        // Invariant: r1 = r0 + self.
+        let mut r0 = Self::identity();
+        let mut r1 = *self;
+        for i in (0..64 * N).rev() {
+            let bit = scalar.bit(i);
+            let (low, high) = (
+                Self::conditional_select(&r0, &r1, bit),
+                Self::conditional_select(&r1, &r0, bit),
+            );
+            r0 = low.double();
+            r1 = low.add_point(&high);
+            let (low, high) = (r0, r1);
+            r0 = Self::conditional_select(&low, &high, bit);
+            r1 = Self::conditional_select(&high, &low, bit);
+        }
+        r0
+    }
+}
+
+impl<C: Curve<N>, const N: usize> PartialEq for ProjectivePoint<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        // (X1 : Y1 : Z1) = (X2 : Y2 : Z2) exactly when the cross products agree.
+        self.x * other.z == other.x * self.z && self.y * other.z == other.y * self.z
+    }
+}
+
+impl<C: Curve<N>, const N: usize> Eq for ProjectivePoint<C, N> {}
+
+impl<C: Curve<N>, const N: usize> Add for ProjectivePoint<C, N> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        self.add_point(&other)
+    }
+}
+
+impl<C: Curve<N>, const N: usize> Sub for ProjectivePoint<C, N> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.add_point(&-other)
+    }
+}
+
+impl<C: Curve<N>, const N: usize> Neg for ProjectivePoint<C, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        ProjectivePoint {
+            x: self.x,
+            y: -self.y,
+            z: self.z,
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Prime-field elements over `BigUInt<N>` moduli in Montgomery form.
+//!
+//! Multiplication is Montgomery's CIOS method on top of `limb::mul_add_row`.
+//! Arithmetic runs in time independent of the operand values; conversions
+//! from `BigUInt` and the zero check in `invert` do not.
+
+use crate::biguint::modular::select;
+use crate::biguint::BigUInt;
+use crate::limb;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// The modulus of a prime field, given as a marker type.
+pub trait FieldParams<const N: usize>: Copy + Debug + Eq {
+    /// An odd prime modulus.
+    const MODULUS: BigUInt<N>;
+}
+
+/// An element of the field defined by `F`, stored as `x * 2^(64N) mod p`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldElement<F: FieldParams<N>, const N: usize> {
+    value: BigUInt<N>,
+    params: PhantomData<F>,
+}
+
+/// Returns `-p^-1 mod 2^64` for odd `p0`, by Newton's iteration.
+const fn montgomery_inverse(p0: u64) -> u64 {
+    assert!(p0 & 1 == 1, "field modulus must be odd");
+    let mut inverse = 1u64;
+    let mut i = 0;
+    // Each step doubles the number of correct low bits: 1, 2, 4, ..., 64.
+    while i < 6 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(p0.wrapping_mul(inverse)));
+        i += 1;
+    }
+    inverse.wrapping_neg()
+}
+
+/// Returns `2^bits mod p` by repeated doubling, for use in constants.
+const fn pow2_mod<const N: usize>(p: &[u64; N], bits: usize) -> [u64; N] {
+    let mut x = [0u64; N];
+    x[0] = 1;
+    let mut i = 0;
+    while i < bits {
+        let mut carry = 0;
+        let mut j = 0;
+        while j < N {
+            let doubled = (x[j] << 1) | carry;
+            carry = x[j] >> 63;
+            x[j] = doubled;
+            j += 1;
+        }
+        // Subtract p if the doubling carried out or the result is at least p.
+        let mut at_least_p = true;
+        let mut j = N;
+        while j > 0 {
+            j -= 1;
+            if x[j] != p[j] {
+                at_least_p = x[j] > p[j];
+                break;
+            }
+        }
+        if carry == 1 || at_least_p {
+            let mut borrow = false;
+            let mut j = 0;
+            while j < N {
+                let (d1, b1) = x[j].overflowing_sub(p[j]);
+                let (d2, b2) = d1.overflowing_sub(borrow as u64);
+                x[j] = d2;
+                borrow = b1 || b2;
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+    x
+}
+
+impl<F: FieldParams<N>, const N: usize> FieldElement<F, N> {
+    const INVERSE: u64 = montgomery_inverse(F::MODULUS.data[0]);
+    /// `2^(64N) mod p`, the Montgomery form of one.
+    const R: BigUInt<N> = BigUInt {
+        data: pow2_mod(&F::MODULUS.data, 64 * N),
+    };
+    /// `2^(128N) mod p`, used to convert into Montgomery form.
+    const R2: BigUInt<N> = BigUInt {
+        data: pow2_mod(&F::MODULUS.data, 128 * N),
+    };
+
+    pub fn zero() -> Self {
+        FieldElement {
+            value: BigUInt::new(),
+            params: PhantomData,
+        }
+    }
+
+    pub fn one() -> Self {
+        FieldElement {
+            value: Self::R,
+            params: PhantomData,
+        }
+    }
+
+    /// Returns `x` as a field element, or `None` if `x` is not below the modulus.
+    pub fn from_biguint(x: &BigUInt<N>) -> Option<Self> {
+        if *x >= F::MODULUS {
+            return None;
+        }
+        Some(Self::from_biguint_reduced(x))
+    }
+
+    /// Returns `x mod p` as a field element.
+    pub fn from_biguint_reduced(x: &BigUInt<N>) -> Self {
+        let reduced = x.strict_rem(&F::MODULUS);
+        FieldElement {
+            value: Self::montgomery_mul(&reduced, &Self::R2),
+            params: PhantomData,
+        }
+    }
+
+    /// Returns the canonical representative in `0..p`.
+    pub fn to_biguint(&self) -> BigUInt<N> {
+        Self::montgomery_mul(&self.value, &BigUInt::from(1u8))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    /// Returns `b` if `choice` is set and `a` otherwise, in constant time.
+    pub fn conditional_select(a: &Self, b: &Self, choice: bool) -> Self {
+        FieldElement {
+            value: select(&a.value, &b.value, choice),
+            params: PhantomData,
+        }
+    }
+
+    /// Returns `a * b * 2^(-64N) mod p` for `a, b < p`.
+    fn montgomery_mul(a: &BigUInt<N>, b: &BigUInt<N>) -> BigUInt<N> {
+        let p = &F::MODULUS.data;
+        let mut t = [0u64; N];
+        // The limbs above `t`, which stay below 2 between iterations.
+        let mut top = 0u64;
+        for &b_limb in &b.data {
+            let mut high = top as u128 + limb::mul_add_row(&mut t, &a.data, b_limb) as u128;
+            let m = t[0].wrapping_mul(Self::INVERSE);
+            high += limb::mul_add_row(&mut t, p, m) as u128;
+            // t[0] is now zero, so dividing by 2^64 is a shift by one limb.
+            t.copy_within(1.., 0);
+            t[N - 1] = high as u64;
+            top = (high >> 64) as u64;
+        }
+        let t = BigUInt { data: t };
+        let (reduced, borrow) = t.overflowing_sub(&F::MODULUS);
+        select(&t, &reduced, top == 1 || !borrow)
+    }
+
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// Returns `self.pow(exponent)`, running over all `64 * N` exponent bits.
+    pub fn pow(&self, exponent: &BigUInt<N>) -> Self {
+        let mut result = Self::one();
+        for i in (0..64 * N).rev() {
+            result = result.square();
+            let product = result * *self;
+            result = Self::conditional_select(&result, &product, exponent.bit(i));
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse by Fermat's little theorem, or
+    /// `None` for zero.
+    pub fn invert(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.pow(&F::MODULUS.wrapping_sub(&BigUInt::from(2u8))))
+    }
+}
+
+impl<F: FieldParams<N>, const N: usize> Add for FieldElement<F, N> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        let (sum, carry) = self.value.overflowing_add(&other.value);
+        let (reduced, borrow) = sum.overflowing_sub(&F::MODULUS);
+        FieldElement {
+            value: select(&sum, &reduced, carry || !borrow),
+            params: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldParams<N>, const N: usize> Sub for FieldElement<F, N> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        let (difference, borrow) = self.value.overflowing_sub(&other.value);
+        let corrected = difference.wrapping_add(&F::MODULUS);
+        FieldElement {
+            value: select(&difference, &corrected, borrow),
+            params: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldParams<N>, const N: usize> Mul for FieldElement<F, N> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        FieldElement {
+            value: FieldElement::<F, N>::montgomery_mul(&self.value, &other.value),
+            params: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldParams<N>, const N: usize> Neg for FieldElement<F, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        FieldElement::zero() - self
+    }
+}
@@ -1,5 +1,17 @@
-#![feature(bigint_helper_methods)]
+#![cfg_attr(
+    all(feature = "nightly", not(stable_bigint_helpers)),
+    feature(bigint_helper_methods)
+)]
+#![cfg_attr(feature = "nightly", feature(const_unsigned_bigint_helpers))]
+pub mod bigint;
 pub mod biguint;
+pub mod dynuint;
+pub mod ec;
+pub mod field;
+pub mod limb;
+pub mod p256;
+pub mod prime;
+pub mod rsa;
 
 /// Define a hash_map! macro to create HashMaps.
 #[macro_export]
@@ -15,3 +27,29 @@ macro_rules! hash_map {
 
     };
 }
+
+/// Define a biguint! macro to build a `BigUInt` from a hex (`0x`) or decimal
+/// integer literal, which may be wider than `u128`. The literal is parsed in
+/// a const block, so a malformed or oversized literal is a compile error.
+///
+/// ```
+/// use p44::biguint::BigUInt;
+///
+/// const P: BigUInt<2> = p44::biguint!(0xffffffff_00000001_00000000_00000001);
+/// let million = p44::biguint!(1_000_000, 1);
+/// assert_eq!(million, BigUInt::<1>::from(1_000_000u32));
+/// ```
+///
+/// ```compile_fail
+/// // 2^64 does not fit in one limb.
+/// let x = p44::biguint!(18446744073709551616, 1);
+/// ```
+#[macro_export]
+macro_rules! biguint {
+    ($literal:literal) => {
+        const { $crate::biguint::BigUInt::from_str_literal(stringify!($literal)) }
+    };
+    ($literal:literal, $N:expr) => {
+        const { $crate::biguint::BigUInt::<$N>::from_str_literal(stringify!($literal)) }
+    };
+}
@@ -0,0 +1,89 @@
+//! Carrying arithmetic on single `u64` limbs.
+//!
+//! The `portable` versions widen to `u128` and build on any toolchain. With
+//! the `nightly` feature the core `bigint_helper_methods` intrinsics are used
+//! instead; both must give identical results. On x86_64 the multiply-accumulate
+//! row uses MULX/ADCX/ADOX when the CPU supports them.
+
+#[cfg(target_arch = "x86_64")]
+pub mod adx;
+
+/// Limb operations implemented with `u128` widening.
+pub mod portable {
+    /// Returns `a + b + carry` and the carry out.
+    #[inline]
+    pub const fn carrying_add(a: u64, b: u64, carry: bool) -> (u64, bool) {
+        let sum = a as u128 + b as u128 + carry as u128;
+        (sum as u64, (sum >> 64) != 0)
+    }
+
+    /// Returns `a - b - borrow` and the borrow out.
+    #[inline]
+    pub const fn borrowing_sub(a: u64, b: u64, borrow: bool) -> (u64, bool) {
+        let difference = (a as u128)
+            .wrapping_sub(b as u128)
+            .wrapping_sub(borrow as u128);
+        (difference as u64, (difference >> 64) != 0)
+    }
+
+    /// Returns the low and high limbs of `a * b + carry`, which cannot overflow.
+    #[inline]
+    pub const fn carrying_mul(a: u64, b: u64, carry: u64) -> (u64, u64) {
+        let product = a as u128 * b as u128 + carry as u128;
+        (product as u64, (product >> 64) as u64)
+    }
+
+    /// Adds `a * x` into `acc` and returns the carry limb.
+    /// `acc` and `a` must have the same length.
+    pub fn mul_add_row(acc: &mut [u64], a: &[u64], x: u64) -> u64 {
+        debug_assert_eq!(acc.len(), a.len());
+        let mut carry = 0;
+        for (acc, &y) in acc.iter_mut().zip(a) {
+            let (low, high) = carrying_mul(x, y, carry);
+            let overflow;
+            (*acc, overflow) = acc.overflowing_add(low);
+            carry = high + overflow as u64;
+        }
+        carry
+    }
+}
+
+/// Limb operations forwarding to the core intrinsics. The single-limb
+/// operations are `const fn` in both backends, so `BigUInt` arithmetic in
+/// constants goes through whichever one is selected.
+#[cfg(feature = "nightly")]
+pub mod intrinsic {
+    /// Returns `a + b + carry` and the carry out.
+    #[inline]
+    pub const fn carrying_add(a: u64, b: u64, carry: bool) -> (u64, bool) {
+        a.carrying_add(b, carry)
+    }
+
+    /// Returns `a - b - borrow` and the borrow out.
+    #[inline]
+    pub const fn borrowing_sub(a: u64, b: u64, borrow: bool) -> (u64, bool) {
+        a.borrowing_sub(b, borrow)
+    }
+
+    /// Returns the low and high limbs of `a * b + carry`, which cannot overflow.
+    #[inline]
+    pub const fn carrying_mul(a: u64, b: u64, carry: u64) -> (u64, u64) {
+        a.carrying_mul(b, carry)
+    }
+}
+
+#[cfg(feature = "nightly")]
+pub use intrinsic::{borrowing_sub, carrying_add, carrying_mul};
+#[cfg(not(feature = "nightly"))]
+pub use portable::{borrowing_sub, carrying_add, carrying_mul};
+
+/// Adds `a * x` into `acc` and returns the carry limb, using the ADX path
+/// when the CPU supports it. `acc` and `a` must have the same length.
+pub fn mul_add_row(acc: &mut [u64], a: &[u64], x: u64) -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    if adx::is_available() {
+        // SAFETY: the CPU supports BMI2 and ADX, and the lengths are checked.
+        return unsafe { adx::mul_add_row(acc, a, x) };
+    }
+    portable::mul_add_row(acc, a, x)
+}
@@ -0,0 +1,81 @@
+//! x86_64 multiply-accumulate with MULX (BMI2) and ADCX/ADOX (ADX).
+//!
+//! ADCX and ADOX carry through CF and OF respectively, so adding the low
+//! product half and the previous high half run as two independent carry
+//! chains instead of serialising on a single flag.
+
+use std::arch::asm;
+
+/// Returns `true` if the CPU supports the instructions used by `mul_add_row`.
+pub fn is_available() -> bool {
+    is_x86_feature_detected!("bmi2") && is_x86_feature_detected!("adx")
+}
+
+/// Adds `a * x` into `acc` and returns the carry limb.
+/// # Safety
+/// This function is unsafe because it uses instructions that require the
+/// target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports BMI2 and ADX (see `is_available`).
+/// 2. `acc` and `a` have the same length; this is checked and panics otherwise.
+#[target_feature(enable = "bmi2,adx")]
+pub unsafe fn mul_add_row(acc: &mut [u64], a: &[u64], x: u64) -> u64 {
+    assert_eq!(acc.len(), a.len());
+    let carry: u64;
+    // The loops only use LEA, MOV and JRCXZ for bookkeeping, none of which
+    // touch CF or OF, so both carry chains survive across iterations. The
+    // first loop handles four limbs per iteration, the second the rest.
+    asm!(
+        "xor {zero:e}, {zero:e}",
+        "xor {carry:e}, {carry:e}",
+        "2:",
+        "jrcxz 3f",
+        "mulx {hi}, {lo}, qword ptr [{a}]",
+        "adcx {lo}, qword ptr [{acc}]",
+        "adox {lo}, {carry}",
+        "mov qword ptr [{acc}], {lo}",
+        "mulx {carry}, {lo}, qword ptr [{a} + 8]",
+        "adcx {lo}, qword ptr [{acc} + 8]",
+        "adox {lo}, {hi}",
+        "mov qword ptr [{acc} + 8], {lo}",
+        "mulx {hi}, {lo}, qword ptr [{a} + 16]",
+        "adcx {lo}, qword ptr [{acc} + 16]",
+        "adox {lo}, {carry}",
+        "mov qword ptr [{acc} + 16], {lo}",
+        "mulx {carry}, {lo}, qword ptr [{a} + 24]",
+        "adcx {lo}, qword ptr [{acc} + 24]",
+        "adox {lo}, {hi}",
+        "mov qword ptr [{acc} + 24], {lo}",
+        "lea {a}, [{a} + 32]",
+        "lea {acc}, [{acc} + 32]",
+        "lea rcx, [rcx - 1]",
+        "jmp 2b",
+        "3:",
+        "mov rcx, {rest}",
+        "4:",
+        "jrcxz 5f",
+        "mulx {hi}, {lo}, qword ptr [{a}]",
+        "adcx {lo}, qword ptr [{acc}]",
+        "adox {lo}, {carry}",
+        "mov qword ptr [{acc}], {lo}",
+        "mov {carry}, {hi}",
+        "lea {a}, [{a} + 8]",
+        "lea {acc}, [{acc} + 8]",
+        "lea rcx, [rcx - 1]",
+        "jmp 4b",
+        "5:",
+        // The full row sum fits in one more limb, so these cannot overflow.
+        "adcx {carry}, {zero}",
+        "adox {carry}, {zero}",
+        a = inout(reg) a.as_ptr() => _,
+        acc = inout(reg) acc.as_mut_ptr() => _,
+        inout("rcx") a.len() / 4 => _,
+        rest = in(reg) a.len() % 4,
+        in("rdx") x,
+        lo = out(reg) _,
+        hi = out(reg) _,
+        zero = out(reg) _,
+        carry = out(reg) carry,
+        options(nostack),
+    );
+    carry
+}
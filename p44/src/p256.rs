@@ -0,0 +1,269 @@
+//! NIST P-256 (secp256r1): ECDH and ECDSA with SHA-256, using deterministic
+//! nonces from RFC 6979.
+
+use crate::biguint;
+use crate::biguint::BigUInt;
+use crate::ec::{Curve, EcError, ProjectivePoint};
+use crate::field::{FieldElement, FieldParams};
+use rand_core::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Length of field elements and scalars in bytes.
+const ELEMENT_LEN: usize = 32;
+/// SHA-256 block length in bytes, for HMAC.
+const HASH_BLOCK_LEN: usize = 64;
+
+/// The base field, modulo `p = 2^256 - 2^224 + 2^192 + 2^96 - 1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P256Base;
+
+impl FieldParams<4> for P256Base {
+    const MODULUS: BigUInt<4> =
+        biguint!(0xffffffff00000001_0000000000000000_00000000ffffffff_ffffffffffffffff);
+}
+
+/// The scalar field, modulo the group order `n`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P256Order;
+
+impl FieldParams<4> for P256Order {
+    const MODULUS: BigUInt<4> =
+        biguint!(0xffffffff00000000_ffffffffffffffff_bce6faada7179e84_f3b9cac2fc632551);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P256;
+
+impl Curve<4> for P256 {
+    type Base = P256Base;
+    type Scalar = P256Order;
+    /// `a = -3 mod p`.
+    const A: BigUInt<4> =
+        biguint!(0xffffffff00000001_0000000000000000_00000000ffffffff_fffffffffffffffc);
+    const B: BigUInt<4> =
+        biguint!(0x5ac635d8aa3a93e7_b3ebbd55769886bc_651d06b0cc53b0f6_3bce3c3e27d2604b);
+    const GENERATOR: (BigUInt<4>, BigUInt<4>) = (
+        biguint!(0x6b17d1f2e12c4247_f8bce6e563a440f2_77037d812deb33a0_f4a13945d898c296),
+        biguint!(0x4fe342e2fe1a7f9b_8ee7eb4a7c0f9e16_2bce33576b315ece_cbb6406837bf51f5),
+    );
+}
+
+pub type P256Point = ProjectivePoint<P256, 4>;
+pub type P256Scalar = FieldElement<P256Order, 4>;
+
+/// A private scalar in `1..n`. It has no `PartialEq`, so keys cannot be
+/// compared in variable time, and its `Debug` output hides the scalar.
+#[derive(Clone)]
+pub struct SecretKey {
+    scalar: P256Scalar,
+}
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretKey").finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicKey {
+    point: P256Point,
+}
+
+/// An ECDSA signature `(r, s)`, both in `1..n`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    r: BigUInt<4>,
+    s: BigUInt<4>,
+}
+
+/// HMAC-SHA256 (RFC 2104) over the concatenation of `parts`.
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut block = [0u8; HASH_BLOCK_LEN];
+    if key.len() > HASH_BLOCK_LEN {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new().chain_update(block.map(|byte| byte ^ 0x36));
+    for part in parts {
+        inner.update(part);
+    }
+    Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x5c))
+        .chain_update(inner.finalize())
+        .finalize()
+        .into()
+}
+
+/// Hashes `message` and reduces it modulo `n`. SHA-256 output is exactly as
+/// long as `n`, so no truncation is needed.
+fn hash_to_scalar(message: &[u8]) -> P256Scalar {
+    let digest = Sha256::digest(message);
+    let z = BigUInt::from_be_bytes(&digest).expect("digest fits in 256 bits");
+    P256Scalar::from_biguint_reduced(&z)
+}
+
+/// Returns `x` as `8 * N` big-endian bytes.
+fn scalar_bytes(x: &P256Scalar) -> Vec<u8> {
+    x.to_biguint().to_be_bytes()
+}
+
+impl SecretKey {
+    /// Draws a uniformly random scalar in `1..n` by rejection sampling.
+    pub fn random<R: RngCore>(rng: &mut R) -> Self {
+        loop {
+            if let Ok(key) = Self::from_bytes(&BigUInt::<4>::random(rng).to_be_bytes()) {
+                return key;
+            }
+        }
+    }
+
+    /// Decodes a 32-byte big-endian scalar in `1..n`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcError> {
+        if bytes.len() != ELEMENT_LEN {
+            return Err(EcError::InvalidScalar);
+        }
+        let x = BigUInt::from_be_bytes(bytes).map_err(|_| EcError::InvalidScalar)?;
+        match P256Scalar::from_biguint(&x) {
+            Some(scalar) if !scalar.is_zero() => Ok(SecretKey { scalar }),
+            _ => Err(EcError::InvalidScalar),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        scalar_bytes(&self.scalar)
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            point: P256Point::generator().mul(&self.scalar.to_biguint()),
+        }
+    }
+
+    /// ECDH: returns the x-coordinate of `self * peer` as 32 big-endian bytes.
+    pub fn diffie_hellman(&self, peer: &PublicKey) -> Vec<u8> {
+        let shared = peer.point.mul(&self.scalar.to_biguint());
+        // The peer is a valid non-identity point and the group has prime
+        // order, so a nonzero scalar below n cannot give the identity.
+        let (x, _) = shared
+            .to_affine()
+            .expect("shared point is not the identity");
+        x.to_be_bytes()
+    }
+
+    /// Generates the ECDSA nonce for the hashed message `h` as in RFC 6979,
+    /// section 3.2.
+    fn nonces(&self, h: &P256Scalar) -> impl Iterator<Item = P256Scalar> {
+        let x = self.to_bytes();
+        let h = scalar_bytes(h);
+        let mut v = [0x01u8; 32];
+        let mut k = hmac_sha256(&[0x00; 32], &[&v, &[0x00], &x, &h]);
+        v = hmac_sha256(&k, &[&v]);
+        k = hmac_sha256(&k, &[&v, &[0x01], &x, &h]);
+        v = hmac_sha256(&k, &[&v]);
+        std::iter::from_fn(move || loop {
+            v = hmac_sha256(&k, &[&v]);
+            let candidate = BigUInt::from_be_bytes(&v).expect("HMAC output fits in 256 bits");
+            let nonce = P256Scalar::from_biguint(&candidate);
+            // Prepare for the next candidate whether or not this one is used.
+            k = hmac_sha256(&k, &[&v, &[0x00]]);
+            v = hmac_sha256(&k, &[&v]);
+            match nonce {
+                Some(nonce) if !nonce.is_zero() => return Some(nonce),
+                _ => continue,
+            }
+        })
+    }
+
+    /// ECDSA signature of `message` with SHA-256 and an RFC 6979 nonce.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let h = hash_to_scalar(message);
+        for k in self.nonces(&h) {
+            let (x, _) = P256Point::generator()
+                .mul(&k.to_biguint())
+                .to_affine()
+                .expect("nonzero multiple of the generator");
+            let r = P256Scalar::from_biguint_reduced(&x);
+            let k_inv = k.invert().expect("nonce is nonzero");
+            let s = k_inv * (h + r * self.scalar);
+            if !r.is_zero() && !s.is_zero() {
+                return Signature {
+                    r: r.to_biguint(),
+                    s: s.to_biguint(),
+                };
+            }
+        }
+        unreachable!("the nonce generator never ends")
+    }
+}
+
+impl PublicKey {
+    /// Decodes an uncompressed SEC1 point.
+    pub fn from_uncompressed(bytes: &[u8]) -> Result<Self, EcError> {
+        let point = P256Point::from_uncompressed(bytes)?;
+        Ok(PublicKey { point })
+    }
+
+    /// Encodes as an uncompressed SEC1 point, `0x04 || x || y`.
+    pub fn to_uncompressed(&self) -> Vec<u8> {
+        self.point
+            .to_uncompressed()
+            .expect("public keys are never the identity")
+    }
+
+    pub fn point(&self) -> &P256Point {
+        &self.point
+    }
+
+    /// Verifies an ECDSA signature of `message` with SHA-256.
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> Result<(), EcError> {
+        let r = P256Scalar::from_biguint(&signature.r).ok_or(EcError::InvalidSignature)?;
+        let s = P256Scalar::from_biguint(&signature.s).ok_or(EcError::InvalidSignature)?;
+        let s_inv = s.invert().ok_or(EcError::InvalidSignature)?;
+        if r.is_zero() {
+            return Err(EcError::InvalidSignature);
+        }
+        let h = hash_to_scalar(message);
+        let u1 = (h * s_inv).to_biguint();
+        let u2 = (r * s_inv).to_biguint();
+        let point = P256Point::generator().mul(&u1) + self.point.mul(&u2);
+        let (x, _) = point.to_affine().ok_or(EcError::InvalidSignature)?;
+        if P256Scalar::from_biguint_reduced(&x) == r {
+            Ok(())
+        } else {
+            Err(EcError::InvalidSignature)
+        }
+    }
+}
+
+impl Signature {
+    /// Returns `r || s` as 64 big-endian bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = self.r.to_be_bytes();
+        result.extend_from_slice(&self.s.to_be_bytes());
+        result
+    }
+
+    /// Decodes `r || s` from 64 big-endian bytes, requiring both in `1..n`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EcError> {
+        if bytes.len() != 2 * ELEMENT_LEN {
+            return Err(EcError::InvalidSignature);
+        }
+        let (r, s) = bytes.split_at(ELEMENT_LEN);
+        let r = BigUInt::from_be_bytes(r).map_err(|_| EcError::InvalidSignature)?;
+        let s = BigUInt::from_be_bytes(s).map_err(|_| EcError::InvalidSignature)?;
+        let in_range = |x: &BigUInt<4>| !x.is_zero() && *x < P256Order::MODULUS;
+        if !in_range(&r) || !in_range(&s) {
+            return Err(EcError::InvalidSignature);
+        }
+        Ok(Signature { r, s })
+    }
+
+    pub fn r(&self) -> &BigUInt<4> {
+        &self.r
+    }
+
+    pub fn s(&self) -> &BigUInt<4> {
+        &self.s
+    }
+}
@@ -0,0 +1,353 @@
+//! Primality testing and random prime generation for `BigUInt<N>`.
+
+use crate::biguint::BigUInt;
+use rand_core::RngCore;
+
+/// Number of odd primes used for trial division and candidate sieving.
+const SMALL_PRIME_COUNT: usize = 308;
+
+/// The odd primes below 2048, computed at compile time.
+pub const SMALL_PRIMES: [u64; SMALL_PRIME_COUNT] = {
+    let mut primes = [0; SMALL_PRIME_COUNT];
+    let mut count = 0;
+    let mut candidate = 3;
+    while count < SMALL_PRIME_COUNT {
+        let mut d = 3;
+        let mut is_prime = true;
+        while d * d <= candidate {
+            if candidate % d == 0 {
+                is_prime = false;
+                break;
+            }
+            d += 2;
+        }
+        if is_prime {
+            primes[count] = candidate;
+            count += 1;
+        }
+        candidate += 2;
+    }
+    primes
+};
+
+/// Bases that make Miller-Rabin deterministic for all n below 3.3 * 10^24.
+const DETERMINISTIC_BASES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// The deterministic bases cover every n below this bound, 3317044064679887385961981.
+const DETERMINISTIC_BOUND: u128 = 3_317_044_064_679_887_385_961_981;
+
+/// How far past a random starting point to search before drawing a new one.
+const SEARCH_WINDOW: u64 = 1 << 16;
+
+/// Returns `x % m` for a single-limb modulus.
+fn rem_u64<const N: usize>(x: &BigUInt<N>, m: u64) -> u64 {
+    let mut rem = 0u128;
+    for &limb in x.data.iter().rev() {
+        rem = ((rem << 64) | limb as u128) % m as u128;
+    }
+    rem as u64
+}
+
+/// Returns `x / 2 mod n` for odd `n` and `x < n`.
+fn half_mod<const N: usize>(x: &BigUInt<N>, n: &BigUInt<N>) -> BigUInt<N> {
+    if !x.bit(0) {
+        return x >> 1;
+    }
+    let (sum, carry) = x.overflowing_add(n);
+    let mut result = sum >> 1;
+    result.set_bit(64 * N - 1, carry);
+    result
+}
+
+/// Returns `d * x mod n` for a small signed `d`.
+fn mul_small_mod<const N: usize>(x: &BigUInt<N>, d: i64, n: &BigUInt<N>) -> BigUInt<N> {
+    let product = x.mul_mod(&BigUInt::<N>::from(d.unsigned_abs()).strict_rem(n), n);
+    if d < 0 {
+        BigUInt::<N>::new().sub_mod(&product, n)
+    } else {
+        product
+    }
+}
+
+/// Jacobi symbol `(a / m)` for odd `m`.
+fn jacobi_u64(mut a: u64, mut m: u64) -> i32 {
+    let mut result = 1;
+    a %= m;
+    while a != 0 {
+        while a.is_multiple_of(2) {
+            a /= 2;
+            if m % 8 == 3 || m % 8 == 5 {
+                result = -result;
+            }
+        }
+        std::mem::swap(&mut a, &mut m);
+        if a % 4 == 3 && m % 4 == 3 {
+            result = -result;
+        }
+        a %= m;
+    }
+    if m == 1 {
+        result
+    } else {
+        0
+    }
+}
+
+/// Jacobi symbol `(d / n)` for a small signed `d` and odd `n`, using reciprocity.
+fn jacobi<const N: usize>(d: i64, n: &BigUInt<N>) -> i32 {
+    let mut result = 1;
+    let mut a = d.unsigned_abs();
+    // (-1 / n) is -1 exactly when n = 3 mod 4.
+    if d < 0 && rem_u64(n, 4) == 3 {
+        result = -result;
+    }
+    let twos = a.trailing_zeros();
+    a >>= twos;
+    let n_mod_8 = rem_u64(n, 8);
+    if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+        result = -result;
+    }
+    // Now a is odd and positive: flip (a / n) to (n mod a / a).
+    if a % 4 == 3 && n_mod_8 % 4 == 3 {
+        result = -result;
+    }
+    result * jacobi_u64(rem_u64(n, a), a)
+}
+
+impl<const N: usize> BigUInt<N> {
+    /// Divides by the small odd primes. Returns `Some(true)` for a small
+    /// prime, `Some(false)` for a number with a small factor, and `None`
+    /// when trial division is inconclusive.
+    pub fn trial_division(&self) -> Option<bool> {
+        if self.bits() <= 1 {
+            return Some(false);
+        }
+        if !self.bit(0) {
+            return Some(self.bits() == 2 && self.data[0] == 2);
+        }
+        for &p in SMALL_PRIMES.iter() {
+            if rem_u64(self, p) == 0 {
+                return Some(self.bits() <= 64 && self.data[0] == p);
+            }
+        }
+        let largest = SMALL_PRIMES[SMALL_PRIME_COUNT - 1] as u128;
+        if u128::try_from(*self).is_ok_and(|n| n < largest * largest) {
+            return Some(true);
+        }
+        None
+    }
+
+    /// Miller-Rabin strong probable prime test of an odd `self > 3` to a single base.
+    pub fn is_strong_probable_prime(&self, base: &Self) -> bool {
+        let one = BigUInt::<N>::from(1u8);
+        let n_minus_one = self.wrapping_sub(&one);
+        let s = n_minus_one.trailing_zeros();
+        let d = n_minus_one >> s as usize;
+
+        let mut x = base.pow_mod(&d, self);
+        if x == one || x == n_minus_one {
+            return true;
+        }
+        for _ in 1..s {
+            x = x.mul_mod(&x, self);
+            if x == n_minus_one {
+                return true;
+            }
+            if x == one {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// Miller-Rabin test. Numbers below 3.3 * 10^24 are tested against a
+    /// deterministic base set; larger ones against `rounds` random bases,
+    /// so `rounds` must not be zero.
+    pub fn is_probable_prime_miller_rabin<R: RngCore>(&self, rounds: usize, rng: &mut R) -> bool {
+        assert!(rounds > 0, "Miller-Rabin needs at least one round");
+        if let Some(result) = self.trial_division() {
+            return result;
+        }
+        if u128::try_from(*self).is_ok_and(|n| n < DETERMINISTIC_BOUND) {
+            return DETERMINISTIC_BASES
+                .iter()
+                .all(|&base| self.is_strong_probable_prime(&BigUInt::<N>::from(base)));
+        }
+        let three = BigUInt::<N>::from(3u8);
+        let range = self.wrapping_sub(&three);
+        (0..rounds).all(|_| {
+            // A base in [2, n - 2].
+            let base = BigUInt::<N>::random(rng).strict_rem(&range) + BigUInt::<N>::from(2u8);
+            self.is_strong_probable_prime(&base)
+        })
+    }
+
+    /// Strong Lucas probable prime test with Selfridge's parameters, for odd `self > 3`.
+    pub fn is_strong_lucas_probable_prime(&self) -> bool {
+        // Find the first D in 5, -7, 9, -11, ... with (D / n) = -1.
+        let mut d: i64 = 5;
+        loop {
+            match jacobi(d, self) {
+                -1 => break,
+                0 if BigUInt::<N>::from(d.unsigned_abs()) != *self => return false,
+                _ => {}
+            }
+            // Perfect squares have no such D, so rule them out after a few tries.
+            if d == 13 {
+                let root = self.isqrt();
+                if root.checked_mul(&root) == Some(*self) {
+                    return false;
+                }
+            }
+            d = if d > 0 { -(d + 2) } else { -d + 2 };
+        }
+        let q = (1 - d) / 4;
+        let q_mod = mul_small_mod(&BigUInt::<N>::from(1u8), q, self);
+
+        // n + 1 = d * 2^s with d odd. n is odd and below 2^(64N), so n + 1 does not overflow
+        // unless n = 2^(64N) - 1, which is divisible by 3 and rejected by trial division.
+        let n_plus_one = self.wrapping_add(&BigUInt::<N>::from(1u8));
+        let s = n_plus_one.trailing_zeros();
+        let k = n_plus_one >> s as usize;
+
+        // Compute U_k, V_k and Q^k with P = 1, processing the bits of k from the top.
+        let mut u = BigUInt::<N>::from(1u8);
+        let mut v = BigUInt::<N>::from(1u8);
+        let mut q_k = q_mod;
+        for i in (0..k.bits() - 1).rev() {
+            u = u.mul_mod(&v, self);
+            v = v.mul_mod(&v, self).sub_mod(&q_k.add_mod(&q_k, self), self);
+            q_k = q_k.mul_mod(&q_k, self);
+            if k.bit(i) {
+                let next_u = half_mod(&u.add_mod(&v, self), self);
+                let next_v = half_mod(&mul_small_mod(&u, d, self).add_mod(&v, self), self);
+                u = next_u;
+                v = next_v;
+                q_k = q_k.mul_mod(&q_mod, self);
+            }
+        }
+
+        if u.is_zero() || v.is_zero() {
+            return true;
+        }
+        for _ in 1..s {
+            v = v.mul_mod(&v, self).sub_mod(&q_k.add_mod(&q_k, self), self);
+            if v.is_zero() {
+                return true;
+            }
+            q_k = q_k.mul_mod(&q_k, self);
+        }
+        false
+    }
+
+    /// Baillie-PSW test: trial division, a base-2 strong probable prime
+    /// test and a strong Lucas test. No composite passing it is known.
+    pub fn is_probable_prime(&self) -> bool {
+        if let Some(result) = self.trial_division() {
+            return result;
+        }
+        self.is_strong_probable_prime(&BigUInt::<N>::from(2u8))
+            && self.is_strong_lucas_probable_prime()
+    }
+
+    /// Returns a uniformly random value.
+    pub fn random<R: RngCore>(rng: &mut R) -> Self {
+        let mut result = BigUInt::<N>::new();
+        for limb in result.data.iter_mut() {
+            *limb = rng.next_u64();
+        }
+        result
+    }
+
+    /// Returns a random value of exactly `bits` bits. Panics if `bits` is zero
+    /// or larger than `64 * N`.
+    pub fn random_bits<R: RngCore>(bits: usize, rng: &mut R) -> Self {
+        assert!(bits > 0 && bits <= 64 * N, "Invalid bit length");
+        let mut result = BigUInt::<N>::random(rng);
+        for i in bits..64 * N {
+            result.set_bit(i, false);
+        }
+        result.set_bit(bits - 1, true);
+        result
+    }
+
+    /// Returns a random Baillie-PSW probable prime of exactly `bits` bits.
+    /// Panics if `bits` is less than 2 or larger than `64 * N`.
+    pub fn random_prime<R: RngCore>(bits: usize, rng: &mut R) -> Self {
+        assert!(bits >= 2, "There are no primes below 2 bits");
+        if bits <= 12 {
+            // Too small to sieve: the candidates include the small primes themselves.
+            loop {
+                let candidate = BigUInt::<N>::random_bits(bits, rng);
+                if candidate.is_probable_prime() {
+                    return candidate;
+                }
+            }
+        }
+        loop {
+            let mut start = BigUInt::<N>::random_bits(bits, rng);
+            start.set_bit(0, true);
+            let residues = SMALL_PRIMES.map(|p| rem_u64(&start, p));
+            for delta in (0..SEARCH_WINDOW).step_by(2) {
+                let survives = SMALL_PRIMES
+                    .iter()
+                    .zip(residues.iter())
+                    .all(|(&p, &r)| (r + delta) % p != 0);
+                if !survives {
+                    continue;
+                }
+                let candidate = start.wrapping_add(&BigUInt::<N>::from(delta));
+                if candidate.bits() != bits {
+                    break;
+                }
+                if candidate.is_probable_prime() {
+                    return candidate;
+                }
+            }
+        }
+    }
+
+    /// Returns a random safe prime `p = 2q + 1` of exactly `bits` bits, where
+    /// `q` is also prime. Panics if `bits` is less than 3 or larger than `64 * N`.
+    pub fn random_safe_prime<R: RngCore>(bits: usize, rng: &mut R) -> Self {
+        assert!(bits >= 3, "There are no safe primes below 3 bits");
+        if bits <= 12 {
+            loop {
+                let q = BigUInt::<N>::random_prime(bits - 1, rng);
+                let p = q.wrapping_add(&q).wrapping_add(&BigUInt::<N>::from(1u8));
+                if p.is_probable_prime() {
+                    return p;
+                }
+            }
+        }
+        let two = BigUInt::<N>::from(2u8);
+        loop {
+            let mut start = BigUInt::<N>::random_bits(bits - 1, rng);
+            start.set_bit(0, true);
+            let residues = SMALL_PRIMES.map(|p| rem_u64(&start, p));
+            for delta in (0..SEARCH_WINDOW).step_by(2) {
+                // Neither q nor 2q + 1 may have a small factor.
+                let survives = SMALL_PRIMES.iter().zip(residues.iter()).all(|(&p, &r)| {
+                    let q = (r + delta) % p;
+                    q != 0 && (2 * q + 1) % p != 0
+                });
+                if !survives {
+                    continue;
+                }
+                let q = start.wrapping_add(&BigUInt::<N>::from(delta));
+                if q.bits() != bits - 1 {
+                    break;
+                }
+                let p = q.wrapping_add(&q).wrapping_add(&BigUInt::<N>::from(1u8));
+                // Cheap base-2 tests on both before the full Baillie-PSW tests.
+                if q.is_strong_probable_prime(&two)
+                    && p.is_strong_probable_prime(&two)
+                    && q.is_strong_lucas_probable_prime()
+                    && p.is_strong_lucas_probable_prime()
+                {
+                    return p;
+                }
+            }
+        }
+    }
+}
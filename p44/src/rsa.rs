@@ -0,0 +1,334 @@
+//! RSA over `BigUInt<N>`: key generation, raw RSA, RSAES-OAEP and RSASSA-PSS
+//! (RFC 8017) with SHA-256 and MGF1-SHA-256.
+
+use crate::biguint::BigUInt;
+use rand_core::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Output length of SHA-256 in bytes.
+const HASH_LEN: usize = 32;
+/// PSS salt length in bytes.
+const SALT_LEN: usize = HASH_LEN;
+/// The public exponent used for generated keys.
+const PUBLIC_EXPONENT: u32 = 65537;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RsaError {
+    /// The key components are inconsistent or unsupported.
+    InvalidKey,
+    /// The message or representative is too large for the modulus.
+    MessageTooLong,
+    /// The ciphertext could not be decrypted.
+    Decryption,
+    /// The signature does not match the message.
+    Verification,
+}
+
+impl std::fmt::Display for RsaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RsaError::InvalidKey => write!(f, "invalid RSA key"),
+            RsaError::MessageTooLong => write!(f, "message too long"),
+            RsaError::Decryption => write!(f, "decryption error"),
+            RsaError::Verification => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl std::error::Error for RsaError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RsaPublicKey<const N: usize> {
+    n: BigUInt<N>,
+    e: BigUInt<N>,
+}
+
+/// An RSA private key. Its `Debug` output shows only the public key.
+#[derive(Clone)]
+pub struct RsaPrivateKey<const N: usize> {
+    public: RsaPublicKey<N>,
+    d: BigUInt<N>,
+    p: BigUInt<N>,
+    q: BigUInt<N>,
+    dp: BigUInt<N>,
+    dq: BigUInt<N>,
+    qinv: BigUInt<N>,
+}
+
+impl<const N: usize> std::fmt::Debug for RsaPrivateKey<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RsaPrivateKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+pub type Rsa2048PublicKey = RsaPublicKey<32>;
+pub type Rsa2048PrivateKey = RsaPrivateKey<32>;
+pub type Rsa4096PublicKey = RsaPublicKey<64>;
+pub type Rsa4096PrivateKey = RsaPrivateKey<64>;
+
+/// MGF1 with SHA-256: XORs the mask generated from `seed` into `out`.
+fn mgf1_xor(out: &mut [u8], seed: &[u8]) {
+    for (counter, chunk) in out.chunks_mut(HASH_LEN).enumerate() {
+        let mask = Sha256::new()
+            .chain_update(seed)
+            .chain_update((counter as u32).to_be_bytes())
+            .finalize();
+        for (byte, mask_byte) in chunk.iter_mut().zip(mask.iter()) {
+            *byte ^= mask_byte;
+        }
+    }
+}
+
+impl<const N: usize> RsaPublicKey<N> {
+    /// Creates a public key, checking that the modulus is odd and `1 < e < n`.
+    pub fn new(n: BigUInt<N>, e: BigUInt<N>) -> Result<Self, RsaError> {
+        let one = BigUInt::<N>::from(1u8);
+        if !n.bit(0) || e <= one || e >= n {
+            return Err(RsaError::InvalidKey);
+        }
+        Ok(RsaPublicKey { n, e })
+    }
+
+    pub fn n(&self) -> &BigUInt<N> {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigUInt<N> {
+        &self.e
+    }
+
+    /// Returns the length of the modulus in bytes.
+    pub fn size(&self) -> usize {
+        self.n.bits().div_ceil(8)
+    }
+
+    /// RSAEP: returns `m^e mod n`.
+    pub fn raw_encrypt(&self, m: &BigUInt<N>) -> Result<BigUInt<N>, RsaError> {
+        if *m >= self.n {
+            return Err(RsaError::MessageTooLong);
+        }
+        Ok(m.pow_mod(&self.e, &self.n))
+    }
+
+    /// RSAES-OAEP encryption with SHA-256 and an optional label.
+    pub fn encrypt_oaep<R: RngCore>(
+        &self,
+        message: &[u8],
+        label: &[u8],
+        rng: &mut R,
+    ) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        if k < 2 * HASH_LEN + 2 || message.len() > k - 2 * HASH_LEN - 2 {
+            return Err(RsaError::MessageTooLong);
+        }
+
+        // EM = 0x00 || maskedSeed || maskedDB, DB = lHash || PS || 0x01 || M
+        let mut em = vec![0; k];
+        let (seed, db) = em[1..].split_at_mut(HASH_LEN);
+        let message_start = db.len() - message.len();
+        db[..HASH_LEN].copy_from_slice(&Sha256::digest(label));
+        db[message_start - 1] = 0x01;
+        db[message_start..].copy_from_slice(message);
+        rng.fill_bytes(seed);
+        mgf1_xor(db, seed);
+        mgf1_xor(seed, db);
+
+        let m = BigUInt::from_be_bytes(&em).map_err(|_| RsaError::MessageTooLong)?;
+        let c = self.raw_encrypt(&m)?;
+        c.to_be_bytes_padded(k).ok_or(RsaError::MessageTooLong)
+    }
+
+    /// RSASSA-PSS verification with SHA-256 and a 32-byte salt.
+    pub fn verify_pss(&self, message: &[u8], signature: &[u8]) -> Result<(), RsaError> {
+        let k = self.size();
+        if signature.len() != k {
+            return Err(RsaError::Verification);
+        }
+        let s = BigUInt::from_be_bytes(signature).map_err(|_| RsaError::Verification)?;
+        let m = self.raw_encrypt(&s).map_err(|_| RsaError::Verification)?;
+
+        let em_bits = self.n.bits() - 1;
+        let em_len = em_bits.div_ceil(8);
+        let mut em = m.to_be_bytes_padded(em_len).ok_or(RsaError::Verification)?;
+        if em_len < HASH_LEN + SALT_LEN + 2 || em[em_len - 1] != 0xbc {
+            return Err(RsaError::Verification);
+        }
+
+        let top_mask = 0xffu8 >> (8 * em_len - em_bits);
+        let (db, rest) = em.split_at_mut(em_len - HASH_LEN - 1);
+        let h = &rest[..HASH_LEN];
+        if db[0] & !top_mask != 0 {
+            return Err(RsaError::Verification);
+        }
+        mgf1_xor(db, h);
+        db[0] &= top_mask;
+
+        let ps_len = db.len() - SALT_LEN - 1;
+        if db[..ps_len].iter().any(|&byte| byte != 0) || db[ps_len] != 0x01 {
+            return Err(RsaError::Verification);
+        }
+        let expected = Sha256::new()
+            .chain_update([0u8; 8])
+            .chain_update(Sha256::digest(message))
+            .chain_update(&db[ps_len + 1..])
+            .finalize();
+        if expected.as_slice() == h {
+            Ok(())
+        } else {
+            Err(RsaError::Verification)
+        }
+    }
+}
+
+impl<const N: usize> RsaPrivateKey<N> {
+    /// Generates a key with a modulus of exactly `bits` bits and `e = 65537`.
+    /// Panics if `bits` is less than 32 or larger than `64 * N`.
+    pub fn generate<R: RngCore>(bits: usize, rng: &mut R) -> Self {
+        assert!(bits >= 32 && bits <= 64 * N, "Invalid RSA key size");
+        let e = BigUInt::<N>::from(PUBLIC_EXPONENT);
+        loop {
+            let p = BigUInt::<N>::random_prime(bits.div_ceil(2), rng);
+            let q = BigUInt::<N>::random_prime(bits / 2, rng);
+            if p == q || p.strict_mul(&q).bits() != bits {
+                continue;
+            }
+            let one = BigUInt::<N>::from(1u8);
+            let phi = (p - one).strict_mul(&(q - one));
+            if let Some(d) = e.inv_mod(&phi) {
+                if let Ok(key) = RsaPrivateKey::from_components(p * q, e, d, p, q) {
+                    return key;
+                }
+            }
+        }
+    }
+
+    /// Builds a private key from its components, precomputing the CRT
+    /// parameters and checking that they are consistent.
+    pub fn from_components(
+        n: BigUInt<N>,
+        e: BigUInt<N>,
+        d: BigUInt<N>,
+        p: BigUInt<N>,
+        q: BigUInt<N>,
+    ) -> Result<Self, RsaError> {
+        let public = RsaPublicKey::new(n, e)?;
+        let one = BigUInt::<N>::from(1u8);
+        if p <= one || q <= one || p.checked_mul(&q) != Some(n) {
+            return Err(RsaError::InvalidKey);
+        }
+        let dp = d.strict_rem(&(p - one));
+        let dq = d.strict_rem(&(q - one));
+        let qinv = q.inv_mod(&p).ok_or(RsaError::InvalidKey)?;
+        // e * d must be 1 modulo both p - 1 and q - 1.
+        if e.mul_mod(&dp, &(p - one)) != one || e.mul_mod(&dq, &(q - one)) != one {
+            return Err(RsaError::InvalidKey);
+        }
+        Ok(RsaPrivateKey {
+            public,
+            d,
+            p,
+            q,
+            dp,
+            dq,
+            qinv,
+        })
+    }
+
+    pub fn public_key(&self) -> &RsaPublicKey<N> {
+        &self.public
+    }
+
+    pub fn d(&self) -> &BigUInt<N> {
+        &self.d
+    }
+
+    pub fn primes(&self) -> (&BigUInt<N>, &BigUInt<N>) {
+        (&self.p, &self.q)
+    }
+
+    /// RSADP: returns `c^d mod n`, using the Chinese remainder theorem to
+    /// exponentiate modulo `p` and `q` separately. The exponentiations use
+    /// `pow_mod_secret`, so their sequence of operations does not depend on
+    /// the private exponents.
+    pub fn raw_decrypt(&self, c: &BigUInt<N>) -> Result<BigUInt<N>, RsaError> {
+        if *c >= self.public.n {
+            return Err(RsaError::MessageTooLong);
+        }
+        let m1 = c.pow_mod_secret(&self.dp, &self.p);
+        let m2 = c.pow_mod_secret(&self.dq, &self.q);
+        let h = self
+            .qinv
+            .mul_mod(&m1.sub_mod(&m2.strict_rem(&self.p), &self.p), &self.p);
+        Ok(m2 + h * self.q)
+    }
+
+    /// RSAES-OAEP decryption with SHA-256 and an optional label. All padding
+    /// failures are reported as the same error.
+    pub fn decrypt_oaep(&self, ciphertext: &[u8], label: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.public.size();
+        if ciphertext.len() != k || k < 2 * HASH_LEN + 2 {
+            return Err(RsaError::Decryption);
+        }
+        let c = BigUInt::from_be_bytes(ciphertext).map_err(|_| RsaError::Decryption)?;
+        let m = self.raw_decrypt(&c).map_err(|_| RsaError::Decryption)?;
+        let mut em = m.to_be_bytes_padded(k).ok_or(RsaError::Decryption)?;
+
+        let (y, rest) = em.split_at_mut(1);
+        let (seed, db) = rest.split_at_mut(HASH_LEN);
+        mgf1_xor(seed, db);
+        mgf1_xor(db, seed);
+
+        // Check everything before reporting, so the failure reason does not leak.
+        let mut bad = y[0] != 0;
+        bad |= db[..HASH_LEN] != Sha256::digest(label)[..];
+        let mut separator = None;
+        for (i, &byte) in db.iter().enumerate().skip(HASH_LEN) {
+            if separator.is_none() {
+                if byte == 0x01 {
+                    separator = Some(i);
+                } else if byte != 0 {
+                    bad = true;
+                }
+            }
+        }
+        match separator {
+            Some(i) if !bad => Ok(db[i + 1..].to_vec()),
+            _ => Err(RsaError::Decryption),
+        }
+    }
+
+    /// RSASSA-PSS signature with SHA-256 and a random 32-byte salt.
+    pub fn sign_pss<R: RngCore>(&self, message: &[u8], rng: &mut R) -> Result<Vec<u8>, RsaError> {
+        let em_bits = self.public.n.bits() - 1;
+        let em_len = em_bits.div_ceil(8);
+        if em_len < HASH_LEN + SALT_LEN + 2 {
+            return Err(RsaError::MessageTooLong);
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let h = Sha256::new()
+            .chain_update([0u8; 8])
+            .chain_update(Sha256::digest(message))
+            .chain_update(salt)
+            .finalize();
+
+        // EM = maskedDB || H || 0xbc, DB = PS || 0x01 || salt
+        let mut em = vec![0; em_len];
+        let (db, rest) = em.split_at_mut(em_len - HASH_LEN - 1);
+        let db_len = db.len();
+        db[db_len - SALT_LEN - 1] = 0x01;
+        db[db_len - SALT_LEN..].copy_from_slice(&salt);
+        mgf1_xor(db, &h);
+        db[0] &= 0xff >> (8 * em_len - em_bits);
+        rest[..HASH_LEN].copy_from_slice(&h);
+        rest[HASH_LEN] = 0xbc;
+
+        let m = BigUInt::from_be_bytes(&em).map_err(|_| RsaError::MessageTooLong)?;
+        let s = self.raw_decrypt(&m)?;
+        s.to_be_bytes_padded(self.public.size())
+            .ok_or(RsaError::MessageTooLong)
+    }
+}
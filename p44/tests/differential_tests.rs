@@ -0,0 +1,357 @@
+//! Differential tests of `BigUInt<N>` against `num_bigint::BigUint`.
+//!
+//! Every property runs on random operands for small sizes and all named
+//! sizes. Operands are biased towards zero, one and all-ones limbs and towards
+//! short values, where carries, normalization and shifts by whole limbs go
+//! wrong most often.
+//!
+//! Failing cases are shrunk and their seeds saved next to this file in
+//! `differential_tests.proptest-regressions`, which is replayed before new
+//! cases on every run. Set `PROPTEST_RNG_SEED` to repeat a whole run and
+//! `PROPTEST_CASES` to change the number of cases per property.
+
+use num_bigint::BigUint;
+use p44::biguint::BigUInt;
+use proptest::prelude::*;
+use proptest::test_runner::{FileFailurePersistence, TestCaseError};
+
+fn config(cases: u32) -> ProptestConfig {
+    let mut config = ProptestConfig {
+        failure_persistence: Some(Box::new(FileFailurePersistence::WithSource(
+            "proptest-regressions",
+        ))),
+        ..ProptestConfig::default()
+    };
+    if std::env::var_os("PROPTEST_CASES").is_none() {
+        config.cases = cases;
+    }
+    config
+}
+
+fn limb() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        1 => Just(0),
+        1 => Just(1),
+        1 => Just(u64::MAX),
+        1 => Just(1 << 63),
+        4 => any::<u64>(),
+    ]
+}
+
+/// Values with a random number of significant limbs.
+fn biguint<const N: usize>() -> impl Strategy<Value = BigUInt<N>> {
+    (prop::collection::vec(limb(), N), 0..=N).prop_map(|(limbs, len)| {
+        let mut result = BigUInt::<N>::new();
+        result.data[..len].copy_from_slice(&limbs[..len]);
+        result
+    })
+}
+
+fn nonzero_biguint<const N: usize>() -> impl Strategy<Value = BigUInt<N>> {
+    biguint::<N>().prop_filter("nonzero", |x| !x.is_zero())
+}
+
+/// Shift counts in `0..=128 * N`, often an exact number of limbs.
+fn shift<const N: usize>() -> impl Strategy<Value = usize> {
+    prop_oneof![(0..=2 * N).prop_map(|limbs| 64 * limbs), 0..=128 * N]
+}
+
+fn reference<const N: usize>(x: &BigUInt<N>) -> BigUint {
+    BigUint::new(
+        x.data
+            .iter()
+            .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+            .collect(),
+    )
+}
+
+/// Converts back, keeping the low `N` limbs.
+fn wrapped<const N: usize>(x: &BigUint) -> BigUInt<N> {
+    let mut result = BigUInt::<N>::new();
+    for (i, limb) in x.iter_u64_digits().take(N).enumerate() {
+        result.data[i] = limb;
+    }
+    result
+}
+
+/// Converts back the limbs from `N` up to `2 * N`.
+fn high_half<const N: usize>(x: &BigUint) -> BigUInt<N> {
+    wrapped(&(x >> (64 * N)))
+}
+
+fn fits<const N: usize>(x: &BigUint) -> bool {
+    x.bits() <= 64 * N as u64
+}
+
+fn reference_gcd(mut a: BigUint, mut b: BigUint) -> BigUint {
+    while b != BigUint::ZERO {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+fn check_add_sub<const N: usize>(a: BigUInt<N>, b: BigUInt<N>) -> Result<(), TestCaseError> {
+    let (ra, rb) = (reference(&a), reference(&b));
+    let sum = &ra + &rb;
+    prop_assert_eq!(a.overflowing_add(&b), (wrapped(&sum), !fits::<N>(&sum)));
+    let difference = (BigUint::from(1u8) << (64 * N)) + &ra - &rb;
+    prop_assert_eq!(a.overflowing_sub(&b), (wrapped(&difference), ra < rb));
+    prop_assert_eq!(a.cmp(&b), ra.cmp(&rb));
+    Ok(())
+}
+
+fn check_mul<const N: usize, const M: usize>(
+    a: BigUInt<N>,
+    b: BigUInt<N>,
+    carry: BigUInt<N>,
+    x: u64,
+) -> Result<(), TestCaseError> {
+    let (ra, rb) = (reference(&a), reference(&b));
+    let product = &ra * &rb + reference(&carry);
+    let expected = (wrapped(&product), high_half(&product));
+    prop_assert_eq!(a.carrying_mul(&b, &mut carry.clone()), expected);
+    prop_assert_eq!(a.carrying_mul_schoolbook(&b, &mut carry.clone()), expected);
+    prop_assert_eq!(a.carrying_mul_karatsuba(&b, &mut carry.clone()), expected);
+    prop_assert_eq!(a.carrying_mul_toom3(&b, &mut carry.clone()), expected);
+    prop_assert_eq!(a.carrying_mul_shift_add(&b, &mut carry.clone()), expected);
+    prop_assert_eq!(a.widening_mul::<M>(&b), wrapped(&(&ra * &rb)));
+
+    let by_limb = &ra * x + carry.data[0];
+    let (low, high) = a.carrying_mul_by_u64(x, carry.data[0]);
+    prop_assert_eq!(low, wrapped(&by_limb));
+    prop_assert_eq!(BigUint::from(high), by_limb >> (64 * N));
+    Ok(())
+}
+
+fn check_div_rem<const N: usize>(a: BigUInt<N>, b: BigUInt<N>) -> Result<(), TestCaseError> {
+    let (ra, rb) = (reference(&a), reference(&b));
+    prop_assert_eq!(
+        a.div_rem(&b),
+        (wrapped(&(&ra / &rb)), wrapped(&(&ra % &rb)))
+    );
+    prop_assert_eq!(a.checked_div_rem(&BigUInt::new()), None);
+    Ok(())
+}
+
+fn check_bits<const N: usize>(a: BigUInt<N>, shift: usize) -> Result<(), TestCaseError> {
+    let ra = reference(&a);
+    prop_assert_eq!(a.bits() as u64, ra.bits());
+    prop_assert_eq!(a.leading_zeros() as u64, 64 * N as u64 - ra.bits());
+    let trailing = ra.trailing_zeros().unwrap_or(64 * N as u64);
+    prop_assert_eq!(a.trailing_zeros() as u64, trailing);
+    prop_assert_eq!(a.is_power_of_two(), ra.count_ones() == 1);
+    if shift < 64 * N {
+        prop_assert_eq!(a.bit(shift), ra.bit(shift as u64));
+    }
+
+    let shifted = &ra << shift;
+    prop_assert_eq!(
+        a.widening_shl(shift),
+        (wrapped(&shifted), high_half(&shifted))
+    );
+    let underflow = (&ra << (64 * N)) >> shift;
+    prop_assert_eq!(
+        a.widening_shr(shift),
+        (high_half(&underflow), wrapped(&underflow))
+    );
+    let in_range = shift < 64 * N;
+    prop_assert_eq!(a.checked_shl(shift), in_range.then(|| wrapped(&shifted)));
+    prop_assert_eq!(
+        a.checked_shr(shift),
+        in_range.then(|| wrapped(&(&ra >> shift)))
+    );
+
+    let amount = shift % (64 * N);
+    prop_assert_eq!(
+        a.overflowing_shl(shift),
+        (wrapped(&(&ra << amount)), !in_range)
+    );
+    prop_assert_eq!(
+        a.overflowing_shr(shift),
+        (wrapped(&(&ra >> amount)), !in_range)
+    );
+    let left = (&ra << amount) | (&ra >> (64 * N - amount));
+    let right = (&ra >> amount) | (&ra << (64 * N - amount));
+    prop_assert_eq!(a.rotate_left(shift), wrapped(&left));
+    prop_assert_eq!(a.rotate_right(shift), wrapped(&right));
+
+    let next = if ra <= BigUint::from(1u8) {
+        BigUint::from(1u8)
+    } else {
+        BigUint::from(1u8) << (&ra - 1u8).bits()
+    };
+    prop_assert_eq!(
+        a.checked_next_power_of_two(),
+        fits::<N>(&next).then(|| wrapped(&next))
+    );
+    Ok(())
+}
+
+fn check_text_and_bytes<const N: usize>(a: BigUInt<N>) -> Result<(), TestCaseError> {
+    let ra = reference(&a);
+    let hex = format!("{:x}", ra);
+    prop_assert_eq!(a.to_string(), format!("0x{:0>width$}", hex, width = 16 * N));
+    prop_assert_eq!(hex.parse::<BigUInt<N>>(), Ok(a));
+    prop_assert_eq!(format!("0x{}", hex).parse::<BigUInt<N>>(), Ok(a));
+    // A leading digit beyond the `N` limbs must be rejected, not truncated.
+    let too_large = format!("{:x}", (BigUint::from(1u8) << (64 * N)) + &ra);
+    prop_assert!(too_large.parse::<BigUInt<N>>().is_err());
+
+    let be = a.to_be_bytes_minimal();
+    prop_assert_eq!(
+        &be,
+        &if a.is_zero() {
+            vec![]
+        } else {
+            ra.to_bytes_be()
+        }
+    );
+    prop_assert_eq!(
+        a.to_le_bytes_minimal(),
+        if a.is_zero() {
+            vec![]
+        } else {
+            ra.to_bytes_le()
+        }
+    );
+    prop_assert_eq!(BigUInt::<N>::from_be_bytes(&be), Ok(a));
+    prop_assert_eq!(BigUInt::<N>::from_le_bytes(&a.to_le_bytes()), Ok(a));
+    prop_assert_eq!(BigUInt::<N>::from_der(&a.to_der()), Ok(a));
+    Ok(())
+}
+
+fn check_pow_and_roots<const N: usize>(
+    a: BigUInt<N>,
+    exponent: u32,
+    order: u32,
+) -> Result<(), TestCaseError> {
+    let ra = reference(&a);
+    let power = ra.pow(exponent);
+    prop_assert_eq!(
+        a.overflowing_pow(exponent),
+        (wrapped(&power), !fits::<N>(&power))
+    );
+    prop_assert_eq!(a.isqrt(), wrapped(&ra.sqrt()));
+    prop_assert_eq!(a.nth_root(order), wrapped(&ra.nth_root(order)));
+    Ok(())
+}
+
+fn check_gcd_lcm<const N: usize>(a: BigUInt<N>, b: BigUInt<N>) -> Result<(), TestCaseError> {
+    let (ra, rb) = (reference(&a), reference(&b));
+    let gcd = reference_gcd(ra.clone(), rb.clone());
+    prop_assert_eq!(a.gcd(&b), wrapped(&gcd));
+    let lcm = if gcd == BigUint::ZERO {
+        BigUint::ZERO
+    } else {
+        &ra / &gcd * &rb
+    };
+    prop_assert_eq!(a.overflowing_lcm(&b), (wrapped(&lcm), !fits::<N>(&lcm)));
+    Ok(())
+}
+
+fn check_modular<const N: usize>(
+    a: BigUInt<N>,
+    b: BigUInt<N>,
+    exponent: u64,
+    modulus: BigUInt<N>,
+) -> Result<(), TestCaseError> {
+    let rm = reference(&modulus);
+    let (a, b) = (a.strict_rem(&modulus), b.strict_rem(&modulus));
+    let (ra, rb) = (reference(&a), reference(&b));
+    prop_assert_eq!(a.add_mod(&b, &modulus), wrapped(&((&ra + &rb) % &rm)));
+    prop_assert_eq!(a.sub_mod(&b, &modulus), wrapped(&((&ra + &rm - &rb) % &rm)));
+    prop_assert_eq!(a.mul_mod(&b, &modulus), wrapped(&(&ra * &rb % &rm)));
+    let exponent_big = BigUInt::<N>::from(exponent);
+    prop_assert_eq!(
+        a.pow_mod(&exponent_big, &modulus),
+        wrapped(&ra.modpow(&BigUint::from(exponent), &rm))
+    );
+    if exponent_big.bits() <= modulus.bits() {
+        prop_assert_eq!(
+            a.pow_mod_secret(&exponent_big, &modulus),
+            a.pow_mod(&exponent_big, &modulus)
+        );
+    }
+    match a.inv_mod(&modulus) {
+        Some(inverse) => {
+            prop_assert!(inverse < modulus);
+            prop_assert_eq!(reference(&inverse) * &ra % &rm, BigUint::from(1u8) % &rm);
+        }
+        None => prop_assert_ne!(reference_gcd(ra, rm), BigUint::from(1u8)),
+    }
+    Ok(())
+}
+
+/// Instantiates every property for `N` limbs, with `M == 2 * N` for the
+/// widening product, running `cases` cases per property by default.
+macro_rules! differential_tests {
+    ($name:ident, $N:expr, $M:expr, $cases:expr) => {
+        mod $name {
+            use super::*;
+
+            proptest! {
+                #![proptest_config(config($cases))]
+
+                #[test]
+                fn add_sub(a in biguint::<$N>(), b in biguint::<$N>()) {
+                    check_add_sub(a, b)?;
+                }
+
+                #[test]
+                fn mul(
+                    a in biguint::<$N>(),
+                    b in biguint::<$N>(),
+                    carry in biguint::<$N>(),
+                    x in limb(),
+                ) {
+                    check_mul::<$N, $M>(a, b, carry, x)?;
+                }
+
+                #[test]
+                fn div_rem(a in biguint::<$N>(), b in nonzero_biguint::<$N>()) {
+                    check_div_rem(a, b)?;
+                }
+
+                #[test]
+                fn bits_and_shifts(a in biguint::<$N>(), shift in shift::<$N>()) {
+                    check_bits(a, shift)?;
+                }
+
+                #[test]
+                fn text_and_bytes(a in biguint::<$N>()) {
+                    check_text_and_bytes(a)?;
+                }
+
+                #[test]
+                fn pow_and_roots(a in biguint::<$N>(), exponent in 0u32..40, order in 1u32..12) {
+                    check_pow_and_roots(a, exponent, order)?;
+                }
+
+                #[test]
+                fn gcd_lcm(a in biguint::<$N>(), b in biguint::<$N>()) {
+                    check_gcd_lcm(a, b)?;
+                }
+
+                #[test]
+                fn modular(
+                    a in biguint::<$N>(),
+                    b in biguint::<$N>(),
+                    exponent in any::<u64>(),
+                    modulus in nonzero_biguint::<$N>(),
+                ) {
+                    check_modular(a, b, exponent, modulus)?;
+                }
+            }
+        }
+    };
+}
+
+differential_tests!(limbs_1, 1, 2, 256);
+differential_tests!(limbs_2, 2, 4, 256);
+differential_tests!(limbs_3, 3, 6, 128);
+differential_tests!(limbs_16, 16, 32, 32);
+differential_tests!(limbs_32, 32, 64, 16);
+differential_tests!(limbs_64, 64, 128, 8);
+differential_tests!(limbs_128, 128, 256, 4);
@@ -0,0 +1,293 @@
+use hex_literal::hex;
+use p44::biguint::BigUInt;
+use p44::ec::EcError;
+use p44::field::{FieldElement, FieldParams};
+use p44::p256::{P256Base, P256Order, P256Point, PublicKey, SecretKey, Signature};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The Goldilocks-style prime `2^64 - 2^32 + 1`, small enough to check against `u128`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SmallPrime;
+
+impl FieldParams<1> for SmallPrime {
+    const MODULUS: BigUInt<1> = BigUInt {
+        data: [0xffffffff00000001],
+    };
+}
+
+type Small = FieldElement<SmallPrime, 1>;
+const SMALL_P: u128 = 0xffffffff00000001;
+
+fn small(x: u64) -> Small {
+    Small::from_biguint_reduced(&BigUInt::from(x))
+}
+
+fn biguint(bytes: &[u8]) -> BigUInt<4> {
+    BigUInt::from_be_bytes(bytes).unwrap()
+}
+
+/// Random scalars with a clear top limb, so sums of two cannot overflow.
+fn random_below_2_192(rng: &mut StdRng) -> BigUInt<4> {
+    let mut x = BigUInt::<4>::random(rng);
+    x.data[3] = 0;
+    x
+}
+
+#[test]
+fn test_small_field_matches_u128() {
+    let values = [0, 1, 2, 0xffffffff, 0xffffffff00000000, 0x123456789abcdef];
+    for &x in &values {
+        for &y in &values {
+            let (a, b) = (small(x), small(y));
+            let (x, y) = (x as u128 % SMALL_P, y as u128 % SMALL_P);
+            let expect = |v: u128| BigUInt::<1>::from(v as u64);
+            assert_eq!((a + b).to_biguint(), expect((x + y) % SMALL_P));
+            assert_eq!((a - b).to_biguint(), expect((x + SMALL_P - y) % SMALL_P));
+            assert_eq!((a * b).to_biguint(), expect(x * y % SMALL_P));
+            assert_eq!((-a).to_biguint(), expect((SMALL_P - x) % SMALL_P));
+        }
+    }
+    assert_eq!(Small::one().to_biguint(), BigUInt::from(1u8));
+    assert_eq!(Small::from_biguint(&SmallPrime::MODULUS), None);
+}
+
+#[test]
+fn test_field_inverse_and_pow() {
+    let mut rng = StdRng::seed_from_u64(36);
+    for _ in 0..10 {
+        let x = BigUInt::<4>::random(&mut rng);
+        let a = FieldElement::<P256Base, 4>::from_biguint_reduced(&x);
+        let b = FieldElement::<P256Order, 4>::from_biguint_reduced(&x);
+        assert_eq!(a * a.invert().unwrap(), FieldElement::one());
+        assert_eq!(b * b.invert().unwrap(), FieldElement::one());
+        assert_eq!(
+            (a * a).to_biguint(),
+            a.to_biguint().mul_mod(&a.to_biguint(), &P256Base::MODULUS)
+        );
+        assert_eq!(a.square(), a.pow(&BigUInt::from(2u8)));
+        assert_eq!(
+            a.pow(&x).to_biguint(),
+            a.to_biguint().pow_mod(&x, &P256Base::MODULUS)
+        );
+    }
+    assert_eq!(FieldElement::<P256Base, 4>::zero().invert(), None);
+}
+
+#[test]
+fn test_point_arithmetic() {
+    let g = P256Point::generator();
+    let two_g = P256Point::from_affine(
+        &biguint(&hex!(
+            "7cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc47669978"
+        )),
+        &biguint(&hex!(
+            "07775510db8ed040293d9ac69f7430dbba7dade63ce982299e04b79d227873d1"
+        )),
+    )
+    .unwrap();
+    let three_g = P256Point::from_affine(
+        &biguint(&hex!(
+            "5ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c"
+        )),
+        &biguint(&hex!(
+            "8734640c4998ff7e374b06ce1a64a2ecd82ab036384fb83d9a79b127a27d5032"
+        )),
+    )
+    .unwrap();
+    assert_eq!(g.double(), two_g);
+    assert_eq!(g + g, two_g);
+    assert_eq!(two_g + g, three_g);
+    assert_eq!(three_g - g, two_g);
+    assert_eq!(g.mul(&BigUInt::from(3u8)), three_g);
+
+    let identity = P256Point::identity();
+    assert!(identity.is_identity());
+    assert_eq!(g + identity, g);
+    assert_eq!(identity + g, g);
+    assert_eq!(identity.double(), identity);
+    assert!((g - g).is_identity());
+    assert_ne!(g, identity);
+    assert_eq!(identity.to_affine(), None);
+    assert!(g.mul(&P256Order::MODULUS).is_identity());
+    assert!(g.mul(&BigUInt::new()).is_identity());
+
+    // NIST point multiplication vector for k = 112233445566778899.
+    let k = BigUInt::<4>::from(112233445566778899u64);
+    let (x, y) = g.mul(&k).to_affine().unwrap();
+    assert_eq!(
+        x.to_be_bytes(),
+        hex!("339150844ec15234807fe862a86be77977dbfb3ae3d96f4c22795513aeaab82f")
+    );
+    assert_eq!(
+        y.to_be_bytes(),
+        hex!("b1c14ddfdc8ec1b2583f51e85a5eb3a155840f2034730e9b5ada38b674336a21")
+    );
+
+    let mut rng = StdRng::seed_from_u64(256);
+    for _ in 0..3 {
+        let a = random_below_2_192(&mut rng);
+        let b = random_below_2_192(&mut rng);
+        assert_eq!(g.mul(&a) + g.mul(&b), g.mul(&(a + b)));
+        assert_eq!(g.mul(&a).mul(&b), g.mul(&b).mul(&a));
+    }
+}
+
+#[test]
+fn test_point_encoding() {
+    let g = P256Point::generator();
+    let encoded = g.to_uncompressed().unwrap();
+    assert_eq!(encoded.len(), 65);
+    assert_eq!(P256Point::from_uncompressed(&encoded), Ok(g));
+    let mut off_curve = encoded.clone();
+    off_curve[64] ^= 1;
+    assert_eq!(
+        P256Point::from_uncompressed(&off_curve),
+        Err(EcError::InvalidPoint)
+    );
+    let mut compressed = encoded.clone();
+    compressed[0] = 0x02;
+    assert_eq!(
+        P256Point::from_uncompressed(&compressed),
+        Err(EcError::InvalidPoint)
+    );
+    assert_eq!(
+        P256Point::from_uncompressed(&encoded[..64]),
+        Err(EcError::InvalidPoint)
+    );
+    assert_eq!(P256Point::identity().to_uncompressed(), None);
+}
+
+#[test]
+fn test_ecdh_nist_vector() {
+    // First P-256 vector from the NIST CAVS ECC CDH primitive test file.
+    let peer = PublicKey::from_uncompressed(&hex!(
+        "04"
+        "700c48f77f56584c5cc632ca65640db91b6bacce3a4df6b42ce7cc838833d287"
+        "db71e509e3fd9b060ddb20ba5c51dcc5948d46fbf640dfe0441782cab85fa4ac"
+    ))
+    .unwrap();
+    let secret = SecretKey::from_bytes(&hex!(
+        "7d7dc5f71eb29ddaf80d6214632eeae03d9058af1fb6d22ed80badb62bc1a534"
+    ))
+    .unwrap();
+    assert_eq!(
+        secret.public_key().to_uncompressed(),
+        hex!(
+            "04"
+            "ead218590119e8876b29146ff89ca61770c4edbbf97d38ce385ed281d8a6b230"
+            "28af61281fd35e2fa7002523acc85a429cb06ee6648325389f59edfce1405141"
+        )
+    );
+    assert_eq!(
+        secret.diffie_hellman(&peer),
+        hex!("46fc62106420ff012e54a434fbdd2d25ccc5852060561e68040dd7778997bd7b")
+    );
+}
+
+#[test]
+fn test_ecdh_agreement() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let alice = SecretKey::random(&mut rng);
+    let bob = SecretKey::random(&mut rng);
+    assert_eq!(
+        alice.diffie_hellman(&bob.public_key()),
+        bob.diffie_hellman(&alice.public_key())
+    );
+}
+
+#[test]
+fn test_ecdsa_rfc6979_vectors() {
+    // RFC 6979, appendix A.2.5, with SHA-256.
+    let secret = SecretKey::from_bytes(&hex!(
+        "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"
+    ))
+    .unwrap();
+    let public = secret.public_key();
+    assert_eq!(
+        public.to_uncompressed(),
+        hex!(
+            "04"
+            "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"
+            "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+        )
+    );
+    let cases: [(&[u8], [u8; 64]); 2] = [
+        (
+            b"sample",
+            hex!(
+                "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716"
+                "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"
+            ),
+        ),
+        (
+            b"test",
+            hex!(
+                "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367"
+                "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083"
+            ),
+        ),
+    ];
+    for (message, expected) in cases {
+        let signature = secret.sign(message);
+        assert_eq!(signature.to_bytes(), expected);
+        assert_eq!(Signature::from_bytes(&expected), Ok(signature));
+        assert_eq!(public.verify(message, &signature), Ok(()));
+    }
+}
+
+#[test]
+fn test_ecdsa_rejects_invalid_signatures() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let secret = SecretKey::random(&mut rng);
+    let public = secret.public_key();
+    let signature = secret.sign(b"message");
+    assert_eq!(public.verify(b"message", &signature), Ok(()));
+    assert_eq!(
+        public.verify(b"massage", &signature),
+        Err(EcError::InvalidSignature)
+    );
+    let other = SecretKey::random(&mut rng).public_key();
+    assert_eq!(
+        other.verify(b"message", &signature),
+        Err(EcError::InvalidSignature)
+    );
+
+    let mut bytes = signature.to_bytes();
+    bytes[63] ^= 1;
+    let tampered = Signature::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        public.verify(b"message", &tampered),
+        Err(EcError::InvalidSignature)
+    );
+    assert_eq!(
+        Signature::from_bytes(&[0; 64]),
+        Err(EcError::InvalidSignature)
+    );
+    let mut out_of_range = [0xff; 64];
+    out_of_range[..32].copy_from_slice(&signature.to_bytes()[..32]);
+    assert_eq!(
+        Signature::from_bytes(&out_of_range),
+        Err(EcError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_secret_key_range() {
+    assert_eq!(
+        SecretKey::from_bytes(&[0; 32]).err(),
+        Some(EcError::InvalidScalar)
+    );
+    assert_eq!(
+        SecretKey::from_bytes(&P256Order::MODULUS.to_be_bytes()).err(),
+        Some(EcError::InvalidScalar)
+    );
+    assert_eq!(
+        SecretKey::from_bytes(&[1; 31]).err(),
+        Some(EcError::InvalidScalar)
+    );
+    let n_minus_one = P256Order::MODULUS - BigUInt::from(1u8);
+    let key = SecretKey::from_bytes(&n_minus_one.to_be_bytes()).unwrap();
+    assert_eq!(key.public_key().point(), &-P256Point::generator());
+    assert_eq!(format!("{key:?}"), "SecretKey { .. }");
+}
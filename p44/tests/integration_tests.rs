@@ -13,3 +13,999 @@ fn test_empty_hash_map_macro() {
     let map: std::collections::HashMap<&str, &str> = p44::hash_map! {};
     assert!(map.is_empty());
 }
+
+use p44::biguint::{BigUInt, BigUInt1024, BigUInt2048, BigUInt4096, BigUInt8192};
+
+/// Deterministic xorshift generator so failures are reproducible.
+fn random_biguint<const N: usize>(state: &mut u64) -> BigUInt<N> {
+    let mut result = BigUInt::<N>::new();
+    for limb in result.data.iter_mut() {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *limb = *state;
+    }
+    result
+}
+
+fn check_mul_algorithms_agree<const N: usize>(seed: u64) {
+    let mut state = seed;
+    for _ in 0..4 {
+        let a = random_biguint::<N>(&mut state);
+        let b = random_biguint::<N>(&mut state);
+        let carry = random_biguint::<N>(&mut state);
+        let expected = a.carrying_mul_shift_add(&b, &mut carry.clone());
+        assert_eq!(a.carrying_mul_schoolbook(&b, &mut carry.clone()), expected);
+        assert_eq!(a.carrying_mul_karatsuba(&b, &mut carry.clone()), expected);
+        assert_eq!(a.carrying_mul_toom3(&b, &mut carry.clone()), expected);
+        assert_eq!(a.carrying_mul(&b, &mut carry.clone()), expected);
+    }
+}
+
+#[test]
+fn test_mul_algorithms_agree_small_sizes() {
+    check_mul_algorithms_agree::<1>(1);
+    check_mul_algorithms_agree::<3>(2);
+    check_mul_algorithms_agree::<5>(3);
+    check_mul_algorithms_agree::<9>(4);
+    check_mul_algorithms_agree::<13>(5);
+    check_mul_algorithms_agree::<31>(6);
+}
+
+#[test]
+fn test_mul_algorithms_agree_named_sizes() {
+    check_mul_algorithms_agree::<16>(7);
+    check_mul_algorithms_agree::<32>(8);
+    check_mul_algorithms_agree::<64>(9);
+    check_mul_algorithms_agree::<128>(10);
+}
+
+#[test]
+fn test_mul_max_values() {
+    fn check<const N: usize>() {
+        let max = BigUInt::<N> {
+            data: [u64::MAX; N],
+        };
+        let mut one = BigUInt::<N>::new();
+        one.data[0] = 1;
+        // (2^k - 1)^2 = (2^k - 2) * 2^k + 1
+        let (low, high) = max.carrying_mul(&max, &mut BigUInt::<N>::new());
+        assert_eq!(low, one);
+        assert_eq!(high, max - one);
+        // Adding a full carry makes the low half wrap to zero.
+        let (low, high) = max.carrying_mul(&max, &mut max.clone());
+        assert_eq!(low, BigUInt::<N>::new());
+        assert_eq!(high, max);
+    }
+    check::<16>();
+    check::<32>();
+    check::<64>();
+    check::<128>();
+}
+
+#[test]
+fn test_mul_named_size_overflow() {
+    let mut state = 11;
+    let a = random_biguint::<32>(&mut state);
+    assert_eq!(a.checked_mul(&BigUInt2048::new()), Some(BigUInt2048::new()));
+    assert_eq!(a.checked_mul(&a), None);
+    let mut three = BigUInt1024::new();
+    three.data[0] = 3;
+    let b = random_biguint::<16>(&mut state);
+    assert_eq!(b * three, b + b + b);
+    let c = random_biguint::<64>(&mut state);
+    assert_eq!(c * BigUInt4096::new(), BigUInt4096::new());
+    let d = random_biguint::<128>(&mut state);
+    assert_eq!(d.checked_mul(&d), None);
+    assert_eq!(d.checked_mul(&BigUInt8192::new()), Some(BigUInt8192::new()));
+}
+
+#[test]
+fn test_widen_and_narrow() {
+    let mut state = 12;
+    let a = random_biguint::<16>(&mut state);
+    let wide: BigUInt4096 = a.widen();
+    assert_eq!(&wide.data[..16], &a.data);
+    assert!(wide.data[16..].iter().all(|&limb| limb == 0));
+    assert_eq!(wide.checked_narrow::<16>(), Some(a));
+    assert_eq!(BigUInt4096::from(a), wide);
+    assert_eq!(BigUInt1024::try_from(wide), Ok(a));
+
+    let b = random_biguint::<64>(&mut state);
+    assert_eq!(b.checked_narrow::<16>(), None);
+    assert!(BigUInt1024::try_from(b).is_err());
+    assert_eq!(b.wrapping_narrow::<16>().data, b.data[..16]);
+    assert_eq!(BigUInt8192::from(b).checked_narrow::<64>(), Some(b));
+    assert_eq!(
+        BigUInt2048::from(BigUInt1024::from(7u8)),
+        BigUInt2048::from(7u8)
+    );
+}
+
+#[test]
+fn test_halves_and_widening_mul() {
+    let mut state = 13;
+    let a = random_biguint::<16>(&mut state);
+    let b = random_biguint::<16>(&mut state);
+    let (low, high) = a.overflowing_mul(&b);
+    let product: BigUInt2048 = a.widening_mul(&b);
+    assert_eq!(product, BigUInt2048::from_halves(&low, &high));
+    assert_eq!(product.split_halves::<16>(), (low, high));
+
+    let c = random_biguint::<64>(&mut state);
+    let d = random_biguint::<64>(&mut state);
+    let (low, high) = c.overflowing_mul(&d);
+    let product: BigUInt8192 = c.widening_mul(&d);
+    assert_eq!(product.split_halves(), (low, high));
+}
+
+#[test]
+fn test_primitive_conversions() {
+    assert_eq!(BigUInt1024::from(0xabu8).data[0], 0xab);
+    assert_eq!(BigUInt1024::from(u64::MAX).data[..2], [u64::MAX, 0]);
+    let big = BigUInt1024::from(u128::MAX);
+    assert_eq!(big.data[..3], [u64::MAX, u64::MAX, 0]);
+    assert_eq!(u128::try_from(big), Ok(u128::MAX));
+    assert!(u64::try_from(big).is_err());
+    assert_eq!(u16::try_from(BigUInt2048::from(500u32)), Ok(500));
+    assert!(u8::try_from(BigUInt2048::from(500u32)).is_err());
+    let mut huge = BigUInt4096::new();
+    huge.data[2] = 1;
+    assert!(u128::try_from(huge).is_err());
+    assert_eq!(u32::try_from(BigUInt::<1>::from(42u64)), Ok(42));
+}
+
+#[test]
+fn test_div_rem() {
+    let mut state = 14;
+    fn check<const N: usize>(state: &mut u64) {
+        for divisor_limbs in [1, 2, N / 2, N] {
+            let a = random_biguint::<N>(state);
+            let mut b = random_biguint::<N>(state);
+            b.data[divisor_limbs..].fill(0);
+            let (q, r) = a.div_rem(&b);
+            assert!(r < b);
+            let (product, high) = q.overflowing_mul(&b);
+            assert_eq!(high, BigUInt::<N>::new());
+            assert_eq!(product.checked_add(&r), Some(a));
+            assert_eq!(a / b, q);
+            assert_eq!(a % b, r);
+        }
+    }
+    check::<2>(&mut state);
+    check::<16>(&mut state);
+    check::<32>(&mut state);
+    check::<64>(&mut state);
+    check::<128>(&mut state);
+    let a = random_biguint::<16>(&mut state);
+    assert_eq!(a.checked_div(&BigUInt1024::new()), None);
+    assert_eq!(a.checked_rem(&a), Some(BigUInt1024::new()));
+    assert_eq!(a.checked_div(&a), Some(BigUInt1024::from(1u8)));
+}
+
+#[test]
+fn test_biguint_ordering() {
+    let mut a = BigUInt1024::from(5u8);
+    let b = BigUInt1024::from(u64::MAX);
+    assert!(a < b);
+    a.data[15] = 1;
+    assert!(a > b);
+    assert_eq!(a.max(b), a);
+}
+
+#[test]
+fn test_biguint_from_str_all_sizes() {
+    let a: BigUInt1024 = "0x1234".parse().unwrap();
+    assert_eq!(a, BigUInt1024::from(0x1234u16));
+    let b: BigUInt<1> = "ff".parse().unwrap();
+    assert_eq!(b.data, [0xff]);
+    let c: BigUInt8192 = "0x100000000000000000000000000000000".parse().unwrap();
+    assert_eq!(c.data[..3], [0, 0, 1]);
+}
+
+#[test]
+fn test_biguint_const_arithmetic() {
+    use std::cmp::Ordering;
+
+    const ONE: BigUInt<2> = BigUInt::from_u64(1);
+    const MAX: BigUInt<2> = BigUInt::new().wrapping_sub(&ONE);
+    const TWO_64: BigUInt<2> = BigUInt::from_u64(u64::MAX).wrapping_add(&ONE);
+    const SCALED: BigUInt<2> = TWO_64.wrapping_mul_by_u64(3).strict_sub(&ONE);
+    const ORDER: Ordering = ONE.const_cmp(&TWO_64);
+
+    assert_eq!(
+        MAX,
+        BigUInt {
+            data: [u64::MAX; 2]
+        }
+    );
+    assert_eq!(TWO_64.data, [0, 1]);
+    assert_eq!(SCALED.data, [u64::MAX, 2]);
+    // Evaluated at compile time.
+    const { assert!(MAX.checked_add(&ONE).is_none()) };
+    assert_eq!(ORDER, Ordering::Less);
+    const { assert!(TWO_64.bit(64)) };
+    assert!(MAX.const_eq(&BigUInt {
+        data: [u64::MAX; 2]
+    }));
+    assert!(!ONE.const_eq(&TWO_64));
+}
+
+#[test]
+fn test_biguint_literals() {
+    const WIDE: BigUInt<3> = p44::biguint!(0x1_0000000000000000_0000000000000002);
+    assert_eq!(WIDE.data, [2, 0, 1]);
+    assert_eq!(
+        p44::biguint!(340282366920938463463374607431768211458, 3),
+        WIDE
+    );
+    assert_eq!(p44::biguint!(0xDEAD_beef, 1).data, [0xdeadbeef]);
+    assert_eq!(p44::biguint!(0, 2), BigUInt::new());
+    let decimal: BigUInt<2> = p44::biguint!(18446744073709551616);
+    assert_eq!(decimal, BigUInt::from(1u128 << 64));
+}
+
+#[test]
+#[should_panic(expected = "Overflow in BigUInt literal")]
+fn test_biguint_literal_overflow() {
+    BigUInt::<1>::from_str_literal("0x1_0000000000000000");
+}
+
+#[test]
+#[should_panic(expected = "Invalid digit in BigUInt literal")]
+fn test_biguint_literal_invalid_digit() {
+    BigUInt::<1>::from_str_literal("12a");
+}
+
+#[test]
+fn test_biguint_from_str_rejects_invalid_input() {
+    let max = "f".repeat(32);
+    assert_eq!(
+        max.parse::<BigUInt<2>>(),
+        Ok(BigUInt {
+            data: [u64::MAX; 2]
+        })
+    );
+    assert!(format!("1{}", max).parse::<BigUInt<2>>().is_err());
+    assert_eq!(
+        format!("000{}", max).parse::<BigUInt<2>>(),
+        max.parse::<BigUInt<2>>()
+    );
+    assert!("0x12g4".parse::<BigUInt<2>>().is_err());
+    // Signs are rejected, including where they used to begin a 16-digit chunk.
+    assert!("+1".parse::<BigUInt<2>>().is_err());
+    assert!(format!("+{}", "f".repeat(15))
+        .parse::<BigUInt<2>>()
+        .is_err());
+    assert!(format!("+{}", "f".repeat(31))
+        .parse::<BigUInt<2>>()
+        .is_err());
+    assert!("\u{e9}".parse::<BigUInt<2>>().is_err());
+    assert_eq!("0x".parse::<BigUInt<2>>(), Ok(BigUInt::new()));
+}
+
+#[test]
+fn test_widening_shl() {
+    let a = BigUInt::<2> {
+        data: [0x0123456789abcdef, 0xfedcba9876543210],
+    };
+    assert_eq!(a.widening_shl(0), (a, BigUInt::new()));
+    assert_eq!(a.widening_shl(64).0.data, [0, 0x0123456789abcdef]);
+    assert_eq!(a.widening_shl(64).1.data, [0xfedcba9876543210, 0]);
+    assert_eq!(a.widening_shl(128), (BigUInt::new(), a));
+    // Bits crossing from the low half into the high half are kept.
+    let (low, high) = a.widening_shl(4);
+    assert_eq!(low.data, [0x123456789abcdef0, 0xedcba98765432100]);
+    assert_eq!(high.data, [0xf, 0]);
+    assert_eq!(a.widening_shl(256), (BigUInt::new(), BigUInt::new()));
+}
+
+#[test]
+fn test_shifts_and_rotations() {
+    let a = BigUInt::<2> {
+        data: [0x0123456789abcdef, 0xfedcba9876543210],
+    };
+    for shift in [0, 1, 63, 64, 65, 127] {
+        assert_eq!(a << shift, a.wrapping_shl(shift));
+        assert_eq!(a >> shift, a.wrapping_shr(shift));
+        assert_eq!(a.rotate_left(shift).rotate_right(shift), a);
+        let (result, underflow) = a.widening_shr(shift);
+        assert_eq!(result, a >> shift);
+        assert_eq!(
+            BigUInt::<4>::from_halves(&underflow, &result) << shift,
+            a.widen::<4>() << 128
+        );
+    }
+    assert_eq!((a << 64).data, [0, 0x0123456789abcdef]);
+    assert_eq!((a >> 64).data, [0xfedcba9876543210, 0]);
+    assert_eq!(
+        a.rotate_left(64).data,
+        [0xfedcba9876543210, 0x0123456789abcdef]
+    );
+    assert_eq!(
+        a.rotate_right(4).data,
+        [0x00123456789abcde, 0xffedcba987654321]
+    );
+    assert_eq!(a.rotate_left(128 + 4), a.rotate_left(4));
+
+    assert_eq!(a.checked_shl(128), None);
+    assert_eq!(a.checked_shr(128), None);
+    assert_eq!(a.overflowing_shl(129), (a << 1, true));
+    assert_eq!(a.wrapping_shr(128 + 64), a >> 64);
+    assert_eq!(a.widening_shr(128), (BigUInt::new(), a));
+    assert_eq!(a.widening_shr(192), (BigUInt::new(), a >> 64));
+
+    let mut b = a;
+    b <<= 8;
+    b >>= 8;
+    assert_eq!(b.data, [0x0123456789abcdef, 0x00dcba9876543210]);
+}
+
+#[test]
+#[should_panic(expected = "Overflow in shift left")]
+fn test_shl_overflow() {
+    let _ = BigUInt::<2>::from(1u8) << 128;
+}
+
+#[test]
+fn test_biguint_bytes() {
+    let a: BigUInt<2> = "0x0102030405060708090a".parse().unwrap();
+    let be = a.to_be_bytes();
+    assert_eq!(be.len(), 16);
+    assert_eq!(be[6..], [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    let mut le = a.to_le_bytes();
+    le.reverse();
+    assert_eq!(le, be);
+    assert_eq!(a.to_be_bytes_minimal(), [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    assert_eq!(a.to_le_bytes_minimal(), [10, 9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    assert_eq!(
+        a.to_be_bytes_padded(12).unwrap(),
+        [0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+    );
+    assert_eq!(
+        a.to_le_bytes_padded(20).unwrap()[9..],
+        [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(a.to_be_bytes_padded(9), None);
+    assert!(BigUInt1024::new().to_be_bytes_minimal().is_empty());
+    assert_eq!(BigUInt::<2>::from_be_bytes(&be), Ok(a));
+    assert_eq!(BigUInt::<2>::from_be_bytes(&a.to_be_bytes_minimal()), Ok(a));
+    assert_eq!(
+        BigUInt::<2>::from_le_bytes(&a.to_le_bytes_padded(40).unwrap()),
+        Ok(a)
+    );
+    assert!(BigUInt::<1>::from_be_bytes(&be).is_err());
+    assert_eq!(BigUInt::<1>::from_be_bytes(&[0; 30]), Ok(BigUInt::new()));
+
+    let mut state = 0xb17e5_u64;
+    for _ in 0..10 {
+        let x = random_biguint::<16>(&mut state);
+        assert_eq!(BigUInt1024::from_le_bytes(&x.to_le_bytes_minimal()), Ok(x));
+        assert_eq!(BigUInt1024::from_be_bytes(&x.to_be_bytes()), Ok(x));
+    }
+}
+
+#[test]
+fn test_biguint_der() {
+    use p44::biguint::DerError;
+    let cases: [(u64, &[u8]); 5] = [
+        (0, &[0x02, 0x01, 0x00]),
+        (0x7f, &[0x02, 0x01, 0x7f]),
+        (0x80, &[0x02, 0x02, 0x00, 0x80]),
+        (0x0100, &[0x02, 0x02, 0x01, 0x00]),
+        (65537, &[0x02, 0x03, 0x01, 0x00, 0x01]),
+    ];
+    for (value, der) in cases {
+        let a = BigUInt1024::from(value);
+        assert_eq!(a.to_der(), der);
+        assert_eq!(BigUInt1024::from_der(der), Ok(a));
+    }
+
+    // A 2048-bit value with the top bit set needs a zero byte and a long length.
+    let mut state = 0xde7_u64;
+    let mut n = random_biguint::<32>(&mut state);
+    n.set_bit(2047, true);
+    let der = n.to_der();
+    assert_eq!(der[..5], [0x02, 0x82, 0x01, 0x01, 0x00]);
+    assert_eq!(der.len(), 5 + 256);
+    assert_eq!(BigUInt2048::from_der(&der), Ok(n));
+    assert_eq!(BigUInt1024::from_der(&der), Err(DerError::Overflow));
+
+    let invalid: [(&[u8], DerError); 10] = [
+        (&[], DerError::Malformed),
+        (&[0x03, 0x01, 0x00], DerError::Malformed),
+        (&[0x02, 0x00], DerError::Malformed),
+        (&[0x02, 0x02, 0x01], DerError::Malformed),
+        (&[0x02, 0x01, 0x01, 0x00], DerError::Malformed),
+        (&[0x02, 0x80, 0x01, 0x00, 0x00], DerError::Malformed),
+        (&[0x02, 0x81, 0x01, 0x01], DerError::NonCanonical),
+        (&[0x02, 0x82, 0x00, 0x01, 0x01], DerError::NonCanonical),
+        (&[0x02, 0x02, 0x00, 0x7f], DerError::NonCanonical),
+        (&[0x02, 0x01, 0x80], DerError::Negative),
+    ];
+    for (der, err) in invalid {
+        assert_eq!(BigUInt1024::from_der(der), Err(err), "{:02x?}", der);
+    }
+}
+
+fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[test]
+fn test_biguint_pow() {
+    let three = BigUInt::<2>::from(3u8);
+    assert_eq!(
+        three.checked_pow(80),
+        Some(BigUInt::<2>::from(3u128.pow(80)))
+    );
+    assert_eq!(three.checked_pow(81), None);
+    assert_eq!(
+        three.wrapping_pow(81),
+        BigUInt::<2>::from(3u128.wrapping_pow(81))
+    );
+    assert_eq!(three.overflowing_pow(0), (BigUInt::from(1u8), false));
+    assert_eq!(
+        BigUInt::<2>::new().overflowing_pow(0),
+        (BigUInt::from(1u8), false)
+    );
+    assert_eq!(BigUInt::<2>::new().checked_pow(1000), Some(BigUInt::new()));
+    let two = BigUInt1024::from(2u8);
+    assert_eq!(two.checked_pow(1024), None);
+    let mut top = BigUInt1024::new();
+    top.set_bit(1023, true);
+    assert_eq!(two.strict_pow(1023), top);
+    assert_eq!(BigUInt1024::from(u64::MAX).wrapping_pow(17), {
+        let mut x = BigUInt1024::from(1u8);
+        for _ in 0..17 {
+            x = x.wrapping_mul(&BigUInt1024::from(u64::MAX));
+        }
+        x
+    });
+}
+
+#[test]
+#[should_panic(expected = "Overflow in exponentiation")]
+fn test_biguint_strict_pow_overflow() {
+    BigUInt::<1>::from(2u8).strict_pow(64);
+}
+
+#[test]
+fn test_biguint_roots() {
+    for x in [
+        0u128,
+        1,
+        2,
+        3,
+        4,
+        15,
+        16,
+        17,
+        1 << 64,
+        u64::MAX as u128,
+        u128::MAX,
+    ] {
+        let a = BigUInt::<2>::from(x);
+        assert_eq!(a.isqrt(), BigUInt::from(x.isqrt()), "isqrt({})", x);
+        let cbrt = a.nth_root(3);
+        let r = u128::try_from(cbrt).unwrap();
+        assert!(r.pow(3) <= x && (r + 1).checked_pow(3).is_none_or(|c| c > x));
+    }
+    assert_eq!(
+        BigUInt::<2>::from(u128::MAX).nth_root(127),
+        BigUInt::from(2u8)
+    );
+    assert_eq!(
+        BigUInt::<2>::from(u128::MAX).nth_root(128),
+        BigUInt::from(1u8)
+    );
+    assert_eq!(
+        BigUInt::<2>::from(12345u16).nth_root(1),
+        BigUInt::from(12345u16)
+    );
+    assert_eq!(BigUInt::<2>::from(12345u16).checked_nth_root(0), None);
+
+    let mut state = 0x5eed_u64;
+    for _ in 0..10 {
+        let mut r = random_biguint::<16>(&mut state);
+        r.data[5..].fill(0);
+        let cube = r.strict_pow(3);
+        assert_eq!(cube.nth_root(3), r);
+        assert_eq!(
+            (cube - BigUInt1024::from(1u8)).nth_root(3),
+            r - BigUInt1024::from(1u8)
+        );
+        let square = r.strict_mul(&r);
+        assert_eq!(square.isqrt(), r);
+        assert_eq!((square + r + r).isqrt(), r);
+    }
+    let max = BigUInt1024 {
+        data: [u64::MAX; 16],
+    };
+    let mut root = BigUInt1024::new();
+    root.data[..8].fill(u64::MAX);
+    assert_eq!(max.isqrt(), root);
+}
+
+#[test]
+fn test_biguint_gcd_lcm() {
+    let mut state = 0x6cd_u64;
+    for _ in 0..50 {
+        let x = u128::from(random_biguint::<1>(&mut state).data[0]) >> (state % 64);
+        let y = u128::from(random_biguint::<1>(&mut state).data[0]) << (state % 32);
+        let (a, b) = (BigUInt::<2>::from(x), BigUInt::<2>::from(y));
+        assert_eq!(
+            a.gcd(&b),
+            BigUInt::from(gcd_u128(x, y)),
+            "gcd({}, {})",
+            x,
+            y
+        );
+    }
+    assert_eq!(BigUInt::<2>::new().gcd(&BigUInt::new()), BigUInt::new());
+    assert_eq!(
+        BigUInt::<2>::new().gcd(&BigUInt::from(6u8)),
+        BigUInt::from(6u8)
+    );
+
+    for _ in 0..5 {
+        let mut g = random_biguint::<16>(&mut state);
+        g.data[4..].fill(0);
+        let mut a = random_biguint::<16>(&mut state);
+        let mut b = random_biguint::<16>(&mut state);
+        a.data[6..].fill(0);
+        b.data[6..].fill(0);
+        let common = a.gcd(&b);
+        let (ag, bg) = (a * g, b * g);
+        assert_eq!(ag.gcd(&bg), common * g);
+        assert_eq!(ag.strict_lcm(&bg), a * b * g / common);
+    }
+
+    let a = BigUInt::<2>::from(12u8);
+    let b = BigUInt::<2>::from(18u8);
+    assert_eq!(a.checked_lcm(&b), Some(BigUInt::from(36u8)));
+    assert_eq!(a.checked_lcm(&BigUInt::new()), Some(BigUInt::new()));
+    let big = BigUInt::<2>::from(u128::MAX);
+    assert_eq!(big.checked_lcm(&BigUInt::from(2u8)), None);
+    assert_eq!(
+        big.overflowing_lcm(&BigUInt::from(2u8)),
+        (big.wrapping_mul(&BigUInt::from(2u8)), true)
+    );
+    assert_eq!(big.wrapping_lcm(&BigUInt::from(5u8)), big);
+}
+
+#[test]
+fn test_biguint_powers_of_two() {
+    let mut top = BigUInt1024::new();
+    top.set_bit(1023, true);
+    assert!(top.is_power_of_two());
+    assert!(BigUInt1024::from(1u8).is_power_of_two());
+    assert!(!BigUInt1024::new().is_power_of_two());
+    assert!(!BigUInt1024::from(6u8).is_power_of_two());
+    assert_eq!(
+        BigUInt1024::new().checked_next_power_of_two(),
+        Some(BigUInt1024::from(1u8))
+    );
+    assert_eq!(
+        BigUInt1024::from(5u8).strict_next_power_of_two(),
+        BigUInt1024::from(8u8)
+    );
+    assert_eq!(top.checked_next_power_of_two(), Some(top));
+    assert_eq!(
+        BigUInt1024::from(u64::MAX).checked_next_power_of_two(),
+        Some(BigUInt1024::from(1u128 << 64))
+    );
+    let above_top = top + BigUInt1024::from(1u8);
+    assert_eq!(above_top.checked_next_power_of_two(), None);
+    assert_eq!(above_top.wrapping_next_power_of_two(), BigUInt1024::new());
+}
+
+mod bigint {
+    use p44::bigint::{BigInt, BigInt1024, BigInt2048, ParseBigIntError};
+    use p44::biguint::BigUInt;
+
+    #[test]
+    fn test_sign_and_abs() {
+        let a = BigInt1024::from(-7i32);
+        assert!(a.is_negative());
+        assert_eq!(a.abs(), BigInt1024::from(7i8));
+        assert_eq!(a.unsigned_abs(), BigUInt::<16>::from(7u8));
+        assert_eq!(-a, BigInt1024::from(7i8));
+        assert_eq!(BigInt1024::MIN.checked_abs(), None);
+        assert_eq!(BigInt1024::MIN.checked_neg(), None);
+        assert_eq!(BigInt1024::MIN.wrapping_neg(), BigInt1024::MIN);
+        assert_eq!(
+            BigInt1024::MAX.checked_neg(),
+            Some(BigInt1024::MIN + BigInt1024::from(1i8))
+        );
+        assert!(BigInt1024::new() < BigInt1024::from(1i8));
+        assert!(
+            BigInt1024::MIN < a && a < BigInt1024::new() && BigInt1024::new() < BigInt1024::MAX
+        );
+    }
+
+    #[test]
+    fn test_add_sub_overflow() {
+        let one = BigInt2048::from(1i8);
+        assert_eq!(BigInt2048::MAX.checked_add(&one), None);
+        assert_eq!(BigInt2048::MAX.wrapping_add(&one), BigInt2048::MIN);
+        assert_eq!(BigInt2048::MIN.checked_sub(&one), None);
+        assert_eq!(
+            BigInt2048::MIN.overflowing_sub(&one),
+            (BigInt2048::MAX, true)
+        );
+        assert_eq!(
+            BigInt2048::from(-3i8) + BigInt2048::from(5i8),
+            BigInt2048::from(2i8)
+        );
+        assert_eq!(
+            BigInt2048::from(-3i8) - BigInt2048::from(5i8),
+            BigInt2048::from(-8i8)
+        );
+        assert_eq!(BigInt2048::MIN.checked_add(&BigInt2048::MAX), Some(-one));
+    }
+
+    #[test]
+    fn test_mul_div_rem_match_i128() {
+        let values = [
+            i64::MIN as i128,
+            -123456789012345678,
+            -97,
+            -1,
+            1,
+            5,
+            4294967311,
+            i64::MAX as i128,
+        ];
+        for &x in &values {
+            for &y in &values {
+                let (a, b) = (BigInt::<4>::from(x), BigInt::<4>::from(y));
+                assert_eq!(a * b, BigInt::<4>::from(x * y));
+                assert_eq!(a / b, BigInt::<4>::from(x / y));
+                assert_eq!(a % b, BigInt::<4>::from(x % y));
+                assert_eq!(a.rem_euclid(&b), BigInt::<4>::from(x.rem_euclid(y)));
+                assert_eq!(a.cmp(&b), x.cmp(&y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_div_overflow() {
+        let minus_one = BigInt1024::from(-1i8);
+        let two = BigInt1024::from(2i8);
+        assert_eq!(BigInt1024::MIN.checked_div(&minus_one), None);
+        assert_eq!(
+            BigInt1024::MIN.overflowing_div(&minus_one),
+            (BigInt1024::MIN, true)
+        );
+        assert_eq!(BigInt1024::MIN.checked_rem(&minus_one), None);
+        assert_eq!(BigInt1024::MIN.wrapping_rem(&minus_one), BigInt1024::new());
+        assert_eq!(two.checked_div(&BigInt1024::new()), None);
+        assert_eq!(BigInt1024::MAX.checked_mul(&two), None);
+        assert_eq!(BigInt1024::MIN.checked_mul(&minus_one), None);
+        let half_min = BigInt1024::MIN / two;
+        assert_eq!(half_min.checked_mul(&two), Some(BigInt1024::MIN));
+        assert_eq!((-half_min).checked_mul(&two), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow in division")]
+    fn test_div_operator_overflow() {
+        let _ = BigInt1024::MIN / BigInt1024::from(-1i8);
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow in remainder")]
+    fn test_rem_operator_overflow() {
+        let _ = BigInt1024::MIN % BigInt1024::from(-1i8);
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let a: BigInt1024 = "-0x1f".parse().unwrap();
+        assert_eq!(a, BigInt1024::from(-31i8));
+        assert_eq!("+1f".parse::<BigInt1024>(), Ok(BigInt1024::from(31i8)));
+        let shown = a.to_string();
+        assert!(shown.starts_with("-0x") && shown.ends_with("001f"));
+        assert_eq!(shown.parse::<BigInt1024>(), Ok(a));
+        let min = BigInt1024::MIN.to_string();
+        assert_eq!(min.parse::<BigInt1024>(), Ok(BigInt1024::MIN));
+        assert_eq!(
+            min[1..].parse::<BigInt1024>(),
+            Err(ParseBigIntError::Overflow)
+        );
+        assert!(matches!(
+            "-0xzz".parse::<BigInt1024>(),
+            Err(ParseBigIntError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_unsigned_conversions() {
+        let a = BigInt1024::from(-1i8);
+        assert!(BigUInt::<16>::try_from(a).is_err());
+        assert_eq!(
+            BigUInt::<16>::try_from(BigInt1024::from(9i8)),
+            Ok(BigUInt::<16>::from(9u8))
+        );
+        assert_eq!(
+            BigInt1024::try_from(BigUInt::<16>::from(9u8)),
+            Ok(BigInt1024::from(9i8))
+        );
+        assert!(BigInt1024::try_from(BigInt1024::MIN.to_bits()).is_err());
+    }
+}
+
+mod prime {
+    use p44::biguint::{BigUInt, BigUInt1024};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn mersenne<const N: usize>(p: usize) -> BigUInt<N> {
+        let mut result = BigUInt::<N>::new();
+        for i in 0..p {
+            result.set_bit(i, true);
+        }
+        result
+    }
+
+    #[test]
+    fn test_pow_mod() {
+        let m = BigUInt::<4>::from(1_000_000_007u64);
+        let base = BigUInt::<4>::from(3u8);
+        let exponent = BigUInt::<4>::from(1_000_000_006u64);
+        // Fermat's little theorem.
+        assert_eq!(base.pow_mod(&exponent, &m), BigUInt::<4>::from(1u8));
+        assert_eq!(
+            base.pow_mod(&BigUInt::<4>::from(40u8), &m),
+            BigUInt::<4>::from(3u128.pow(40) % 1_000_000_007)
+        );
+        assert_eq!(
+            base.pow_mod(&BigUInt::<4>::new(), &m),
+            BigUInt::<4>::from(1u8)
+        );
+        for e in [0u64, 1, 40, 1_000_000_006] {
+            let exponent = BigUInt::<4>::from(e);
+            assert_eq!(
+                base.pow_mod_secret(&exponent, &m),
+                base.pow_mod(&exponent, &m)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Exponent is longer than modulus")]
+    fn test_pow_mod_secret_long_exponent() {
+        let m = BigUInt::<4>::from(7u8);
+        BigUInt::<4>::from(3u8).pow_mod_secret(&BigUInt::<4>::from(8u8), &m);
+    }
+
+    #[test]
+    fn test_trial_division() {
+        assert_eq!(BigUInt1024::from(0u8).trial_division(), Some(false));
+        assert_eq!(BigUInt1024::from(1u8).trial_division(), Some(false));
+        assert_eq!(BigUInt1024::from(2u8).trial_division(), Some(true));
+        assert_eq!(BigUInt1024::from(4u8).trial_division(), Some(false));
+        assert_eq!(BigUInt1024::from(2039u16).trial_division(), Some(true));
+        assert_eq!(
+            BigUInt1024::from(2039u32 * 2029).trial_division(),
+            Some(false)
+        );
+        assert_eq!(BigUInt1024::from(1000003u32).trial_division(), Some(true));
+        assert_eq!(mersenne::<16>(89).trial_division(), None);
+    }
+
+    #[test]
+    fn test_known_primes() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for p in [61, 89, 107, 127, 521, 607] {
+            let n = mersenne::<16>(p);
+            assert!(n.is_probable_prime(), "2^{p} - 1");
+            assert!(n.is_probable_prime_miller_rabin(20, &mut rng), "2^{p} - 1");
+            assert!(n.is_strong_lucas_probable_prime(), "2^{p} - 1");
+        }
+        for p in [67, 101, 523, 1009] {
+            let n = mersenne::<16>(p);
+            assert!(!n.is_probable_prime(), "2^{p} - 1");
+            assert!(!n.is_probable_prime_miller_rabin(20, &mut rng), "2^{p} - 1");
+        }
+        let p = BigUInt1024::from(u64::MAX - 58);
+        let q = BigUInt1024::from(u64::MAX - 82);
+        assert!(p.is_probable_prime() && q.is_probable_prime());
+        assert!(!(p * q).is_probable_prime());
+        assert!(!(p * p).is_probable_prime());
+        assert!(!(p * q).is_probable_prime_miller_rabin(20, &mut rng));
+    }
+
+    #[test]
+    #[should_panic(expected = "Miller-Rabin needs at least one round")]
+    fn test_miller_rabin_zero_rounds() {
+        let p = BigUInt1024::from(u64::MAX - 58);
+        (p * p).is_probable_prime_miller_rabin(0, &mut StdRng::seed_from_u64(1));
+    }
+
+    #[test]
+    fn test_pseudoprimes() {
+        // Strong pseudoprimes to base 2 fail the Lucas test, and vice versa.
+        let two = BigUInt::<2>::from(2u8);
+        for n in [2047u64, 3277, 4033, 4681, 8321, 3215031751] {
+            let n = BigUInt::<2>::from(n);
+            assert!(n.is_strong_probable_prime(&two));
+            assert!(!n.is_strong_lucas_probable_prime());
+            assert!(!n.is_probable_prime());
+        }
+        for n in [5459u64, 5777, 10877, 16109, 18971] {
+            let n = BigUInt::<2>::from(n);
+            assert!(n.is_strong_lucas_probable_prime());
+            assert!(!n.is_strong_probable_prime(&two));
+            assert!(!n.is_probable_prime());
+        }
+        // Carmichael numbers.
+        let mut rng = StdRng::seed_from_u64(2);
+        for n in [561u64, 41041, 825265, 321197185] {
+            let n = BigUInt::<2>::from(n);
+            assert!(!n.is_probable_prime());
+            assert!(!n.is_probable_prime_miller_rabin(20, &mut rng));
+        }
+    }
+
+    #[test]
+    fn test_random_prime() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for bits in [2, 3, 8, 13, 64, 100, 256] {
+            let p = BigUInt::<8>::random_prime(bits, &mut rng);
+            assert_eq!(p.bits(), bits);
+            assert!(p.is_probable_prime());
+            assert!(p.is_probable_prime_miller_rabin(20, &mut rng));
+        }
+        let p = BigUInt1024::random_prime(512, &mut rng);
+        assert_eq!(p.bits(), 512);
+        assert!(p.is_probable_prime());
+    }
+
+    #[test]
+    fn test_random_safe_prime() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let one = BigUInt::<4>::from(1u8);
+        let two = BigUInt::<4>::from(2u8);
+        for bits in [3, 10, 64, 128] {
+            let p = BigUInt::<4>::random_safe_prime(bits, &mut rng);
+            assert_eq!(p.bits(), bits);
+            assert!(p.is_probable_prime());
+            assert!(((p - one) / two).is_probable_prime());
+        }
+    }
+}
+
+mod dynuint {
+    use super::random_biguint;
+    use p44::biguint::{BigUInt, BigUInt1024, BigUInt2048};
+    use p44::dynuint::DynUInt;
+
+    #[test]
+    fn test_normalization() {
+        let a = DynUInt::from_limbs(&[5, 0, 0]);
+        assert_eq!(a.limbs(), [5]);
+        assert_eq!(a, DynUInt::from(5u8));
+        assert!(DynUInt::from_limbs(&[0, 0]).is_zero());
+        assert_eq!(DynUInt::from(BigUInt1024::new()), DynUInt::new());
+        let b = DynUInt::from(u128::MAX) - DynUInt::from(u64::MAX);
+        assert_eq!(b.limbs(), [0, u64::MAX]);
+        assert_eq!((&b - &b).limbs(), []);
+        assert_eq!(b.bits(), 128);
+        assert!(b.bit(64) && !b.bit(63) && !b.bit(1000));
+    }
+
+    #[test]
+    fn test_grows_without_overflow() {
+        let max_fixed = BigUInt1024 {
+            data: [u64::MAX; 16],
+        };
+        let max = DynUInt::from(max_fixed);
+        let sum = &max + &DynUInt::from(1u8);
+        assert_eq!(sum.limbs().len(), 17);
+        assert_eq!(sum.bits(), 1025);
+        let product = &max * &max;
+        assert_eq!(product.limbs().len(), 32);
+        assert_eq!(
+            product,
+            DynUInt::from(max_fixed.widening_mul::<32>(&max_fixed))
+        );
+        let mut power = DynUInt::from(1u8);
+        for _ in 0..300 {
+            power *= DynUInt::from(u64::MAX);
+        }
+        assert_eq!(power.limbs().len(), 300);
+        assert_eq!(power.checked_sub(&(&power + &DynUInt::from(1u8))), None);
+    }
+
+    #[test]
+    fn test_matches_biguint() {
+        let mut state = 0x5eed_d1ce_u64;
+        for _ in 0..20 {
+            let a = random_biguint::<16>(&mut state);
+            let mut b = random_biguint::<16>(&mut state);
+            b.data[8..].fill(0);
+            let (x, y) = (DynUInt::from(a), DynUInt::from(b));
+            assert_eq!(
+                BigUInt2048::try_from(&x * &y).unwrap(),
+                a.widening_mul::<32>(&b)
+            );
+            let (q, r) = a.div_rem(&b);
+            assert_eq!((&x / &y).to_biguint::<16>(), Some(q));
+            assert_eq!((&x % &y).to_biguint::<16>(), Some(r));
+            assert_eq!(x.cmp(&y), a.cmp(&b));
+            let sum = BigUInt::<17>::try_from(&x + &y).unwrap();
+            assert_eq!(sum, a.widen::<17>() + b.widen::<17>());
+        }
+    }
+
+    #[test]
+    fn test_mixed_lengths_and_large_operands() {
+        let mut state = 0xabcdef_u64;
+        let a = DynUInt::from(random_biguint::<300>(&mut state));
+        let b = DynUInt::from(random_biguint::<7>(&mut state));
+        let product = &a * &b;
+        assert_eq!(&product / &b, a);
+        assert!((&product % &b).is_zero());
+        let c = &product + &DynUInt::from(12345u16);
+        let (q, r) = c.div_rem(&a);
+        assert_eq!(q, b);
+        assert_eq!(r, DynUInt::from(12345u16));
+        assert_eq!(c.checked_div(&DynUInt::new()), None);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let max = BigUInt2048 {
+            data: [u64::MAX; 32],
+        };
+        let a = DynUInt::from(max);
+        assert_eq!(a.to_biguint::<32>(), Some(max));
+        assert_eq!(a.to_biguint::<40>(), Some(max.widen::<40>()));
+        assert_eq!(a.to_biguint::<31>(), None);
+        assert!(BigUInt1024::try_from(a).is_err());
+        assert_eq!(u64::try_from(DynUInt::from(7u8)), Ok(7));
+        assert!(u8::try_from(DynUInt::from(256u16)).is_err());
+        assert_eq!(u128::try_from(DynUInt::from(u128::MAX)), Ok(u128::MAX));
+    }
+
+    #[test]
+    fn test_from_str_and_display() {
+        let hex = "0x1000000000000000000000000000000000000000000000000000000000000000000000001";
+        let a: DynUInt = hex.parse().unwrap();
+        assert_eq!(a.limbs().len(), 5);
+        assert_eq!(a.to_string(), hex);
+        assert_eq!("0x000ff".parse::<DynUInt>().unwrap().to_string(), "0xff");
+        assert_eq!(DynUInt::new().to_string(), "0x0");
+        assert!("0xfg".parse::<DynUInt>().is_err());
+        assert!("+1".parse::<DynUInt>().is_err());
+        assert!("0x+1234567890abcdef".parse::<DynUInt>().is_err());
+        // A multibyte character straddling a 16-digit boundary.
+        assert!("0x\u{e9}123456789abcdef".parse::<DynUInt>().is_err());
+        assert!("0x123456789abcdef\u{e9}".parse::<DynUInt>().is_err());
+    }
+
+    #[test]
+    fn test_pow_mod() {
+        let p = DynUInt::from(1000003u32);
+        let a = DynUInt::from(123456789u32);
+        assert_eq!(
+            a.pow_mod(&(&p - &DynUInt::from(1u8)), &p),
+            DynUInt::from(1u8)
+        );
+        assert_eq!(
+            a.pow_mod(&DynUInt::new(), &DynUInt::from(1u8)),
+            DynUInt::new()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow in subtraction")]
+    fn test_sub_underflow_panics() {
+        let _ = DynUInt::from(1u8) - DynUInt::from(2u8);
+    }
+}
@@ -0,0 +1,150 @@
+use p44::limb::{self, portable};
+
+/// Edge values followed by xorshift output, so failures are reproducible.
+fn sample_limbs() -> Vec<u64> {
+    let mut values = vec![0, 1, 2, u32::MAX as u64, 1 << 63, u64::MAX - 1, u64::MAX];
+    let mut state = 0x2545f4914f6cdd1d_u64;
+    for _ in 0..40 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        values.push(state);
+    }
+    values
+}
+
+#[test]
+fn test_portable_matches_overflowing_ops() {
+    for &a in &sample_limbs() {
+        for &b in &sample_limbs() {
+            for flag in [false, true] {
+                let (sum, c1) = a.overflowing_add(b);
+                let (sum, c2) = sum.overflowing_add(flag as u64);
+                assert_eq!(portable::carrying_add(a, b, flag), (sum, c1 || c2));
+
+                let (difference, b1) = a.overflowing_sub(b);
+                let (difference, b2) = difference.overflowing_sub(flag as u64);
+                assert_eq!(portable::borrowing_sub(a, b, flag), (difference, b1 || b2));
+            }
+            for carry in [0, 1, a ^ b, u64::MAX] {
+                let (low, high) = portable::carrying_mul(a, b, carry);
+                let expected = a as u128 * b as u128 + carry as u128;
+                assert_eq!(((high as u128) << 64) | low as u128, expected);
+            }
+        }
+    }
+    assert_eq!(
+        portable::carrying_mul(u64::MAX, u64::MAX, u64::MAX),
+        (0, u64::MAX)
+    );
+}
+
+#[test]
+fn test_selected_backend_matches_portable() {
+    for &a in &sample_limbs() {
+        for &b in &sample_limbs() {
+            for flag in [false, true] {
+                assert_eq!(
+                    limb::carrying_add(a, b, flag),
+                    portable::carrying_add(a, b, flag)
+                );
+                assert_eq!(
+                    limb::borrowing_sub(a, b, flag),
+                    portable::borrowing_sub(a, b, flag)
+                );
+            }
+            assert_eq!(limb::carrying_mul(a, b, a), portable::carrying_mul(a, b, a));
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn test_intrinsics_match_portable() {
+    use p44::limb::intrinsic;
+    for &a in &sample_limbs() {
+        for &b in &sample_limbs() {
+            for flag in [false, true] {
+                assert_eq!(
+                    intrinsic::carrying_add(a, b, flag),
+                    portable::carrying_add(a, b, flag)
+                );
+                assert_eq!(
+                    intrinsic::borrowing_sub(a, b, flag),
+                    portable::borrowing_sub(a, b, flag)
+                );
+            }
+            for carry in [0, b, u64::MAX] {
+                assert_eq!(
+                    intrinsic::carrying_mul(a, b, carry),
+                    portable::carrying_mul(a, b, carry)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mul_add_row_matches_reference() {
+    let limbs = sample_limbs();
+    for len in [0, 1, 2, 7, limbs.len()] {
+        let a = &limbs[..len];
+        let mut acc: Vec<u64> = limbs.iter().rev().take(len).copied().collect();
+        for x in [0, 1, u64::MAX, limbs[len / 2]] {
+            // Reference: accumulate the row one limb at a time in u128.
+            let mut expected = acc.clone();
+            let mut carry = 0u128;
+            for (e, &y) in expected.iter_mut().zip(a) {
+                let t = *e as u128 + x as u128 * y as u128 + carry;
+                *e = t as u64;
+                carry = t >> 64;
+            }
+            let mut result = acc.clone();
+            assert_eq!(limb::mul_add_row(&mut result, a, x), carry as u64);
+            assert_eq!(result, expected);
+            let mut result = acc.clone();
+            assert_eq!(portable::mul_add_row(&mut result, a, x), carry as u64);
+            assert_eq!(result, expected);
+            acc = expected;
+        }
+    }
+    let mut acc = [u64::MAX; 9];
+    assert_eq!(
+        limb::mul_add_row(&mut acc, &[u64::MAX; 9], u64::MAX),
+        u64::MAX
+    );
+    assert_eq!(
+        acc,
+        [
+            0,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX,
+            u64::MAX
+        ]
+    );
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn test_adx_matches_portable() {
+    use p44::limb::adx;
+    if !adx::is_available() {
+        return;
+    }
+    let limbs = sample_limbs();
+    for start in 0..limbs.len() {
+        let a = &limbs[start..];
+        let x = limbs[start];
+        let mut expected: Vec<u64> = limbs[..a.len()].to_vec();
+        let mut result = expected.clone();
+        let carry = portable::mul_add_row(&mut expected, a, x);
+        // SAFETY: the CPU supports BMI2 and ADX.
+        assert_eq!(unsafe { adx::mul_add_row(&mut result, a, x) }, carry);
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,214 @@
+use hex_literal::hex;
+use p44::biguint::BigUInt;
+use p44::rsa::{Rsa2048PrivateKey, RsaError, RsaPrivateKey};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// A 2048-bit key and test vectors generated with the Python `cryptography` package.
+const N: &str = concat!(
+    "b54d9890885e1a882e38a3581445b327a2591ba1fcb7ac76b7fdc3c97a3688bd",
+    "8f937e306ff538896e790176072b7664103c8771af80f60a14d001ad407c6b4c",
+    "4d10dc3ca5e84b2a79e770bf0588f95ba9ed18c82e48c1e48ee5b2b713139f8c",
+    "b463933ec7686fe8e6e53c87defd80e9fa0a282963df9eb262c502a2c6e6ca2f",
+    "cbe5a10c98db65463a80a1a9668d6059544c5b17c574b4a42361a80334a5301d",
+    "43dbc061d585a9b3a0ede24567f6aeb421cfa31763443ccd48571f8d1b71e863",
+    "fab160caaf784acebc94cc6342f92a0863fcf43e69c1b91de6fd68adc81ba99e",
+    "c0d973e2e1ef7225fb2ad0f388837cb877efc7e77e18b379530a467030a16ae1",
+);
+const D: &str = concat!(
+    "3d6b338f66fb061ef31591d4614db962e85f2a7ac64b8cbbcd19c50d487f17f4",
+    "aba26a21cb93b59aed174614ef59dc6a0a8c0ed04705b793f3b8019169229d10",
+    "c49cbd173897087bb3bf6154fcf18993f16d56335e669c74aaaa0ad075407510",
+    "ebfd3da10b59886bcc6885087c54c1aac1df55faab9052491927a77c704837de",
+    "c60bb92d348e6d9bdc6ec8a449693411d71b8c35d85e7ceb29fe6b971b42364f",
+    "9c1ef1ab79df6ec88ee154920c1fcfe5852efe6e26805f4d4b1807b80e5ffe06",
+    "c755341699a7e3e1bbeca0bb3fba12b3e61740b3d0bb38a5af8c6bb70f99cff2",
+    "7beebe4f6454d70979b52cc9967c7a9d44ea5d59366c7419e61e0ebf876c047f",
+);
+const P: &str = concat!(
+    "e459733e0bfb57f67f32b6df5e305ef4889301a72dba5acdff86f080c5ec1f92",
+    "bf6eca12db2eedf69fc00950f23d62198b297d0c7c57abcf022deaf1bc565669",
+    "5eccf8c3edf80405113ac68c160c5aa7a6b54ad7cc2d771ccc29893768de7169",
+    "08d76d5d4669bac9d4087ff8855ebd3adfedd977dba3b503169bf10031203473",
+);
+const Q: &str = concat!(
+    "cb41c4b764d3cdef62cf57dd104604e48f6cdcbaee375d30afb14d9c5afa84a3",
+    "f8e2b7731486b48b1022c1662523138bbde060a9cbee2a68734fc8a6359735b9",
+    "5a2f36886c10679bced9c52d7001dac0d8458c57b20d33142e1f8471d1caf8ea",
+    "03d7d1f39eebd58845c091f877729d3084208205e6752c2483dd5021e18aa25b",
+);
+const RAW_M: &str = "72617720727361206d657373616765";
+const RAW_C: &str = concat!(
+    "576cf25df09b40b96e6b54051c8d575b7a01545a7e5292632b7d7f9cf2ded777",
+    "d69d6c0b31803eaf14fd72251a77fca841f22bd628e9e787ae6b5f01a4c5ce93",
+    "406c53def06b8122e08d373ef41c498e3527430223ea59434312ee7934ae352f",
+    "a98a5b7cab86b2a28d24c61d62b25d57f71fa825d0fce41118a8671b2faf2cdd",
+    "427c9b2e1decdbdce2b665d8faef1d24b3fd121acd183208f05de6bb7e2c1a4b",
+    "ff732b7c8bb67ba9ead4293e6debd65b3c1fa060eccb57567d8587029b0ba564",
+    "f6ffb1fd4c78ba506317ee817015741befec4d83b90e3b3055ebe80ce0977224",
+    "f0925679d8104f39433c18318c7b636629887367a7d7469d1517aa9b1a23467c",
+);
+const OAEP_CT: [u8; 256] = hex!(
+    "0ff98dc32aa2fc1be3c627b60b8d8842a7272af1ebf5f8ad9cd1debd9eda3906"
+    "3152f9ea7dc9b95d021ee8809efb503121487f144e7aab5a44caf7ead5fdea5b"
+    "bff77ec0e8709c743e6778d1a22f6639fa8697f222a94c3ffdbb91bc5c3df54f"
+    "e00c50fced20042cdcb9738c8f41834c89a8d70c1c7cb445602303fd9173bac0"
+    "fb0afb302f4f2d22b8893476bebabaf7e11796de2ce145a71583fc7fe55311bd"
+    "aff5139840671a46109cf438828c4f33bcf81d4948d8e865e003b41c969d2f6f"
+    "e903b575126c89ebf534f077efe2982b45ba404eaef76300b8368db171efb813"
+    "91966edafce595cc8e59f8ccfeba7dbdfe2aa23ec86d3ddcde40dd8a525eabe5"
+);
+const OAEP_CT_LABEL: [u8; 256] = hex!(
+    "6a5ffed001d0778d0688b3cf62679d2b577ca89a66c6fed7c84541d8b100f36c"
+    "bb81c06167b46d5ef33c4f7d47b050f9bbc51cae5149703c9ddec070e19168cd"
+    "09a879393b0bac70741cef91eb9c96d8ca510b82f5ad3a8ebbe9e9eea0f1a883"
+    "68e7c581b3ca65c7c5210fce01c29e2893f59cc2b944866cee5a09b25915e2a1"
+    "00d4bd33412fdd9f2c6e77e609e154482c4a8c6ae883ec59f542659808a7dc49"
+    "fb3fc75984c81f651d5392376fdc6fede614902d0268bb3d499aaa2ec206abab"
+    "5bcb51c921d4117ff28fee350c594353333d3b94be6a98b59bfdc6b3e88d3ed1"
+    "dacb0cc52e5edcd551e9b65bea65d9422b2c420c49390f17288fc43353fce567"
+);
+const PSS_SIG: [u8; 256] = hex!(
+    "5e033058539394edc762d0ac79fc7929d39ac6ddd30fb3f044f152b87b5bba21"
+    "1120421e31c88122deca75bece9234bb1a4542b900f028dc13f724c161bbe57a"
+    "9dfdb957249ccebba9e3b2d5a4b8efbd40bf09dcf9dbb68bd3513b0dc0c40692"
+    "3ddb1eee76f49ef87af31477fcf8738ce671d32fddc16e27d73de566aba3491e"
+    "9df7a33eed64add4b7a04e1a8aaae676aadf2882fe74408c587f04bc8b84877d"
+    "3855c6f947dc99e9cf92259e6f2e967d49f1d4ba3c9e75a33b5cb6ee8e192491"
+    "c41befb1fc45d7bcbe93f96cfd6dcd1871860606ca1caf1d6806a2f771ccfe4a"
+    "cfea9a0d5c4a461e68980ab612f0d134a1c5eae70d243215e747a6a0b5015ddb"
+);
+const MESSAGE: &[u8] = b"The quick brown fox jumps over the lazy dog";
+
+fn test_key() -> Rsa2048PrivateKey {
+    RsaPrivateKey::from_components(
+        N.parse().unwrap(),
+        BigUInt::from(65537u32),
+        D.parse().unwrap(),
+        P.parse().unwrap(),
+        Q.parse().unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_raw_rsa_vector() {
+    let key = test_key();
+    let m: BigUInt<32> = RAW_M.parse().unwrap();
+    let c: BigUInt<32> = RAW_C.parse().unwrap();
+    assert_eq!(key.public_key().raw_encrypt(&m), Ok(c));
+    assert_eq!(key.raw_decrypt(&c), Ok(m));
+    // The CRT path agrees with plain exponentiation by d.
+    assert_eq!(c.pow_mod(key.d(), key.public_key().n()), m);
+    assert_eq!(c.pow_mod_secret(key.d(), key.public_key().n()), m);
+    assert_eq!(
+        key.public_key().raw_encrypt(key.public_key().n()),
+        Err(RsaError::MessageTooLong)
+    );
+}
+
+#[test]
+fn test_oaep_vectors() {
+    let key = test_key();
+    assert_eq!(key.decrypt_oaep(&OAEP_CT, b""), Ok(MESSAGE.to_vec()));
+    assert_eq!(
+        key.decrypt_oaep(&OAEP_CT_LABEL, b"label"),
+        Ok(MESSAGE.to_vec())
+    );
+    assert_eq!(
+        key.decrypt_oaep(&OAEP_CT_LABEL, b""),
+        Err(RsaError::Decryption)
+    );
+    let mut tampered = OAEP_CT;
+    tampered[100] ^= 1;
+    assert_eq!(key.decrypt_oaep(&tampered, b""), Err(RsaError::Decryption));
+    assert_eq!(
+        key.decrypt_oaep(&OAEP_CT[1..], b""),
+        Err(RsaError::Decryption)
+    );
+}
+
+#[test]
+fn test_oaep_roundtrip() {
+    let key = test_key();
+    let mut rng = StdRng::seed_from_u64(1);
+    let public = key.public_key();
+    for len in [0, 1, 100, public.size() - 66] {
+        let message = vec![0xa5; len];
+        let ciphertext = public.encrypt_oaep(&message, b"label", &mut rng).unwrap();
+        assert_eq!(ciphertext.len(), 256);
+        assert_eq!(key.decrypt_oaep(&ciphertext, b"label"), Ok(message));
+    }
+    let too_long = vec![0; public.size() - 65];
+    assert_eq!(
+        public.encrypt_oaep(&too_long, b"", &mut rng),
+        Err(RsaError::MessageTooLong)
+    );
+}
+
+#[test]
+fn test_pss_vectors() {
+    let key = test_key();
+    let public = key.public_key();
+    assert_eq!(public.verify_pss(MESSAGE, &PSS_SIG), Ok(()));
+    assert_eq!(
+        public.verify_pss(b"another message", &PSS_SIG),
+        Err(RsaError::Verification)
+    );
+    let mut tampered = PSS_SIG;
+    tampered[0] ^= 0x80;
+    assert_eq!(
+        public.verify_pss(MESSAGE, &tampered),
+        Err(RsaError::Verification)
+    );
+
+    let mut rng = StdRng::seed_from_u64(2);
+    let signature = key.sign_pss(MESSAGE, &mut rng).unwrap();
+    assert_eq!(signature.len(), 256);
+    assert_eq!(public.verify_pss(MESSAGE, &signature), Ok(()));
+    assert_eq!(
+        public.verify_pss(&MESSAGE[1..], &signature),
+        Err(RsaError::Verification)
+    );
+}
+
+#[test]
+fn test_generate() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let key = RsaPrivateKey::<16>::generate(1024, &mut rng);
+    let public = key.public_key();
+    assert_eq!(public.n().bits(), 1024);
+    let (p, q) = key.primes();
+    assert!(p.is_probable_prime() && q.is_probable_prime());
+    let ciphertext = public.encrypt_oaep(b"secret", b"", &mut rng).unwrap();
+    assert_eq!(key.decrypt_oaep(&ciphertext, b""), Ok(b"secret".to_vec()));
+    let signature = key.sign_pss(b"signed", &mut rng).unwrap();
+    assert_eq!(public.verify_pss(b"signed", &signature), Ok(()));
+}
+
+#[test]
+fn test_private_key_debug_is_redacted() {
+    let key = test_key();
+    let debug = format!("{key:?}");
+    assert!(debug.starts_with("RsaPrivateKey { public: RsaPublicKey"));
+    assert!(debug.ends_with(", .. }"));
+    assert!(!debug.contains(&format!("{:?}", key.d())));
+    assert!(!debug.contains(&format!("{:?}", key.primes().0)));
+}
+
+#[test]
+fn test_invalid_components() {
+    let key = test_key();
+    let (p, q) = key.primes();
+    let n = *key.public_key().n();
+    let e = BigUInt::from(65537u32);
+    let wrong_d = *key.d() + BigUInt::from(2u8);
+    assert_eq!(
+        RsaPrivateKey::from_components(n, e, wrong_d, *p, *q).err(),
+        Some(RsaError::InvalidKey)
+    );
+    assert_eq!(
+        RsaPrivateKey::from_components(n, e, *key.d(), *p, *p).err(),
+        Some(RsaError::InvalidKey)
+    );
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "serde")]
+
+use p44::biguint::{BigUInt, BigUInt1024};
+use serde_test::{assert_de_tokens_error, assert_tokens, Configure, Token};
+
+#[test]
+fn test_human_readable_hex_string() {
+    let a = BigUInt::<2>::from(0x1234u16);
+    assert_tokens(
+        &a.readable(),
+        &[Token::Str("0x00000000000000000000000000001234")],
+    );
+    serde_test::assert_de_tokens(&a.readable(), &[Token::Str("0x1234")]);
+    assert_de_tokens_error::<serde_test::Readable<BigUInt<1>>>(
+        &[Token::Str("0x10000000000000000")],
+        "invalid length 17, expected a hex string or big-endian bytes of at most 8 bytes",
+    );
+}
+
+#[test]
+fn test_compact_minimal_bytes() {
+    let a = BigUInt1024::from(0x010203u32);
+    assert_tokens(&a.compact(), &[Token::Bytes(&[1, 2, 3])]);
+    assert_tokens(&BigUInt1024::new().compact(), &[Token::Bytes(&[])]);
+    serde_test::assert_de_tokens(&a.compact(), &[Token::Bytes(&[0, 0, 1, 2, 3])]);
+    assert_de_tokens_error::<serde_test::Compact<BigUInt<1>>>(
+        &[Token::Bytes(&[1, 0, 0, 0, 0, 0, 0, 0, 0])],
+        "out of range integral type conversion attempted",
+    );
+}
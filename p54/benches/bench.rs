@@ -25,3 +25,161 @@ fn bench_decrypt8(b: &mut Bencher) {
     });
     b.bytes = 128;
 }
+
+#[bench]
+fn bench_soft_encrypt8(b: &mut Bencher) {
+    let key = black_box([11u8; 16]);
+    let data = black_box([0u8; 128]);
+    let round_keys = soft::expand_key(&key);
+    b.iter(|| {
+        black_box(soft::encrypt8(&round_keys, &data));
+    });
+    b.bytes = 128;
+}
+
+#[bench]
+fn bench_soft_decrypt8(b: &mut Bencher) {
+    let key = black_box([7u8; 16]);
+    let data = black_box([0u8; 128]);
+    let round_keys = soft::expand_key(&key);
+    b.iter(|| {
+        black_box(soft::decrypt8(&round_keys, &data));
+    });
+    b.bytes = 128;
+}
+
+#[bench]
+fn bench_encrypt_in_place_16k(b: &mut Bencher) {
+    let round_keys = unsafe { expand_key(&black_box([11u8; 16])) };
+    let mut data = black_box(vec![0u8; 16384]);
+    b.iter(|| {
+        unsafe { encrypt_in_place(&round_keys, &mut data) };
+        black_box(&data);
+    });
+    b.bytes = 16384;
+}
+
+#[bench]
+fn bench_decrypt_in_place_16k(b: &mut Bencher) {
+    let round_keys = unsafe { expand_key(&black_box([7u8; 16])) };
+    let mut data = black_box(vec![0u8; 16384]);
+    b.iter(|| {
+        unsafe { decrypt_in_place(&round_keys, &mut data) };
+        black_box(&data);
+    });
+    b.bytes = 16384;
+}
+
+#[bench]
+fn bench_aes128_encrypt_into_16k(b: &mut Bencher) {
+    let aes = Aes128::new(&black_box([11u8; 16]));
+    let input = black_box(vec![0u8; 16384]);
+    let mut output = vec![0u8; 16384];
+    b.iter(|| {
+        aes.encrypt_into(&input, &mut output);
+        black_box(&output);
+    });
+    b.bytes = 16384;
+}
+
+#[bench]
+fn bench_aes128_decrypt_into_16k(b: &mut Bencher) {
+    let aes = Aes128::new(&black_box([7u8; 16]));
+    let input = black_box(vec![0u8; 16384]);
+    let mut output = vec![0u8; 16384];
+    b.iter(|| {
+        aes.decrypt_into(&input, &mut output);
+        black_box(&output);
+    });
+    b.bytes = 16384;
+}
+
+#[bench]
+fn bench_vaes256_encrypt_in_place_16k(b: &mut Bencher) {
+    bench_vaes_encrypt_in_place_16k(b, vaes::Width::Bits256);
+}
+
+#[bench]
+fn bench_vaes512_encrypt_in_place_16k(b: &mut Bencher) {
+    bench_vaes_encrypt_in_place_16k(b, vaes::Width::Bits512);
+}
+
+fn bench_vaes_encrypt_in_place_16k(b: &mut Bencher, width: vaes::Width) {
+    if !width.is_available() {
+        return;
+    }
+    let round_keys = unsafe { expand_key(&black_box([11u8; 16])) };
+    let mut data = black_box(vec![0u8; 16384]);
+    b.iter(|| {
+        unsafe { vaes::encrypt_in_place(width, &round_keys, &mut data) };
+        black_box(&data);
+    });
+    b.bytes = 16384;
+}
+
+#[bench]
+fn bench_vaes512_decrypt_in_place_16k(b: &mut Bencher) {
+    if !vaes::Width::Bits512.is_available() {
+        return;
+    }
+    let round_keys = unsafe { expand_key(&black_box([7u8; 16])) };
+    let mut data = black_box(vec![0u8; 16384]);
+    b.iter(|| {
+        unsafe { vaes::decrypt_in_place(vaes::Width::Bits512, &round_keys, &mut data) };
+        black_box(&data);
+    });
+    b.bytes = 16384;
+}
+
+#[bench]
+fn bench_aes128_ctr_16k(b: &mut Bencher) {
+    let mut ctr = modes::Ctr::new(Aes128::new(&black_box([11u8; 16])), &[0; 16]);
+    let mut data = black_box(vec![0u8; 16384]);
+    b.iter(|| {
+        ctr.apply_keystream(&mut data);
+        black_box(&data);
+    });
+    b.bytes = 16384;
+}
+
+#[bench]
+fn bench_soft_aes128_ctr_4k(b: &mut Bencher) {
+    let mut ctr = modes::Ctr::new(Aes128::new_soft(&black_box([11u8; 16])), &[0; 16]);
+    let mut data = black_box(vec![0u8; 4096]);
+    b.iter(|| {
+        ctr.apply_keystream(&mut data);
+        black_box(&data);
+    });
+    b.bytes = 4096;
+}
+
+#[bench]
+fn bench_soft_encrypt_in_place_4k(b: &mut Bencher) {
+    let round_keys = soft::expand_key(&black_box([11u8; 16]));
+    let mut data = black_box(vec![0u8; 4096]);
+    b.iter(|| {
+        soft::encrypt_in_place(&round_keys, &mut data);
+        black_box(&data);
+    });
+    b.bytes = 4096;
+}
+
+#[bench]
+fn bench_aes128_gcm_encrypt_4k(b: &mut Bencher) {
+    let gcm = modes::Aes128Gcm::new(Aes128::new(&black_box([11u8; 16])));
+    let mut data = black_box(vec![0u8; 4096]);
+    b.iter(|| {
+        black_box(gcm.encrypt(&[0; 12], &[], &mut data).unwrap());
+    });
+    b.bytes = 4096;
+}
+
+#[bench]
+fn bench_aes256_gcm_encrypt_4k(b: &mut Bencher) {
+    let gcm = modes::Aes256Gcm::new(Aes256::new(&black_box([11u8; 32])));
+    let mut data = black_box(vec![0u8; 4096]);
+    b.iter(|| {
+        black_box(gcm.encrypt(&[0; 12], &[], &mut data).unwrap());
+    });
+    b.bytes = 4096;
+}
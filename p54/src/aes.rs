@@ -0,0 +1,332 @@
+//! A safe AES block cipher that picks its implementation at runtime.
+//!
+//! `Aes<R>` checks for AES-NI once, when it is constructed. With AES-NI it
+//! keeps both the encryption key schedule and the inverse schedule used by
+//! `aesdec`, so decryption does not run `aesimc` on every block, and it
+//! processes bulk data with the widest VAES vectors the CPU has, if any.
+//! Without AES-NI, or on other architectures, it uses the constant-time
+//! software AES in `soft`. Either way the schedules are kept in
+//! `SecretKeys`, which wipes them when the `Aes` is dropped.
+
+#[cfg(target_arch = "x86_64")]
+use crate::{aesni, vaes};
+use crate::{soft, AesKey, SecretKeys};
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// An expanded AES key with `R` round keys, ready to encrypt and decrypt.
+#[derive(Clone)]
+pub struct Aes<const R: usize> {
+    backend: Backend<R>,
+}
+
+#[derive(Clone)]
+enum Backend<const R: usize> {
+    #[cfg(target_arch = "x86_64")]
+    AesNi {
+        encrypt_keys: SecretKeys<[__m128i; R]>,
+        decrypt_keys: SecretKeys<[__m128i; R]>,
+        width: Option<vaes::Width>,
+    },
+    Soft {
+        keys: SecretKeys<[[u8; 16]; R]>,
+    },
+}
+
+pub type Aes128 = Aes<11>;
+pub type Aes192 = Aes<13>;
+pub type Aes256 = Aes<15>;
+
+/// Returns whether this CPU supports the instructions the AES-NI functions use.
+pub fn aesni_available() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        is_x86_feature_detected!("sse2") && is_x86_feature_detected!("aes")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+impl<const R: usize> Aes<R> {
+    /// Expands `key`, using AES-NI if the CPU supports it.
+    pub fn new<K: AesKey<SoftRoundKeys = [[u8; 16]; R]>>(key: &K) -> Self {
+        let keys = SecretKeys::new(key.expand_soft());
+        #[cfg(target_arch = "x86_64")]
+        if aesni_available() {
+            // SAFETY: AES-NI and SSE2 were detected above.
+            unsafe {
+                let encrypt_keys = SecretKeys::new(
+                    keys.expose()
+                        .map(|key| _mm_loadu_si128(key.as_ptr() as *const _)),
+                );
+                let decrypt_keys =
+                    SecretKeys::new(aesni::inverse_key_schedule(encrypt_keys.expose()));
+                return Self {
+                    backend: Backend::AesNi {
+                        encrypt_keys,
+                        decrypt_keys,
+                        width: vaes::Width::detect(),
+                    },
+                };
+            }
+        }
+        Self {
+            backend: Backend::Soft { keys },
+        }
+    }
+
+    /// Expands `key` for the software implementation, even if AES-NI is available.
+    pub fn new_soft<K: AesKey<SoftRoundKeys = [[u8; 16]; R]>>(key: &K) -> Self {
+        Self {
+            backend: Backend::Soft {
+                keys: SecretKeys::new(key.expand_soft()),
+            },
+        }
+    }
+
+    /// Returns whether this key uses AES-NI rather than the software implementation.
+    pub fn is_hardware_accelerated(&self) -> bool {
+        !matches!(self.backend, Backend::Soft { .. })
+    }
+
+    /// Locks the key schedules into memory, so that they are never written
+    /// to swap. See `SecretKeys::lock`.
+    #[cfg(all(feature = "mlock", unix))]
+    pub fn lock(&mut self) -> std::io::Result<()> {
+        match &mut self.backend {
+            #[cfg(target_arch = "x86_64")]
+            Backend::AesNi {
+                encrypt_keys,
+                decrypt_keys,
+                ..
+            } => {
+                encrypt_keys.lock()?;
+                decrypt_keys.lock()
+            }
+            Backend::Soft { keys } => keys.lock(),
+        }
+    }
+
+    pub fn encrypt_block(&self, block: &mut [u8; 16]) {
+        self.encrypt_in_place(block);
+    }
+
+    pub fn decrypt_block(&self, block: &mut [u8; 16]) {
+        self.decrypt_in_place(block);
+    }
+
+    /// Encrypts `blocks` in place, eight at a time where possible.
+    pub fn encrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        self.encrypt_in_place(blocks.as_flattened_mut());
+    }
+
+    /// Decrypts `blocks` in place, eight at a time where possible.
+    pub fn decrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        self.decrypt_in_place(blocks.as_flattened_mut());
+    }
+
+    /// Encrypts `data`, any whole number of blocks, in place.
+    /// # Panics
+    /// If the length of `data` is not a multiple of 16.
+    pub fn encrypt_in_place(&self, data: &mut [u8]) {
+        match &self.backend {
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: `new` only picks AES-NI when it is available.
+            Backend::AesNi {
+                encrypt_keys,
+                width,
+                ..
+            } => unsafe {
+                match width {
+                    Some(width) => vaes::encrypt_in_place(*width, encrypt_keys.expose(), data),
+                    None => aesni::encrypt_in_place(encrypt_keys.expose(), data),
+                }
+            },
+            Backend::Soft { keys } => soft::encrypt_in_place(keys.expose(), data),
+        }
+    }
+
+    /// Decrypts `data`, any whole number of blocks, in place.
+    /// # Panics
+    /// If the length of `data` is not a multiple of 16.
+    pub fn decrypt_in_place(&self, data: &mut [u8]) {
+        match &self.backend {
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: `new` only picks AES-NI when it is available.
+            Backend::AesNi {
+                decrypt_keys,
+                width,
+                ..
+            } => unsafe {
+                match width {
+                    Some(width) => {
+                        vaes::decrypt_inverse_in_place(*width, decrypt_keys.expose(), data)
+                    }
+                    None => aesni::decrypt_inverse_in_place(decrypt_keys.expose(), data),
+                }
+            },
+            Backend::Soft { keys } => soft::decrypt_in_place(keys.expose(), data),
+        }
+    }
+
+    /// Encrypts `input`, any whole number of blocks, into `output`.
+    /// # Panics
+    /// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+    pub fn encrypt_into(&self, input: &[u8], output: &mut [u8]) {
+        match &self.backend {
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: `new` only picks AES-NI when it is available.
+            Backend::AesNi {
+                encrypt_keys,
+                width,
+                ..
+            } => unsafe {
+                match width {
+                    Some(width) => vaes::encrypt_into(*width, encrypt_keys.expose(), input, output),
+                    None => aesni::encrypt_into(encrypt_keys.expose(), input, output),
+                }
+            },
+            Backend::Soft { keys } => soft::encrypt_into(keys.expose(), input, output),
+        }
+    }
+
+    /// Decrypts `input`, any whole number of blocks, into `output`.
+    /// # Panics
+    /// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+    pub fn decrypt_into(&self, input: &[u8], output: &mut [u8]) {
+        match &self.backend {
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: `new` only picks AES-NI when it is available.
+            Backend::AesNi {
+                decrypt_keys,
+                width,
+                ..
+            } => unsafe {
+                match width {
+                    Some(width) => {
+                        vaes::decrypt_inverse_into(*width, decrypt_keys.expose(), input, output)
+                    }
+                    None => aesni::decrypt_inverse_into(decrypt_keys.expose(), input, output),
+                }
+            },
+            Backend::Soft { keys } => soft::decrypt_into(keys.expose(), input, output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // FIPS-197 Appendix C.
+    #[test]
+    fn test_aes_fips197() {
+        let plaintext = hex!("00112233445566778899aabbccddeeff");
+
+        let aes = Aes128::new(&hex!("000102030405060708090a0b0c0d0e0f"));
+        let mut block = plaintext;
+        aes.encrypt_block(&mut block);
+        assert_eq!(block, hex!("69c4e0d86a7b0430d8cdb78070b4c55a"));
+        aes.decrypt_block(&mut block);
+        assert_eq!(block, plaintext);
+
+        let aes = Aes192::new(&hex!("000102030405060708090a0b0c0d0e0f1011121314151617"));
+        let mut block = plaintext;
+        aes.encrypt_block(&mut block);
+        assert_eq!(block, hex!("dda97ca4864cdfe06eaf70a0ec0d7191"));
+        aes.decrypt_block(&mut block);
+        assert_eq!(block, plaintext);
+
+        let aes = Aes256::new(&hex!(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        ));
+        let mut block = plaintext;
+        aes.encrypt_block(&mut block);
+        assert_eq!(block, hex!("8ea2b7ca516745bfeafc49904b496089"));
+        aes.decrypt_block(&mut block);
+        assert_eq!(block, plaintext);
+    }
+
+    #[test]
+    fn test_aes_blocks() {
+        let aes = Aes128::new(&[0x2a; 16]);
+        // Two full chunks of eight and a remainder of three.
+        let plaintext: Vec<[u8; 16]> = (0..19).map(|i| [i as u8; 16]).collect();
+        let mut blocks = plaintext.clone();
+        aes.encrypt_blocks(&mut blocks);
+        for (encrypted, block) in blocks.iter().zip(&plaintext) {
+            let mut expected = *block;
+            aes.encrypt_block(&mut expected);
+            assert_eq!(*encrypted, expected);
+        }
+        aes.decrypt_blocks(&mut blocks);
+        assert_eq!(blocks, plaintext);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_aes_matches_decrypt1() {
+        let key = hex!("c0000000000000000000000000000000");
+        let aes = Aes128::new(&key);
+        let ciphertext = hex!("4bc3f883450c113c64ca42e1112a9e87");
+        let expected = unsafe { crate::decrypt1(&crate::expand_key(&key), &ciphertext) };
+        let mut block = ciphertext;
+        aes.decrypt_block(&mut block);
+        assert_eq!(block, expected);
+    }
+
+    #[test]
+    fn test_aes_soft_matches_aesni() {
+        let key = hex!("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let aes = Aes256::new(&key);
+        let soft = Aes256::new_soft(&key);
+        assert!(!soft.is_hardware_accelerated());
+        assert_eq!(aes.is_hardware_accelerated(), aesni_available());
+
+        let plaintext: Vec<[u8; 16]> = (0..21u8).map(|i| [i.wrapping_mul(37); 16]).collect();
+        let mut blocks = plaintext.clone();
+        let mut soft_blocks = plaintext.clone();
+        aes.encrypt_blocks(&mut blocks);
+        soft.encrypt_blocks(&mut soft_blocks);
+        assert_eq!(blocks, soft_blocks);
+        soft.decrypt_blocks(&mut blocks);
+        aes.decrypt_blocks(&mut soft_blocks);
+        assert_eq!(blocks, plaintext);
+        assert_eq!(soft_blocks, plaintext);
+    }
+
+    #[test]
+    fn test_aes_into() {
+        let aes = Aes192::new(&[0x5c; 24]);
+        let plaintext: Vec<u8> = (0..16 * 77).map(|i| i as u8).collect();
+        let mut ciphertext = vec![0; plaintext.len()];
+        aes.encrypt_into(&plaintext, &mut ciphertext);
+        let mut data = plaintext.clone();
+        Aes192::new_soft(&[0x5c; 24]).encrypt_in_place(&mut data);
+        assert_eq!(ciphertext, data);
+
+        let mut decrypted = vec![0; plaintext.len()];
+        aes.decrypt_into(&ciphertext, &mut decrypted);
+        assert_eq!(decrypted, plaintext);
+        aes.decrypt_in_place(&mut data);
+        assert_eq!(data, plaintext);
+    }
+
+    #[cfg(all(feature = "mlock", unix))]
+    #[test]
+    fn test_aes_lock() {
+        let key = hex!("000102030405060708090a0b0c0d0e0f");
+        let mut aes = Aes128::new(&key);
+        aes.lock().unwrap();
+        let mut block = hex!("00112233445566778899aabbccddeeff");
+        aes.encrypt_block(&mut block);
+        assert_eq!(block, hex!("69c4e0d86a7b0430d8cdb78070b4c55a"));
+        let mut soft = Aes128::new_soft(&key);
+        soft.lock().unwrap();
+        soft.decrypt_block(&mut block);
+        assert_eq!(block, hex!("00112233445566778899aabbccddeeff"));
+    }
+}
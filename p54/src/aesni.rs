@@ -0,0 +1,682 @@
+//! AES on x86_64 using the AES-NI instructions.
+
+use crate::{assert_round_key_count, assert_whole_blocks, secret, AesKey};
+use std::arch::x86_64::*;
+
+macro_rules! set_round_key {
+    ($i:expr, $round_keys:ident) => {{
+        let prev_round_key = $round_keys[$i];
+        const RCON: i32 = ROUND_CONSTANTS[$i];
+        $round_keys[$i + 1] = get_next_round_key::<RCON>(prev_round_key);
+    }};
+}
+
+/// This function expands AES keys of any size to round keys.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `key` parameter must be a valid reference to a 16-, 24- or 32-byte array.
+pub unsafe fn expand_key<K: AesKey>(key: &K) -> K::RoundKeys {
+    key.expand()
+}
+
+/// This function expands 128-bit AES keys to round keys.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `key` parameter must be a valid reference to a 16-byte array.
+pub unsafe fn expand_key_128(key: &[u8; 16]) -> [__m128i; 11] {
+    // The first round key is the key itself
+    let mut round_keys = [_mm_setzero_si128(); 11];
+    round_keys[0] = _mm_loadu_si128(key.as_ptr() as *const _);
+    // The rest of the round keys are generated from the previous round key
+    // Precomputed round constants for AES key schedule
+    const ROUND_CONSTANTS: [i32; 10] = [1, 2, 4, 8, 16, 32, 64, 128, 27, 54];
+
+    set_round_key!(0, round_keys);
+    set_round_key!(1, round_keys);
+    set_round_key!(2, round_keys);
+    set_round_key!(3, round_keys);
+    set_round_key!(4, round_keys);
+    set_round_key!(5, round_keys);
+    set_round_key!(6, round_keys);
+    set_round_key!(7, round_keys);
+    set_round_key!(8, round_keys);
+    set_round_key!(9, round_keys);
+
+    round_keys
+}
+
+/// This function expands 192-bit AES keys to round keys.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `key` parameter must be a valid reference to a 24-byte array.
+pub unsafe fn expand_key_192(key: &[u8; 24]) -> [__m128i; 13] {
+    // Each step produces six words, one and a half round keys, in `low`
+    // (four words) and the low half of `high` (two words). Round keys that
+    // straddle two steps are spliced together from the 64-bit halves.
+    let mut round_keys = [_mm_setzero_si128(); 13];
+    let mut low = _mm_loadu_si128(key.as_ptr() as *const _);
+    let mut high = _mm_loadl_epi64(key[16..].as_ptr() as *const _);
+    round_keys[0] = low;
+    round_keys[1] = high;
+
+    next_192_words::<1>(&mut low, &mut high);
+    round_keys[1] = splice_halves::<0>(round_keys[1], low);
+    round_keys[2] = splice_halves::<1>(low, high);
+    next_192_words::<2>(&mut low, &mut high);
+    round_keys[3] = low;
+    round_keys[4] = high;
+    next_192_words::<4>(&mut low, &mut high);
+    round_keys[4] = splice_halves::<0>(round_keys[4], low);
+    round_keys[5] = splice_halves::<1>(low, high);
+    next_192_words::<8>(&mut low, &mut high);
+    round_keys[6] = low;
+    round_keys[7] = high;
+    next_192_words::<16>(&mut low, &mut high);
+    round_keys[7] = splice_halves::<0>(round_keys[7], low);
+    round_keys[8] = splice_halves::<1>(low, high);
+    next_192_words::<32>(&mut low, &mut high);
+    round_keys[9] = low;
+    round_keys[10] = high;
+    next_192_words::<64>(&mut low, &mut high);
+    round_keys[10] = splice_halves::<0>(round_keys[10], low);
+    round_keys[11] = splice_halves::<1>(low, high);
+    next_192_words::<128>(&mut low, &mut high);
+    round_keys[12] = low;
+
+    round_keys
+}
+
+/// This function expands 256-bit AES keys to round keys.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `key` parameter must be a valid reference to a 32-byte array.
+pub unsafe fn expand_key_256(key: &[u8; 32]) -> [__m128i; 15] {
+    // The first two round keys are the key itself. Each later round key is
+    // derived from the two before it, alternating between the step with a
+    // round constant and the plain SubWord step.
+    let mut round_keys = [_mm_setzero_si128(); 15];
+    round_keys[0] = _mm_loadu_si128(key.as_ptr() as *const _);
+    round_keys[1] = _mm_loadu_si128(key[16..].as_ptr() as *const _);
+
+    round_keys[2] = next_256_even::<1>(round_keys[0], round_keys[1]);
+    round_keys[3] = next_256_odd(round_keys[1], round_keys[2]);
+    round_keys[4] = next_256_even::<2>(round_keys[2], round_keys[3]);
+    round_keys[5] = next_256_odd(round_keys[3], round_keys[4]);
+    round_keys[6] = next_256_even::<4>(round_keys[4], round_keys[5]);
+    round_keys[7] = next_256_odd(round_keys[5], round_keys[6]);
+    round_keys[8] = next_256_even::<8>(round_keys[6], round_keys[7]);
+    round_keys[9] = next_256_odd(round_keys[7], round_keys[8]);
+    round_keys[10] = next_256_even::<16>(round_keys[8], round_keys[9]);
+    round_keys[11] = next_256_odd(round_keys[9], round_keys[10]);
+    round_keys[12] = next_256_even::<32>(round_keys[10], round_keys[11]);
+    round_keys[13] = next_256_odd(round_keys[11], round_keys[12]);
+    round_keys[14] = next_256_even::<64>(round_keys[12], round_keys[13]);
+
+    round_keys
+}
+
+/// Returns the running XOR of the four words of `key`, so that word `i` of
+/// the result is the XOR of words `0..=i`.
+unsafe fn xor_prefix(key: __m128i) -> __m128i {
+    let mut result = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+    result = _mm_xor_si128(result, _mm_slli_si128(key, 8));
+    _mm_xor_si128(result, _mm_slli_si128(key, 12))
+}
+
+/// This functions gets the next AES round key from the previous round key.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `key` parameter must be a valid reference to a 16-byte array.
+pub unsafe fn get_next_round_key<const RCON: i32>(prev_round_key: __m128i) -> __m128i {
+    // Rotate the last 4 bytes of the previous round key
+    let mut next_key = _mm_aeskeygenassist_si128(prev_round_key, RCON);
+    next_key = _mm_shuffle_epi32(next_key, 0xFF); // Broadcast the last 4 bytes
+    _mm_xor_si128(next_key, xor_prefix(prev_round_key))
+}
+
+/// Advances the AES-192 schedule by six words, from the previous six in
+/// `low` and the low half of `high`.
+unsafe fn next_192_words<const RCON: i32>(low: &mut __m128i, high: &mut __m128i) {
+    // RotWord(SubWord(w5)) ^ RCON, broadcast
+    let assist = _mm_shuffle_epi32(_mm_aeskeygenassist_si128(*high, RCON), 0x55);
+    *low = _mm_xor_si128(xor_prefix(*low), assist);
+    // The last two words continue the running XOR from the new w3
+    let carry = _mm_shuffle_epi32(*low, 0xFF);
+    *high = _mm_xor_si128(_mm_xor_si128(*high, _mm_slli_si128(*high, 4)), carry);
+}
+
+/// Returns the low (`HALF == 0`) or high (`HALF == 1`) half of `a`
+/// followed by the low half of `b`.
+unsafe fn splice_halves<const HALF: i32>(a: __m128i, b: __m128i) -> __m128i {
+    _mm_castpd_si128(_mm_shuffle_pd(
+        _mm_castsi128_pd(a),
+        _mm_castsi128_pd(b),
+        HALF,
+    ))
+}
+
+/// The AES-256 round keys with even index, which apply the round constant.
+unsafe fn next_256_even<const RCON: i32>(prev2: __m128i, prev1: __m128i) -> __m128i {
+    let assist = _mm_shuffle_epi32(_mm_aeskeygenassist_si128(prev1, RCON), 0xFF);
+    _mm_xor_si128(xor_prefix(prev2), assist)
+}
+
+/// The AES-256 round keys with odd index, which apply SubWord without rotation.
+unsafe fn next_256_odd(prev2: __m128i, prev1: __m128i) -> __m128i {
+    let assist = _mm_shuffle_epi32(_mm_aeskeygenassist_si128(prev1, 0), 0xAA);
+    _mm_xor_si128(xor_prefix(prev2), assist)
+}
+
+/// This function encrypts one 128-bit block using AES.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `block` parameter must be a valid reference to a 16-byte array.
+/// 3. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 4. The `keys` array must be generated using the `expand_key` function.
+pub unsafe fn encrypt1<const R: usize>(keys: &[__m128i; R], block: &[u8; 16]) -> [u8; 16] {
+    const { assert_round_key_count(R) };
+    // Load the input block
+    let mut state = _mm_loadu_si128(block.as_ptr() as *const _);
+    // Add the first round key
+    state = _mm_xor_si128(state, keys[0]);
+    // Perform all rounds but the last
+    for key in &keys[1..R - 1] {
+        state = _mm_aesenc_si128(state, *key);
+    }
+    // Perform the final round of AES encryption
+    state = _mm_aesenclast_si128(state, keys[R - 1]);
+    // Store the result
+    let mut output = [0u8; 16];
+    _mm_storeu_si128(output.as_mut_ptr() as *mut _, state);
+    output
+}
+
+/// This function decrypts one 128-bit block using AES.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `block` parameter must be a valid reference to a 16-byte array.
+/// 3. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 4. The `keys` array must be generated using the `expand_key` function.
+pub unsafe fn decrypt1<const R: usize>(keys: &[__m128i; R], block: &[u8; 16]) -> [u8; 16] {
+    const { assert_round_key_count(R) };
+    // Load the input block
+    let mut state = _mm_loadu_si128(block.as_ptr() as *const _);
+    // Add the first round key
+    state = _mm_xor_si128(state, keys[R - 1]);
+    // Perform all rounds but the last, in reverse
+    for key in keys[1..R - 1].iter().rev() {
+        let rk = _mm_aesimc_si128(*key);
+        state = _mm_aesdec_si128(state, rk);
+    }
+    // Perform the final round of AES decryption
+    state = _mm_aesdeclast_si128(state, keys[0]);
+    // Store the result
+    let mut output = [0u8; 16];
+    _mm_storeu_si128(output.as_mut_ptr() as *mut _, state);
+    output
+}
+
+/// This function encrypts eight 128-bit blocks using AES.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `blocks` parameter must be a valid reference to an array of 128 bytes.
+/// 3. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 4. The `keys` array must be generated using the `expand_key` function.
+pub unsafe fn encrypt8<const R: usize>(keys: &[__m128i; R], blocks: &[u8; 128]) -> [u8; 128] {
+    let mut output = *blocks;
+    encrypt_in_place(keys, &mut output);
+    output
+}
+
+/// This function decrypts eight 128-bit blocks using AES.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `blocks` parameter must be a valid reference to a 16-byte array.
+/// 3. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 4. The `keys` array must be generated using the `expand_key` function.
+pub unsafe fn decrypt8<const R: usize>(keys: &[__m128i; R], blocks: &[u8; 128]) -> [u8; 128] {
+    let mut output = *blocks;
+    decrypt_in_place(keys, &mut output);
+    output
+}
+
+/// This function encrypts any whole number of 128-bit blocks in place using
+/// AES, eight at a time with their rounds interleaved.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `data` is not a multiple of 16.
+#[target_feature(enable = "aes,sse2")]
+pub unsafe fn encrypt_in_place<const R: usize>(keys: &[__m128i; R], data: &mut [u8]) {
+    assert_whole_blocks(data.len());
+    let ptr = data.as_mut_ptr();
+    process_blocks(ptr, ptr, data.len(), |states| encrypt_states(keys, states));
+}
+
+/// This function encrypts any whole number of 128-bit blocks from `input`
+/// into `output` using AES, eight at a time with their rounds interleaved.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+#[target_feature(enable = "aes,sse2")]
+pub unsafe fn encrypt_into<const R: usize>(keys: &[__m128i; R], input: &[u8], output: &mut [u8]) {
+    assert_whole_blocks(input.len());
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Output length differs from input"
+    );
+    process_blocks(input.as_ptr(), output.as_mut_ptr(), input.len(), |states| {
+        encrypt_states(keys, states)
+    });
+}
+
+/// This function decrypts any whole number of 128-bit blocks in place using
+/// AES, eight at a time with their rounds interleaved.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `data` is not a multiple of 16.
+#[target_feature(enable = "aes,sse2")]
+pub unsafe fn decrypt_in_place<const R: usize>(keys: &[__m128i; R], data: &mut [u8]) {
+    let mut inverse_keys = inverse_key_schedule(keys);
+    decrypt_inverse_in_place(&inverse_keys, data);
+    secret::wipe(&mut inverse_keys);
+}
+
+/// This function decrypts any whole number of 128-bit blocks from `input`
+/// into `output` using AES, eight at a time with their rounds interleaved.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the required SIMD instructions (SSE2, AES-NI).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+#[target_feature(enable = "aes,sse2")]
+pub unsafe fn decrypt_into<const R: usize>(keys: &[__m128i; R], input: &[u8], output: &mut [u8]) {
+    let mut inverse_keys = inverse_key_schedule(keys);
+    decrypt_inverse_into(&inverse_keys, input, output);
+    secret::wipe(&mut inverse_keys);
+}
+
+/// Builds the key schedule for the equivalent inverse cipher: the round keys
+/// in reverse order, with `InvMixColumns` applied to all but the outer two.
+#[target_feature(enable = "aes,sse2")]
+pub(crate) unsafe fn inverse_key_schedule<const R: usize>(keys: &[__m128i; R]) -> [__m128i; R] {
+    const { assert_round_key_count(R) };
+    let mut inverse = [_mm_setzero_si128(); R];
+    inverse[0] = keys[R - 1];
+    for i in 1..R - 1 {
+        inverse[i] = _mm_aesimc_si128(keys[R - 1 - i]);
+    }
+    inverse[R - 1] = keys[0];
+    inverse
+}
+
+/// Like `decrypt_in_place`, with a schedule from `inverse_key_schedule`.
+#[target_feature(enable = "aes,sse2")]
+pub(crate) unsafe fn decrypt_inverse_in_place<const R: usize>(
+    inverse_keys: &[__m128i; R],
+    data: &mut [u8],
+) {
+    assert_whole_blocks(data.len());
+    let ptr = data.as_mut_ptr();
+    process_blocks(ptr, ptr, data.len(), |states| {
+        decrypt_states(inverse_keys, states)
+    });
+}
+
+/// Like `decrypt_into`, with a schedule from `inverse_key_schedule`.
+#[target_feature(enable = "aes,sse2")]
+pub(crate) unsafe fn decrypt_inverse_into<const R: usize>(
+    inverse_keys: &[__m128i; R],
+    input: &[u8],
+    output: &mut [u8],
+) {
+    assert_whole_blocks(input.len());
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Output length differs from input"
+    );
+    process_blocks(input.as_ptr(), output.as_mut_ptr(), input.len(), |states| {
+        decrypt_states(inverse_keys, states)
+    });
+}
+
+/// Loads `len` bytes of blocks from `input`, runs `rounds` over them eight
+/// at a time and then over the rest, and stores them to `output`. A group is
+/// loaded before it is stored, so `output` may be `input`.
+#[target_feature(enable = "aes,sse2")]
+pub(crate) unsafe fn process_blocks(
+    input: *const u8,
+    output: *mut u8,
+    len: usize,
+    rounds: impl Fn(&mut [__m128i]),
+) {
+    let (groups, rest) = (len / (16 * 8), len % (16 * 8) / 16);
+    let mut states = [_mm_setzero_si128(); 8];
+    for group in 0..groups {
+        // A full group gets its own call, so that the compiler sees eight
+        // states and keeps them in registers.
+        process_group(input, output, 16 * 8 * group, &mut states, &rounds);
+    }
+    process_group(input, output, 16 * 8 * groups, &mut states[..rest], &rounds);
+}
+
+#[target_feature(enable = "aes,sse2")]
+#[inline]
+unsafe fn process_group(
+    input: *const u8,
+    output: *mut u8,
+    start: usize,
+    states: &mut [__m128i],
+    rounds: &impl Fn(&mut [__m128i]),
+) {
+    for (i, state) in states.iter_mut().enumerate() {
+        *state = _mm_loadu_si128(input.add(start + 16 * i) as *const _);
+    }
+    rounds(states);
+    for (i, state) in states.iter().enumerate() {
+        _mm_storeu_si128(output.add(start + 16 * i) as *mut _, *state);
+    }
+}
+
+/// Encrypts up to eight states round by round, so that each `aesenc` can
+/// start before the one on the previous state has finished.
+#[target_feature(enable = "aes,sse2")]
+pub(crate) unsafe fn encrypt_states<const R: usize>(keys: &[__m128i; R], states: &mut [__m128i]) {
+    const { assert_round_key_count(R) };
+    for state in states.iter_mut() {
+        *state = _mm_xor_si128(*state, keys[0]);
+    }
+    for key in &keys[1..R - 1] {
+        for state in states.iter_mut() {
+            *state = _mm_aesenc_si128(*state, *key);
+        }
+    }
+    for state in states.iter_mut() {
+        *state = _mm_aesenclast_si128(*state, keys[R - 1]);
+    }
+}
+
+/// Decrypts up to eight states round by round with a schedule from
+/// `inverse_key_schedule`.
+#[target_feature(enable = "aes,sse2")]
+pub(crate) unsafe fn decrypt_states<const R: usize>(
+    inverse_keys: &[__m128i; R],
+    states: &mut [__m128i],
+) {
+    const { assert_round_key_count(R) };
+    for state in states.iter_mut() {
+        *state = _mm_xor_si128(*state, inverse_keys[0]);
+    }
+    for key in &inverse_keys[1..R - 1] {
+        for state in states.iter_mut() {
+            *state = _mm_aesdec_si128(*state, *key);
+        }
+    }
+    for state in states.iter_mut() {
+        *state = _mm_aesdeclast_si128(*state, inverse_keys[R - 1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_expand_key() {
+        let key: [u8; 16] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+            0x0E, 0x0F,
+        ];
+        unsafe {
+            let round_keys = expand_key(&key);
+            assert_eq!(round_keys.len(), 11);
+        }
+    }
+    #[test]
+    fn test_expand_key_2() {
+        let key: [u8; 16] = [
+            0x54, 0x68, 0x61, 0x74, 0x73, 0x20, 0x6D, 0x79, 0x20, 0x4B, 0x75, 0x6E, 0x67, 0x20,
+            0x46, 0x75,
+        ];
+        let final_key: [u8; 16] = [
+            0x28, 0xFD, 0xDE, 0xF8, 0x6D, 0xA4, 0x24, 0x4A, 0xCC, 0xC0, 0xA4, 0xFE, 0x3B, 0x31,
+            0x6F, 0x26,
+        ];
+        let mut final_key_2 = [0u8; 16];
+        unsafe {
+            let round_keys = expand_key(&key);
+            _mm_storeu_si128(final_key_2.as_mut_ptr() as *mut _, round_keys[10]);
+        }
+        assert_eq!(final_key_2, final_key);
+    }
+
+    #[test]
+    fn test_encrypt1_1() {
+        let key = hex!("80000000000000000000000000000000");
+        let plaintext = hex!("00000000000000000000000000000000");
+        let expected_ciphertext = hex!("0edd33d3c621e546455bd8ba1418bec8");
+        unsafe {
+            let round_keys = expand_key(&key);
+            let ciphertext = encrypt1(&round_keys, &plaintext);
+            assert_eq!(ciphertext, expected_ciphertext);
+        }
+    }
+
+    #[test]
+    fn test_encrypt1_2() {
+        let key = hex!("c0000000000000000000000000000000");
+        let plaintext = hex!("00000000000000000000000000000000");
+        let expected_ciphertext = hex!("4bc3f883450c113c64ca42e1112a9e87");
+        unsafe {
+            let round_keys = expand_key(&key);
+            let ciphertext = encrypt1(&round_keys, &plaintext);
+            assert_eq!(ciphertext, expected_ciphertext);
+        }
+    }
+
+    #[test]
+    fn test_decrypt1_1() {
+        let key = hex!("80000000000000000000000000000000");
+        let ciphertext = hex!("0edd33d3c621e546455bd8ba1418bec8");
+        let expected_plaintext = hex!("00000000000000000000000000000000");
+        unsafe {
+            let round_keys = expand_key(&key);
+            let plaintext = decrypt1(&round_keys, &ciphertext);
+            assert_eq!(plaintext, expected_plaintext);
+        }
+    }
+
+    #[test]
+    fn test_decrypt1_2() {
+        let key = hex!("c0000000000000000000000000000000");
+        let ciphertext = hex!("4bc3f883450c113c64ca42e1112a9e87");
+        let expected_plaintext = hex!("00000000000000000000000000000000");
+        unsafe {
+            let round_keys = expand_key(&key);
+            let plaintext = decrypt1(&round_keys, &ciphertext);
+            assert_eq!(plaintext, expected_plaintext);
+        }
+    }
+
+    #[test]
+    fn test_encrypt8() {
+        let key = hex!("0edd33d3c621e546455bd8ba1418bec8");
+        let round_keys = unsafe { expand_key(&key) };
+        let plaintext8 = hex!(
+            "00000000000000000000000000000000"
+            "01010101010101010101010101010101"
+            "02020202020202020202020202020202"
+            "03030303030303030303030303030303"
+            "04040404040404040404040404040404"
+            "05050505050505050505050505050505"
+            "06060606060606060606060606060606"
+            "07070707070707070707070707070707"
+        );
+        let plaintext1 = hex!("01010101010101010101010101010101");
+        let ciphertext8 = unsafe { encrypt8(&round_keys, &plaintext8) };
+        let ciphertext1 = unsafe { encrypt1(&round_keys, &plaintext1) };
+        assert_eq!(ciphertext8[16..32], ciphertext1);
+    }
+
+    #[test]
+    fn test_decrypt8() {
+        let key = hex!("4bc3f883450c113c64ca42e1112a9e87");
+        let round_keys = unsafe { expand_key(&key) };
+        let ciphertext8 = hex!(
+            "00000000000000000000000000000000"
+            "01010101010101010101010101010101"
+            "02020202020202020202020202020202"
+            "03030303030303030303030303030303"
+            "04040404040404040404040404040404"
+            "05050505050505050505050505050505"
+            "06060606060606060606060606060606"
+            "07070707070707070707070707070707"
+        );
+        let ciphertext1 = hex!("04040404040404040404040404040404");
+        let plaintext8 = unsafe { encrypt8(&round_keys, &ciphertext8) };
+        let plaintext1 = unsafe { encrypt1(&round_keys, &ciphertext1) };
+        assert_eq!(plaintext8[64..80], plaintext1);
+    }
+
+    // FIPS-197 Appendix A: the last round key of each example key expansion.
+    #[test]
+    fn test_expand_key_fips197() {
+        let mut last = [0u8; 16];
+        unsafe {
+            let round_keys = expand_key(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+            _mm_storeu_si128(last.as_mut_ptr() as *mut _, round_keys[10]);
+            assert_eq!(last, hex!("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+
+            let round_keys = expand_key(&hex!("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b"));
+            assert_eq!(round_keys.len(), 13);
+            _mm_storeu_si128(last.as_mut_ptr() as *mut _, round_keys[12]);
+            assert_eq!(last, hex!("e98ba06f448c773c8ecc720401002202"));
+
+            let round_keys = expand_key(&hex!(
+                "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+            ));
+            assert_eq!(round_keys.len(), 15);
+            _mm_storeu_si128(last.as_mut_ptr() as *mut _, round_keys[14]);
+            assert_eq!(last, hex!("fe4890d1e6188d0b046df344706c631e"));
+        }
+    }
+
+    // FIPS-197 Appendix C: the example vectors for all three key sizes.
+    #[test]
+    fn test_encrypt1_fips197() {
+        let plaintext = hex!("00112233445566778899aabbccddeeff");
+        unsafe {
+            let round_keys = expand_key(&hex!("000102030405060708090a0b0c0d0e0f"));
+            let ciphertext = encrypt1(&round_keys, &plaintext);
+            assert_eq!(ciphertext, hex!("69c4e0d86a7b0430d8cdb78070b4c55a"));
+            assert_eq!(decrypt1(&round_keys, &ciphertext), plaintext);
+
+            let round_keys = expand_key(&hex!("000102030405060708090a0b0c0d0e0f1011121314151617"));
+            let ciphertext = encrypt1(&round_keys, &plaintext);
+            assert_eq!(ciphertext, hex!("dda97ca4864cdfe06eaf70a0ec0d7191"));
+            assert_eq!(decrypt1(&round_keys, &ciphertext), plaintext);
+
+            let round_keys = expand_key(&hex!(
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+            ));
+            let ciphertext = encrypt1(&round_keys, &plaintext);
+            assert_eq!(ciphertext, hex!("8ea2b7ca516745bfeafc49904b496089"));
+            assert_eq!(decrypt1(&round_keys, &ciphertext), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_encrypt8_decrypt8_192_256() {
+        let blocks: [u8; 128] = std::array::from_fn(|i| i as u8);
+        let check = |encrypted: [u8; 128], decrypted: [u8; 128], first: [u8; 16]| {
+            assert_eq!(encrypted[..16], first);
+            assert_eq!(decrypted, blocks);
+        };
+        unsafe {
+            let keys = expand_key(&hex!("000102030405060708090a0b0c0d0e0f1011121314151617"));
+            let encrypted = encrypt8(&keys, &blocks);
+            let first = encrypt1(&keys, blocks[..16].try_into().unwrap());
+            check(encrypted, decrypt8(&keys, &encrypted), first);
+
+            let keys = expand_key_256(&[0x42; 32]);
+            let encrypted = encrypt8(&keys, &blocks);
+            let first = encrypt1(&keys, blocks[..16].try_into().unwrap());
+            check(encrypted, decrypt8(&keys, &encrypted), first);
+        }
+    }
+
+    #[test]
+    fn test_encrypt_in_place() {
+        let round_keys = unsafe { expand_key(&[0x2a; 24]) };
+        // Whole chunks of eight blocks, a remainder, and nothing at all.
+        for blocks in [0, 3, 8, 21] {
+            let plaintext: Vec<u8> = (0..16 * blocks).map(|i| (i * 7) as u8).collect();
+            let mut data = plaintext.clone();
+            unsafe { encrypt_in_place(&round_keys, &mut data) };
+            for (encrypted, block) in data.chunks(16).zip(plaintext.chunks(16)) {
+                let expected = unsafe { encrypt1(&round_keys, block.try_into().unwrap()) };
+                assert_eq!(encrypted, expected);
+            }
+            let mut output = vec![0; data.len()];
+            unsafe { encrypt_into(&round_keys, &plaintext, &mut output) };
+            assert_eq!(output, data);
+
+            unsafe { decrypt_in_place(&round_keys, &mut data) };
+            assert_eq!(data, plaintext);
+            unsafe { decrypt_into(&round_keys, &output, &mut data) };
+            assert_eq!(data, plaintext);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Data length is not a multiple of 16 bytes")]
+    fn test_encrypt_in_place_partial_block() {
+        let round_keys = unsafe { expand_key(&[0; 16]) };
+        unsafe { encrypt_in_place(&round_keys, &mut [0; 40]) };
+    }
+
+    #[test]
+    #[should_panic(expected = "Output length differs from input")]
+    fn test_decrypt_into_length_mismatch() {
+        let round_keys = unsafe { expand_key(&[0; 16]) };
+        unsafe { decrypt_into(&round_keys, &[0; 32], &mut [0; 48]) };
+    }
+}
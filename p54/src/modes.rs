@@ -0,0 +1,66 @@
+//! Block cipher modes of operation over `Aes<R>`: CBC, CFB, OFB and CTR from
+//! NIST SP 800-38A, XTS from SP 800-38E (IEEE 1619), the GCM authenticated
+//! encryption mode from SP 800-38D, and AES key wrap with and without padding
+//! (RFC 3394 and RFC 5649).
+//!
+//! Wherever a mode lets blocks be processed independently (CBC decryption,
+//! CFB decryption, CTR, XTS and GCM), the blocks go through `Aes::encrypt_blocks`
+//! or `Aes::decrypt_blocks`, which run eight at a time. The counter modes
+//! generate `KEYSTREAM_BLOCKS` blocks per call, enough for a full step of the
+//! widest VAES kernel. CBC and CFB encryption and OFB are inherently serial.
+//!
+//! `CipherReader` and `CipherWriter` stream data through CTR or CBC with
+//! `std::io`, so a file or socket need not fit in memory.
+
+mod cbc;
+mod cfb;
+mod ctr;
+mod gcm;
+mod ghash;
+mod kw;
+mod ofb;
+mod stream;
+mod xts;
+
+pub use cbc::Cbc;
+pub use cfb::Cfb;
+pub use ctr::Ctr;
+pub use gcm::{Aes128Gcm, Aes256Gcm, Gcm};
+pub use kw::KeyWrap;
+pub use ofb::Ofb;
+pub use stream::{CbcChain, CbcDecryptor, CbcEncryptor, CipherReader, CipherWriter, Transform};
+pub use xts::Xts;
+
+use std::fmt;
+
+/// How many counter blocks CTR and GCM encrypt per `Aes::encrypt_blocks` call.
+const KEYSTREAM_BLOCKS: usize = 32;
+
+/// An error from decrypting or processing a message in one of the modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeError {
+    /// The input length is not one the mode can process.
+    InvalidLength,
+    /// The PKCS#7 padding of a decrypted CBC message is malformed.
+    InvalidPadding,
+    /// The authentication tag does not match the message.
+    InvalidTag,
+}
+
+impl fmt::Display for ModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModeError::InvalidLength => write!(f, "invalid input length"),
+            ModeError::InvalidPadding => write!(f, "invalid padding"),
+            ModeError::InvalidTag => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for ModeError {}
+
+fn xor_in_place(data: &mut [u8], other: &[u8]) {
+    for (byte, other) in data.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
@@ -0,0 +1,188 @@
+use super::{xor_in_place, ModeError};
+use crate::Aes;
+
+/// Cipher block chaining. The chaining value carries over between calls, so
+/// a long message can be processed in pieces of whole blocks.
+#[derive(Clone)]
+pub struct Cbc<const R: usize> {
+    aes: Aes<R>,
+    chain: [u8; 16],
+}
+
+impl<const R: usize> Cbc<R> {
+    pub fn new(aes: Aes<R>, iv: &[u8; 16]) -> Self {
+        Self { aes, chain: *iv }
+    }
+
+    /// Encrypts `blocks` in place, continuing from the previous call.
+    pub fn encrypt_blocks(&mut self, blocks: &mut [[u8; 16]]) {
+        for block in blocks {
+            xor_in_place(block, &self.chain);
+            self.aes.encrypt_block(block);
+            self.chain = *block;
+        }
+    }
+
+    /// Decrypts `blocks` in place, continuing from the previous call.
+    pub fn decrypt_blocks(&mut self, blocks: &mut [[u8; 16]]) {
+        // Each block decrypts independently; only the XOR needs the ciphertext before it.
+        for chunk in blocks.chunks_mut(8) {
+            let mut ciphertext = [[0u8; 16]; 8];
+            ciphertext[..chunk.len()].copy_from_slice(chunk);
+            self.aes.decrypt_blocks(chunk);
+            for (i, block) in chunk.iter_mut().enumerate() {
+                let previous = if i == 0 {
+                    &self.chain
+                } else {
+                    &ciphertext[i - 1]
+                };
+                xor_in_place(block, previous);
+            }
+            self.chain = ciphertext[chunk.len() - 1];
+        }
+    }
+
+    /// Encrypts a whole message, padding it with PKCS#7.
+    pub fn encrypt_padded(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let (blocks, rest) = plaintext.as_chunks::<16>();
+        let mut output = blocks.to_vec();
+        output.push(pkcs7_pad(rest));
+        self.encrypt_blocks(&mut output);
+        output.into_flattened()
+    }
+
+    /// Decrypts a whole message and removes its PKCS#7 padding.
+    pub fn decrypt_padded(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, ModeError> {
+        let (blocks, rest) = ciphertext.as_chunks::<16>();
+        if blocks.is_empty() || !rest.is_empty() {
+            return Err(ModeError::InvalidLength);
+        }
+        let mut output = blocks.to_vec();
+        self.decrypt_blocks(&mut output);
+        let unpadded = pkcs7_unpadded_len(&output[output.len() - 1])?;
+        let mut output = output.into_flattened();
+        output.truncate(output.len() - 16 + unpadded);
+        Ok(output)
+    }
+}
+
+/// Pads the last, incomplete block of a message to a full block.
+pub(crate) fn pkcs7_pad(rest: &[u8]) -> [u8; 16] {
+    let mut block = [(16 - rest.len()) as u8; 16];
+    block[..rest.len()].copy_from_slice(rest);
+    block
+}
+
+/// Returns how many bytes of the last block of a message are data, checking
+/// every padding byte without branching on them.
+pub(crate) fn pkcs7_unpadded_len(block: &[u8; 16]) -> Result<usize, ModeError> {
+    let padding = block[15];
+    let mut invalid = (padding == 0) as u8 | (padding > 16) as u8;
+    for (i, byte) in block.iter().enumerate() {
+        let in_padding = (i + padding as usize >= 16) as u8;
+        invalid |= in_padding & (*byte != padding) as u8;
+    }
+    if invalid != 0 {
+        return Err(ModeError::InvalidPadding);
+    }
+    Ok(16 - padding as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes256};
+    use hex_literal::hex;
+
+    const PLAINTEXT: [u8; 64] = hex!(
+        "6bc1bee22e409f96e93d7e117393172a"
+        "ae2d8a571e03ac9c9eb76fac45af8e51"
+        "30c81c46a35ce411e5fbc1191a0a52ef"
+        "f69f2445df4f9b17ad2b417be66c3710"
+    );
+    const IV: [u8; 16] = hex!("000102030405060708090a0b0c0d0e0f");
+
+    // NIST SP 800-38A F.2.1 and F.2.2.
+    #[test]
+    fn test_cbc_aes128() {
+        let aes = Aes128::new(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+        let expected = hex!(
+            "7649abac8119b246cee98e9b12e9197d"
+            "5086cb9b507219ee95db113a917678b2"
+            "73bed6b8e3c1743b7116e69e22229516"
+            "3ff1caa1681fac09120eca307586e1a7"
+        );
+        let mut blocks = *PLAINTEXT.as_chunks::<16>().0.first_chunk::<4>().unwrap();
+        Cbc::new(aes.clone(), &IV).encrypt_blocks(&mut blocks);
+        assert_eq!(*blocks.as_flattened(), expected);
+        Cbc::new(aes, &IV).decrypt_blocks(&mut blocks);
+        assert_eq!(*blocks.as_flattened(), PLAINTEXT);
+    }
+
+    // NIST SP 800-38A F.2.5 and F.2.6.
+    #[test]
+    fn test_cbc_aes256() {
+        let aes = Aes256::new(&hex!(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+        ));
+        let expected = hex!(
+            "f58c4c04d6e5f1ba779eabfb5f7bfbd6"
+            "9cfc4e967edb808d679f777bc6702c7d"
+            "39f23369a9d9bacfa530e26304231461"
+            "b2eb05e2c39be9fcda6c19078c6a9d1b"
+        );
+        let mut blocks = *PLAINTEXT.as_chunks::<16>().0.first_chunk::<4>().unwrap();
+        // Split across calls to check that the chain carries over.
+        let mut cbc = Cbc::new(aes.clone(), &IV);
+        cbc.encrypt_blocks(&mut blocks[..1]);
+        cbc.encrypt_blocks(&mut blocks[1..]);
+        assert_eq!(*blocks.as_flattened(), expected);
+        let mut cbc = Cbc::new(aes, &IV);
+        cbc.decrypt_blocks(&mut blocks[..3]);
+        cbc.decrypt_blocks(&mut blocks[3..]);
+        assert_eq!(*blocks.as_flattened(), PLAINTEXT);
+    }
+
+    #[test]
+    fn test_cbc_padded() {
+        let aes = Aes128::new(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+        let ciphertext = Cbc::new(aes.clone(), &IV).encrypt_padded(&PLAINTEXT[..20]);
+        assert_eq!(
+            ciphertext,
+            hex!("7649abac8119b246cee98e9b12e9197d2e013f890472d82217b17f45f6e7f539")
+        );
+        let decrypted = Cbc::new(aes.clone(), &IV).decrypt_padded(&ciphertext);
+        assert_eq!(decrypted.as_deref(), Ok(&PLAINTEXT[..20]));
+
+        // A whole number of blocks gains a full block of padding.
+        let ciphertext = Cbc::new(aes.clone(), &IV).encrypt_padded(&PLAINTEXT[..32]);
+        assert_eq!(ciphertext.len(), 48);
+        assert_eq!(ciphertext[32..], hex!("55e21d7100b988ffec32feeafaf23538"));
+        let decrypted = Cbc::new(aes.clone(), &IV).decrypt_padded(&ciphertext);
+        assert_eq!(decrypted.as_deref(), Ok(&PLAINTEXT[..32]));
+
+        let empty = Cbc::new(aes.clone(), &IV).encrypt_padded(&[]);
+        assert_eq!(Cbc::new(aes, &IV).decrypt_padded(&empty), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_cbc_padded_errors() {
+        let aes = Aes128::new(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+        let mut cbc = Cbc::new(aes.clone(), &IV);
+        assert_eq!(cbc.decrypt_padded(&[]), Err(ModeError::InvalidLength));
+        assert_eq!(cbc.decrypt_padded(&[0; 17]), Err(ModeError::InvalidLength));
+
+        // The raw SP 800-38A ciphertext decrypts to data that is not padded.
+        let ciphertext = hex!("7649abac8119b246cee98e9b12e9197d");
+        let decrypted = Cbc::new(aes, &IV).decrypt_padded(&ciphertext);
+        assert_eq!(decrypted, Err(ModeError::InvalidPadding));
+
+        assert_eq!(pkcs7_unpadded_len(&[16; 16]), Ok(0));
+        assert_eq!(pkcs7_unpadded_len(&[1; 16]), Ok(15));
+        assert!(pkcs7_unpadded_len(&[0; 16]).is_err());
+        assert!(pkcs7_unpadded_len(&[17; 16]).is_err());
+        let mut block = [3; 16];
+        block[13] = 2;
+        assert!(pkcs7_unpadded_len(&block).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use super::xor_in_place;
+use crate::Aes;
+
+/// Cipher feedback mode with 128-bit segments (CFB128). A message can be
+/// processed in pieces of any length; whole blocks of ciphertext decrypt in
+/// parallel.
+#[derive(Clone)]
+pub struct Cfb<const R: usize> {
+    aes: Aes<R>,
+    // Once all 16 bytes are used, this is the last ciphertext block (or the IV).
+    register: [u8; 16],
+    used: usize,
+}
+
+impl<const R: usize> Cfb<R> {
+    pub fn new(aes: Aes<R>, iv: &[u8; 16]) -> Self {
+        Self {
+            aes,
+            register: *iv,
+            used: 16,
+        }
+    }
+
+    pub fn encrypt(&mut self, data: &mut [u8]) {
+        for byte in data {
+            if self.used == 16 {
+                self.aes.encrypt_block(&mut self.register);
+                self.used = 0;
+            }
+            self.register[self.used] ^= *byte;
+            *byte = self.register[self.used];
+            self.used += 1;
+        }
+    }
+
+    pub fn decrypt(&mut self, data: &mut [u8]) {
+        let (head, rest) = data.split_at_mut((16 - self.used).min(data.len()));
+        self.decrypt_bytes(head);
+
+        // The keystream for each whole block is the encryption of the
+        // ciphertext block before it, all of which are already known.
+        let (blocks, tail) = rest.as_chunks_mut::<16>();
+        for chunk in blocks.chunks_mut(8) {
+            let mut keystream = [[0u8; 16]; 8];
+            let keystream = &mut keystream[..chunk.len()];
+            keystream[0] = self.register;
+            keystream[1..].copy_from_slice(&chunk[..chunk.len() - 1]);
+            self.register = chunk[chunk.len() - 1];
+            self.aes.encrypt_blocks(keystream);
+            for (block, keystream) in chunk.iter_mut().zip(keystream.iter()) {
+                xor_in_place(block, keystream);
+            }
+        }
+
+        self.decrypt_bytes(tail);
+    }
+
+    fn decrypt_bytes(&mut self, data: &mut [u8]) {
+        for byte in data {
+            if self.used == 16 {
+                self.aes.encrypt_block(&mut self.register);
+                self.used = 0;
+            }
+            let ciphertext = *byte;
+            *byte ^= self.register[self.used];
+            self.register[self.used] = ciphertext;
+            self.used += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes256};
+    use hex_literal::hex;
+
+    const PLAINTEXT: [u8; 64] = hex!(
+        "6bc1bee22e409f96e93d7e117393172a"
+        "ae2d8a571e03ac9c9eb76fac45af8e51"
+        "30c81c46a35ce411e5fbc1191a0a52ef"
+        "f69f2445df4f9b17ad2b417be66c3710"
+    );
+    const IV: [u8; 16] = hex!("000102030405060708090a0b0c0d0e0f");
+
+    // NIST SP 800-38A F.3.13 and F.3.14.
+    #[test]
+    fn test_cfb128_aes128() {
+        let aes = Aes128::new(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+        let expected = hex!(
+            "3b3fd92eb72dad20333449f8e83cfb4a"
+            "c8a64537a0b3a93fcde3cdad9f1ce58b"
+            "26751f67a3cbb140b1808cf187a4f4df"
+            "c04b05357c5d1c0eeac4c66f9ff7f2e6"
+        );
+        let mut data = PLAINTEXT;
+        Cfb::new(aes.clone(), &IV).encrypt(&mut data);
+        assert_eq!(data, expected);
+        Cfb::new(aes, &IV).decrypt(&mut data);
+        assert_eq!(data, PLAINTEXT);
+    }
+
+    // NIST SP 800-38A F.3.17 and F.3.18, processed in uneven pieces.
+    #[test]
+    fn test_cfb128_aes256_pieces() {
+        let aes = Aes256::new(&hex!(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+        ));
+        let expected = hex!(
+            "dc7e84bfda79164b7ecd8486985d3860"
+            "39ffed143b28b1c832113c6331e5407b"
+            "df10132415e54b92a13ed0a8267ae2f9"
+            "75a385741ab9cef82031623d55b1e471"
+        );
+        let pieces = [7, 7, 23, 48, 64];
+        let mut data = PLAINTEXT;
+        let mut cfb = Cfb::new(aes.clone(), &IV);
+        let mut start = 0;
+        for end in pieces {
+            cfb.encrypt(&mut data[start..end]);
+            start = end;
+        }
+        assert_eq!(data, expected);
+        let mut cfb = Cfb::new(aes, &IV);
+        let mut start = 0;
+        for end in pieces {
+            cfb.decrypt(&mut data[start..end]);
+            start = end;
+        }
+        assert_eq!(data, PLAINTEXT);
+    }
+
+    #[test]
+    fn test_cfb128_many_blocks() {
+        let aes = Aes128::new(&[9; 16]);
+        let plaintext: Vec<u8> = (0..16 * 19 + 5).map(|i| i as u8).collect();
+        let mut data = plaintext.clone();
+        Cfb::new(aes.clone(), &[1; 16]).encrypt(&mut data);
+        Cfb::new(aes, &[1; 16]).decrypt(&mut data);
+        assert_eq!(data, plaintext);
+    }
+}
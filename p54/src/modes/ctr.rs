@@ -0,0 +1,143 @@
+use super::{xor_in_place, KEYSTREAM_BLOCKS};
+use crate::Aes;
+
+/// Counter mode, with the whole 128-bit block as a big-endian counter. It
+/// is a stream cipher: the same call encrypts and decrypts, and a message can
+/// be processed in pieces of any length.
+#[derive(Clone)]
+pub struct Ctr<const R: usize> {
+    aes: Aes<R>,
+    counter: u128,
+    keystream: [u8; 16],
+    used: usize,
+}
+
+impl<const R: usize> Ctr<R> {
+    pub fn new(aes: Aes<R>, initial_counter: &[u8; 16]) -> Self {
+        Self {
+            aes,
+            counter: u128::from_be_bytes(*initial_counter),
+            keystream: [0; 16],
+            used: 16,
+        }
+    }
+
+    /// Returns the current counter block and advances the counter.
+    fn next_counter(&mut self) -> [u8; 16] {
+        let counter = self.counter.to_be_bytes();
+        self.counter = self.counter.wrapping_add(1);
+        counter
+    }
+
+    /// XORs `data` with the next `data.len()` bytes of keystream.
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        // Use up the rest of the last keystream block first.
+        let (head, rest) = data.split_at_mut((16 - self.used).min(data.len()));
+        xor_in_place(head, &self.keystream[self.used..]);
+        self.used += head.len();
+
+        let (blocks, tail) = rest.as_chunks_mut::<16>();
+        for chunk in blocks.chunks_mut(KEYSTREAM_BLOCKS) {
+            let mut keystream = [[0u8; 16]; KEYSTREAM_BLOCKS];
+            let keystream = &mut keystream[..chunk.len()];
+            for block in keystream.iter_mut() {
+                *block = self.next_counter();
+            }
+            self.aes.encrypt_blocks(keystream);
+            // Whole blocks are XORed as `u128`s rather than byte by byte.
+            for (block, keystream) in chunk.iter_mut().zip(keystream.iter()) {
+                *block =
+                    (u128::from_ne_bytes(*block) ^ u128::from_ne_bytes(*keystream)).to_ne_bytes();
+            }
+        }
+
+        if !tail.is_empty() {
+            self.keystream = self.next_counter();
+            self.aes.encrypt_block(&mut self.keystream);
+            xor_in_place(tail, &self.keystream);
+            self.used = tail.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes256};
+    use hex_literal::hex;
+
+    const PLAINTEXT: [u8; 64] = hex!(
+        "6bc1bee22e409f96e93d7e117393172a"
+        "ae2d8a571e03ac9c9eb76fac45af8e51"
+        "30c81c46a35ce411e5fbc1191a0a52ef"
+        "f69f2445df4f9b17ad2b417be66c3710"
+    );
+    const COUNTER: [u8; 16] = hex!("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+
+    // NIST SP 800-38A F.5.1 and F.5.2.
+    #[test]
+    fn test_ctr_aes128() {
+        let aes = Aes128::new(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+        let expected = hex!(
+            "874d6191b620e3261bef6864990db6ce"
+            "9806f66b7970fdff8617187bb9fffdff"
+            "5ae4df3edbd5d35e5b4f09020db03eab"
+            "1e031dda2fbe03d1792170a0f3009cee"
+        );
+        let mut data = PLAINTEXT;
+        Ctr::new(aes.clone(), &COUNTER).apply_keystream(&mut data);
+        assert_eq!(data, expected);
+        Ctr::new(aes, &COUNTER).apply_keystream(&mut data);
+        assert_eq!(data, PLAINTEXT);
+    }
+
+    // NIST SP 800-38A F.5.5, processed in uneven pieces.
+    #[test]
+    fn test_ctr_aes256_pieces() {
+        let aes = Aes256::new(&hex!(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+        ));
+        let expected = hex!(
+            "601ec313775789a5b7a7f504bbf3d228"
+            "f443e3ca4d62b59aca84e990cacaf5c5"
+            "2b0930daa23de94ce87017ba2d84988d"
+            "dfc9c58db67aada613c2dd08457941a6"
+        );
+        let mut data = PLAINTEXT;
+        let mut ctr = Ctr::new(aes, &COUNTER);
+        let mut start = 0;
+        for end in [3, 3, 16, 33, 34, 64] {
+            ctr.apply_keystream(&mut data[start..end]);
+            start = end;
+        }
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_ctr_many_blocks() {
+        let aes = Aes128::new(&[5; 16]);
+        let mut data = vec![0u8; 16 * 19 + 5];
+        Ctr::new(aes.clone(), &[0xff; 16]).apply_keystream(&mut data);
+        // The counter wraps around from all ones to zero.
+        let mut expected = [[0xff; 16], [0; 16]];
+        for (i, counter) in expected.iter_mut().enumerate() {
+            aes.encrypt_block(counter);
+            assert_eq!(data[16 * i..16 * (i + 1)], *counter);
+        }
+        let mut block = [0; 16];
+        block[15] = 18;
+        aes.encrypt_block(&mut block);
+        assert_eq!(data[16 * 19..], block[..5]);
+    }
+
+    // Several full batches, so that the widest kernel available runs.
+    #[test]
+    fn test_ctr_matches_soft() {
+        let key = [0x3c; 32];
+        let mut data: Vec<u8> = (0..16 * 75 + 9).map(|i| i as u8).collect();
+        let mut soft_data = data.clone();
+        Ctr::new(Aes256::new(&key), &[0xfe; 16]).apply_keystream(&mut data);
+        Ctr::new(Aes256::new_soft(&key), &[0xfe; 16]).apply_keystream(&mut soft_data);
+        assert_eq!(data, soft_data);
+    }
+}
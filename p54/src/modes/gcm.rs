@@ -0,0 +1,320 @@
+use super::ghash::GHash;
+use super::{xor_in_place, ModeError, KEYSTREAM_BLOCKS};
+use crate::{secret, Aes};
+
+/// Galois/counter mode authenticated encryption (NIST SP 800-38D). The
+/// data is encrypted in place, a batch of counter blocks at a time, and the tag
+/// authenticates both it and the associated data.
+#[derive(Clone)]
+pub struct Gcm<const R: usize> {
+    aes: Aes<R>,
+    ghash: GHash,
+}
+
+pub type Aes128Gcm = Gcm<11>;
+pub type Aes256Gcm = Gcm<15>;
+
+/// The most data one message can hold, `2^39 - 256` bits.
+const MAX_DATA_LEN: u64 = (1 << 36) - 32;
+
+impl<const R: usize> Gcm<R> {
+    pub fn new(aes: Aes<R>) -> Self {
+        let mut h = [0u8; 16];
+        aes.encrypt_block(&mut h);
+        let ghash = GHash::new(&h);
+        secret::wipe(&mut h);
+        Self { aes, ghash }
+    }
+
+    /// Encrypts `data` in place and returns the tag. The nonce is usually
+    /// 12 bytes; other lengths are hashed into the initial counter.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        data: &mut [u8],
+    ) -> Result<[u8; 16], ModeError> {
+        let counter = self.initial_counter(nonce, data.len())?;
+        self.apply_keystream(&counter, data);
+        Ok(self.tag(&counter, aad, data))
+    }
+
+    /// Checks `tag` and decrypts `data` in place. Nothing is decrypted if
+    /// the tag does not match.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        data: &mut [u8],
+        tag: &[u8; 16],
+    ) -> Result<(), ModeError> {
+        let counter = self.initial_counter(nonce, data.len())?;
+        let expected = self.tag(&counter, aad, data);
+        let difference = expected
+            .iter()
+            .zip(tag)
+            .fold(0, |acc, (a, b)| acc | (a ^ b));
+        if difference != 0 {
+            return Err(ModeError::InvalidTag);
+        }
+        self.apply_keystream(&counter, data);
+        Ok(())
+    }
+
+    /// Returns the pre-counter block `J0` for `nonce`.
+    fn initial_counter(&self, nonce: &[u8], data_len: usize) -> Result<[u8; 16], ModeError> {
+        if nonce.is_empty() || data_len as u64 > MAX_DATA_LEN {
+            return Err(ModeError::InvalidLength);
+        }
+        let mut counter = [0u8; 16];
+        if nonce.len() == 12 {
+            counter[..12].copy_from_slice(nonce);
+            counter[15] = 1;
+        } else {
+            self.ghash.update_padded(&mut counter, nonce);
+            let lengths = ((nonce.len() as u128) * 8).to_be_bytes();
+            self.ghash.update_blocks(&mut counter, &[lengths]);
+        }
+        Ok(counter)
+    }
+
+    /// XORs `data` with the keystream, which starts from the block after `J0`.
+    fn apply_keystream(&self, initial: &[u8; 16], data: &mut [u8]) {
+        let mut counter = u32::from_be_bytes(initial[12..].try_into().unwrap());
+        for chunk in data.chunks_mut(16 * KEYSTREAM_BLOCKS) {
+            let mut keystream = [*initial; KEYSTREAM_BLOCKS];
+            let keystream = &mut keystream[..chunk.len().div_ceil(16)];
+            for block in keystream.iter_mut() {
+                // Only the low 32 bits count, wrapping around (inc32).
+                counter = counter.wrapping_add(1);
+                block[12..].copy_from_slice(&counter.to_be_bytes());
+            }
+            self.aes.encrypt_blocks(keystream);
+            xor_in_place(chunk, keystream.as_flattened());
+        }
+    }
+
+    fn tag(&self, initial: &[u8; 16], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+        let mut state = [0u8; 16];
+        self.ghash.update_padded(&mut state, aad);
+        self.ghash.update_padded(&mut state, ciphertext);
+        let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+        self.ghash
+            .update_blocks(&mut state, &[lengths.to_be_bytes()]);
+        let mut mask = *initial;
+        self.aes.encrypt_block(&mut mask);
+        xor_in_place(&mut state, &mask);
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes256};
+    use hex_literal::hex;
+
+    const KEY: [u8; 16] = hex!("feffe9928665731c6d6a8f9467308308");
+    const PLAINTEXT: [u8; 64] = hex!(
+        "d9313225f88406e5a55909c5aff5269a"
+        "86a7a9531534f7da2e4c303d8a318a72"
+        "1c3c0c95956809532fcf0e2449a6b525"
+        "b16aedf5aa0de657ba637b391aafd255"
+    );
+    const AAD: [u8; 20] = hex!("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+    const NONCE: [u8; 12] = hex!("cafebabefacedbaddecaf888");
+
+    fn check<const R: usize>(
+        gcm: &Gcm<R>,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+        ciphertext: &[u8],
+        tag: [u8; 16],
+    ) {
+        let mut data = plaintext.to_vec();
+        assert_eq!(gcm.encrypt(nonce, aad, &mut data), Ok(tag));
+        assert_eq!(data, ciphertext);
+        assert_eq!(gcm.decrypt(nonce, aad, &mut data, &tag), Ok(()));
+        assert_eq!(data, plaintext);
+    }
+
+    // The GCM specification's test cases 1 to 6.
+    #[test]
+    fn test_gcm_aes128() {
+        let gcm = Aes128Gcm::new(Aes128::new(&[0; 16]));
+        check(
+            &gcm,
+            &[0; 12],
+            &[],
+            &[],
+            &[],
+            hex!("58e2fccefa7e3061367f1d57a4e7455a"),
+        );
+        check(
+            &gcm,
+            &[0; 12],
+            &[],
+            &[0; 16],
+            &hex!("0388dace60b6a392f328c2b971b2fe78"),
+            hex!("ab6e47d42cec13bdf53a67b21257bddf"),
+        );
+
+        let gcm = Aes128Gcm::new(Aes128::new(&KEY));
+        let ciphertext = hex!(
+            "42831ec2217774244b7221b784d0d49c"
+            "e3aa212f2c02a4e035c17e2329aca12e"
+            "21d514b25466931c7d8f6a5aac84aa05"
+            "1ba30b396a0aac973d58e091473f5985"
+        );
+        check(
+            &gcm,
+            &NONCE,
+            &[],
+            &PLAINTEXT,
+            &ciphertext,
+            hex!("4d5c2af327cd64a62cf35abd2ba6fab4"),
+        );
+        check(
+            &gcm,
+            &NONCE,
+            &AAD,
+            &PLAINTEXT[..60],
+            &ciphertext[..60],
+            hex!("5bc94fbc3221a5db94fae95ae7121a47"),
+        );
+        check(
+            &gcm,
+            &NONCE[..8],
+            &AAD,
+            &PLAINTEXT[..60],
+            &hex!(
+                "61353b4c2806934a777ff51fa22a4755"
+                "699b2a714fcdc6f83766e5f97b6c7423"
+                "73806900e49f24b22b097544d4896b42"
+                "4989b5e1ebac0f07c23f4598"
+            ),
+            hex!("3612d2e79e3b0785561be14aaca2fccb"),
+        );
+        check(
+            &gcm,
+            &hex!(
+                "9313225df88406e555909c5aff5269aa"
+                "6a7a9538534f7da1e4c303d2a318a728"
+                "c3c0c95156809539fcf0e2429a6b5254"
+                "16aedbf5a0de6a57a637b39b"
+            ),
+            &AAD,
+            &PLAINTEXT[..60],
+            &hex!(
+                "8ce24998625615b603a033aca13fb894"
+                "be9112a5c3a211a8ba262a3cca7e2ca7"
+                "01e4a9a4fba43c90ccdcb281d48c7c6f"
+                "d62875d2aca417034c34aee5"
+            ),
+            hex!("619cc5aefffe0bfa462af43c1699d050"),
+        );
+    }
+
+    // The GCM specification's test cases 13 to 16.
+    #[test]
+    fn test_gcm_aes256() {
+        let gcm = Aes256Gcm::new(Aes256::new(&[0; 32]));
+        check(
+            &gcm,
+            &[0; 12],
+            &[],
+            &[],
+            &[],
+            hex!("530f8afbc74536b9a963b4f1c4cb738b"),
+        );
+        check(
+            &gcm,
+            &[0; 12],
+            &[],
+            &[0; 16],
+            &hex!("cea7403d4d606b6e074ec5d3baf39d18"),
+            hex!("d0d1c8a799996bf0265b98b5d48ab919"),
+        );
+
+        let gcm = Aes256Gcm::new(Aes256::new(&hex!(
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308"
+        )));
+        let ciphertext = hex!(
+            "522dc1f099567d07f47f37a32a84427d"
+            "643a8cdcbfe5c0c97598a2bd2555d1aa"
+            "8cb08e48590dbb3da7b08b1056828838"
+            "c5f61e6393ba7a0abcc9f662898015ad"
+        );
+        check(
+            &gcm,
+            &NONCE,
+            &[],
+            &PLAINTEXT,
+            &ciphertext,
+            hex!("b094dac5d93471bdec1a502270e3cc6c"),
+        );
+        check(
+            &gcm,
+            &NONCE,
+            &AAD,
+            &PLAINTEXT[..60],
+            &ciphertext[..60],
+            hex!("76fc6ece0f4e1768cddf8853bb2d551b"),
+        );
+    }
+
+    // Long enough for several eight-block steps of both GHASH and CTR, with
+    // partial blocks at the end; the tag is from OpenSSL.
+    #[test]
+    fn test_gcm_long_message() {
+        let key: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let nonce: [u8; 12] = std::array::from_fn(|i| i as u8);
+        let plaintext: Vec<u8> = (0..16 * 19 + 7).map(|i| (i % 251) as u8).collect();
+        let aad: Vec<u8> = (0..16 * 9 + 3).map(|i| (i % 7) as u8).collect();
+        let tag = hex!("35454789ea82887f95b3f47d30a64fee");
+
+        let mut data = plaintext.clone();
+        let gcm = Aes128Gcm::new(Aes128::new(&key));
+        assert_eq!(gcm.encrypt(&nonce, &aad, &mut data), Ok(tag));
+        // Decrypt with the portable AES and GHASH.
+        let aes = Aes128::new_soft(&key);
+        let mut h = [0u8; 16];
+        aes.encrypt_block(&mut h);
+        let soft = Aes128Gcm {
+            aes,
+            ghash: GHash::new_soft(&h),
+        };
+        assert_eq!(soft.decrypt(&nonce, &aad, &mut data, &tag), Ok(()));
+        assert_eq!(data, plaintext);
+    }
+
+    #[test]
+    fn test_gcm_rejects_forgeries() {
+        let gcm = Aes128Gcm::new(Aes128::new(&KEY));
+        let mut data = PLAINTEXT;
+        let tag = gcm.encrypt(&NONCE, &AAD, &mut data).unwrap();
+        let ciphertext = data;
+
+        let mut bad_tag = tag;
+        bad_tag[15] ^= 1;
+        assert_eq!(
+            gcm.decrypt(&NONCE, &AAD, &mut data, &bad_tag),
+            Err(ModeError::InvalidTag)
+        );
+        assert_eq!(data, ciphertext);
+        assert_eq!(
+            gcm.decrypt(&NONCE, &AAD[1..], &mut data, &tag),
+            Err(ModeError::InvalidTag)
+        );
+        data[0] ^= 1;
+        assert_eq!(
+            gcm.decrypt(&NONCE, &AAD, &mut data, &tag),
+            Err(ModeError::InvalidTag)
+        );
+        assert_eq!(
+            gcm.encrypt(&[], &AAD, &mut data),
+            Err(ModeError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use super::ModeError;
+use crate::secret::{self, SecretBytes};
+use crate::Aes;
+
+/// The initial value of RFC 3394 key wrap.
+const IV: [u8; 8] = [0xa6; 8];
+/// The first half of the alternative initial value of RFC 5649.
+const AIV_PREFIX: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+/// AES key wrap (RFC 3394, NIST SP 800-38F KW) and key wrap with padding
+/// (RFC 5649, KWP), for storing keys encrypted under a key-encryption key.
+/// Unwrapping checks the integrity value and fails with
+/// `ModeError::InvalidTag` if the wrapped key was altered or the
+/// key-encryption key is wrong. Unwrapped keys come back in `SecretBytes`,
+/// and a candidate key that fails the check is wiped before returning.
+#[derive(Clone)]
+pub struct KeyWrap<const R: usize> {
+    aes: Aes<R>,
+}
+
+impl<const R: usize> KeyWrap<R> {
+    pub fn new(aes: Aes<R>) -> Self {
+        Self { aes }
+    }
+
+    /// Wraps `key`, which must be a multiple of 8 bytes and at least 16.
+    pub fn wrap(&self, key: &[u8]) -> Result<Vec<u8>, ModeError> {
+        let (semiblocks, rest) = key.as_chunks::<8>();
+        if semiblocks.len() < 2 || !rest.is_empty() {
+            return Err(ModeError::InvalidLength);
+        }
+        Ok(self.wrap_semiblocks(IV, semiblocks.to_vec()))
+    }
+
+    /// Unwraps a key from `wrap`.
+    pub fn unwrap(&self, wrapped: &[u8]) -> Result<SecretBytes, ModeError> {
+        let (semiblocks, rest) = wrapped.as_chunks::<8>();
+        if semiblocks.len() < 3 || !rest.is_empty() {
+            return Err(ModeError::InvalidLength);
+        }
+        let (integrity, key) = self.unwrap_semiblocks(semiblocks);
+        if !constant_time_eq(&integrity, &IV) {
+            return Err(ModeError::InvalidTag);
+        }
+        Ok(key)
+    }
+
+    /// Wraps `key` of any length from 1 byte to 4 GiB, padding it with zeros.
+    pub fn wrap_padded(&self, key: &[u8]) -> Result<Vec<u8>, ModeError> {
+        let len = u32::try_from(key.len()).map_err(|_| ModeError::InvalidLength)?;
+        if len == 0 {
+            return Err(ModeError::InvalidLength);
+        }
+        let mut integrity = [0u8; 8];
+        integrity[..4].copy_from_slice(&AIV_PREFIX);
+        integrity[4..].copy_from_slice(&len.to_be_bytes());
+        let mut semiblocks = vec![[0u8; 8]; key.len().div_ceil(8)];
+        semiblocks.as_flattened_mut()[..key.len()].copy_from_slice(key);
+
+        if let [semiblock] = semiblocks[..] {
+            // A single semiblock is encrypted as one block with the integrity value.
+            let mut block = [0u8; 16];
+            block[..8].copy_from_slice(&integrity);
+            block[8..].copy_from_slice(&semiblock);
+            self.aes.encrypt_block(&mut block);
+            return Ok(block.to_vec());
+        }
+        Ok(self.wrap_semiblocks(integrity, semiblocks))
+    }
+
+    /// Unwraps a key from `wrap_padded` and removes its padding.
+    pub fn unwrap_padded(&self, wrapped: &[u8]) -> Result<SecretBytes, ModeError> {
+        let (semiblocks, rest) = wrapped.as_chunks::<8>();
+        if semiblocks.len() < 2 || !rest.is_empty() {
+            return Err(ModeError::InvalidLength);
+        }
+        let (integrity, mut key) = if let Ok(block) = <&[u8; 16]>::try_from(wrapped) {
+            let mut block = *block;
+            self.aes.decrypt_block(&mut block);
+            let integrity = *block.first_chunk::<8>().unwrap();
+            let key = SecretBytes::from(block[8..].to_vec());
+            secret::wipe(&mut block);
+            (integrity, key)
+        } else {
+            self.unwrap_semiblocks(semiblocks)
+        };
+
+        // Check the prefix, that the length fits the padded key, and that
+        // the padding is zero, without branching on any of them separately.
+        let len = u32::from_be_bytes(integrity[4..].try_into().unwrap()) as usize;
+        let mut invalid = !constant_time_eq(&integrity[..4], &AIV_PREFIX) as u8;
+        invalid |= (len > key.expose().len()) as u8 | (len + 8 <= key.expose().len()) as u8;
+        for (i, byte) in key.expose().iter().enumerate() {
+            invalid |= (i >= len) as u8 & (*byte != 0) as u8;
+        }
+        if invalid != 0 {
+            return Err(ModeError::InvalidTag);
+        }
+        key.truncate(len);
+        Ok(key)
+    }
+
+    /// The wrapping function W of RFC 3394 section 2.2.1, in its indexed
+    /// form: six passes over the semiblocks, each encrypting one block per
+    /// semiblock.
+    fn wrap_semiblocks(&self, mut integrity: [u8; 8], mut semiblocks: Vec<[u8; 8]>) -> Vec<u8> {
+        let n = semiblocks.len() as u64;
+        for j in 0..6 {
+            for (i, semiblock) in semiblocks.iter_mut().enumerate() {
+                let mut block = [0u8; 16];
+                block[..8].copy_from_slice(&integrity);
+                block[8..].copy_from_slice(semiblock);
+                self.aes.encrypt_block(&mut block);
+                let t = n * j + i as u64 + 1;
+                integrity = (u64::from_be_bytes(block[..8].try_into().unwrap()) ^ t).to_be_bytes();
+                semiblock.copy_from_slice(&block[8..]);
+            }
+        }
+        let mut output = integrity.to_vec();
+        output.extend_from_slice(semiblocks.as_flattened());
+        output
+    }
+
+    /// The unwrapping function W^-1 of RFC 3394 section 2.2.2. Returns the
+    /// recovered integrity value and key, for the caller to check.
+    fn unwrap_semiblocks(&self, wrapped: &[[u8; 8]]) -> ([u8; 8], SecretBytes) {
+        let mut integrity = wrapped[0];
+        let mut semiblocks = wrapped[1..].to_vec();
+        let n = semiblocks.len() as u64;
+        for j in (0..6).rev() {
+            for (i, semiblock) in semiblocks.iter_mut().enumerate().rev() {
+                let t = n * j + i as u64 + 1;
+                let mut block = [0u8; 16];
+                block[..8].copy_from_slice(&(u64::from_be_bytes(integrity) ^ t).to_be_bytes());
+                block[8..].copy_from_slice(semiblock);
+                self.aes.decrypt_block(&mut block);
+                integrity.copy_from_slice(&block[..8]);
+                semiblock.copy_from_slice(&block[8..]);
+                secret::wipe(&mut block);
+            }
+        }
+        (integrity, semiblocks.into_flattened().into())
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes192, Aes256};
+    use hex_literal::hex;
+
+    const KEY_DATA: [u8; 32] =
+        hex!("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F");
+    const KEK: [u8; 32] = hex!("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F");
+
+    fn check<const R: usize>(kw: &KeyWrap<R>, key: &[u8], wrapped: &[u8]) {
+        assert_eq!(kw.wrap(key).unwrap(), wrapped);
+        assert_eq!(kw.unwrap(wrapped).unwrap().expose(), key);
+    }
+
+    // RFC 3394 section 4.
+    #[test]
+    fn test_key_wrap_rfc3394() {
+        let kw = KeyWrap::new(Aes128::new(KEK.first_chunk::<16>().unwrap()));
+        check(
+            &kw,
+            &KEY_DATA[..16],
+            &hex!("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5"),
+        );
+
+        let kw = KeyWrap::new(Aes192::new(KEK.first_chunk::<24>().unwrap()));
+        check(
+            &kw,
+            &KEY_DATA[..16],
+            &hex!("96778B25AE6CA435F92B5B97C050AED2468AB8A17AD84E5D"),
+        );
+        check(
+            &kw,
+            &KEY_DATA[..24],
+            &hex!("031D33264E15D33268F24EC260743EDCE1C6C7DDEE725A936BA814915C6762D2"),
+        );
+
+        let kw = KeyWrap::new(Aes256::new(&KEK));
+        check(
+            &kw,
+            &KEY_DATA[..16],
+            &hex!("64E8C3F9CE0F5BA263E9777905818A2A93C8191E7D6E8AE7"),
+        );
+        check(
+            &kw,
+            &KEY_DATA[..24],
+            &hex!("A8F9BC1612C68B3FF6E6F4FBE30E71E4769C8B80A32CB8958CD5D17D6B254DA1"),
+        );
+        check(
+            &kw,
+            &KEY_DATA,
+            &hex!(
+                "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326"
+                "CBC7F0E71A99F43BFB988B9B7A02DD21"
+            ),
+        );
+    }
+
+    // RFC 5649 section 6.
+    #[test]
+    fn test_key_wrap_padded_rfc5649() {
+        let kw = KeyWrap::new(Aes192::new(&hex!(
+            "5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8"
+        )));
+        let key = hex!("c37b7e6492584340bed12207808941155068f738");
+        let wrapped = hex!("138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a");
+        assert_eq!(kw.wrap_padded(&key).unwrap(), wrapped);
+        assert_eq!(kw.unwrap_padded(&wrapped).unwrap().expose(), key);
+
+        let key = hex!("466f7250617369");
+        let wrapped = hex!("afbeb0f07dfbf5419200f2ccb50bb24f");
+        assert_eq!(kw.wrap_padded(&key).unwrap(), wrapped);
+        assert_eq!(kw.unwrap_padded(&wrapped).unwrap().expose(), key);
+    }
+
+    // Lengths around the semiblock boundaries; the wrapped keys are from
+    // OpenSSL through Python's `cryptography`.
+    #[test]
+    fn test_key_wrap_padded_lengths() {
+        let kw = KeyWrap::new(Aes128::new(&hex!("000102030405060708090a0b0c0d0e0f")));
+        for (len, wrapped) in [
+            (1, &hex!("46e154a0b1453ab4f60d492271bb8480")[..]),
+            (8, &hex!("2c50cb417b48d13bce783237ce818343")),
+            (9, &hex!("de35d957ebf1225c7db39b3bef7f1de65a0fb7700e438e88")),
+            (
+                31,
+                &hex!(
+                    "cbc704b864b3eb5c3718b024ca4f7fa12a0913fe7d809fd8"
+                    "b22d444a5995d5eec3f1edbe8173f5b6"
+                ),
+            ),
+        ] {
+            let key: Vec<u8> = (100..100 + len).collect();
+            assert_eq!(kw.wrap_padded(&key).unwrap(), wrapped);
+            assert_eq!(kw.unwrap_padded(wrapped).unwrap().expose(), key);
+        }
+    }
+
+    #[test]
+    fn test_key_wrap_rejects_tampering() {
+        let kw = KeyWrap::new(Aes256::new(&KEK));
+        let mut wrapped = kw.wrap(&KEY_DATA).unwrap();
+        wrapped[9] ^= 1;
+        assert_eq!(kw.unwrap(&wrapped).err(), Some(ModeError::InvalidTag));
+        let other = KeyWrap::new(Aes256::new(&[0; 32]));
+        assert_eq!(
+            other.unwrap(&kw.wrap(&KEY_DATA).unwrap()).err(),
+            Some(ModeError::InvalidTag)
+        );
+        // A padded wrapping is not a valid plain one, nor the other way round.
+        assert_eq!(
+            kw.unwrap(&kw.wrap_padded(&KEY_DATA).unwrap()).err(),
+            Some(ModeError::InvalidTag)
+        );
+        assert_eq!(
+            kw.unwrap_padded(&kw.wrap(&KEY_DATA).unwrap()).err(),
+            Some(ModeError::InvalidTag)
+        );
+
+        for len in [1, 8, 13] {
+            let mut wrapped = kw.wrap_padded(&KEY_DATA[..len]).unwrap();
+            let last = wrapped.len() - 1;
+            wrapped[last] ^= 0x80;
+            assert_eq!(
+                kw.unwrap_padded(&wrapped).err(),
+                Some(ModeError::InvalidTag)
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_wrap_lengths() {
+        let kw = KeyWrap::new(Aes128::new(&[0; 16]));
+        assert_eq!(kw.wrap(&[0; 8]), Err(ModeError::InvalidLength));
+        assert_eq!(kw.wrap(&[0; 20]), Err(ModeError::InvalidLength));
+        assert_eq!(kw.unwrap(&[0; 16]).err(), Some(ModeError::InvalidLength));
+        assert_eq!(kw.unwrap(&[0; 25]).err(), Some(ModeError::InvalidLength));
+        assert_eq!(kw.wrap_padded(&[]), Err(ModeError::InvalidLength));
+        assert_eq!(
+            kw.unwrap_padded(&[0; 8]).err(),
+            Some(ModeError::InvalidLength)
+        );
+        assert_eq!(
+            kw.unwrap_padded(&[0; 20]).err(),
+            Some(ModeError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use super::xor_in_place;
+use crate::Aes;
+
+/// Output feedback mode. Like CTR it is a stream cipher, but each keystream
+/// block is the encryption of the previous one, so it cannot be parallelised.
+#[derive(Clone)]
+pub struct Ofb<const R: usize> {
+    aes: Aes<R>,
+    register: [u8; 16],
+    used: usize,
+}
+
+impl<const R: usize> Ofb<R> {
+    pub fn new(aes: Aes<R>, iv: &[u8; 16]) -> Self {
+        Self {
+            aes,
+            register: *iv,
+            used: 16,
+        }
+    }
+
+    /// XORs `data` with the next `data.len()` bytes of keystream.
+    pub fn apply_keystream(&mut self, mut data: &mut [u8]) {
+        while !data.is_empty() {
+            if self.used == 16 {
+                self.aes.encrypt_block(&mut self.register);
+                self.used = 0;
+            }
+            let len = (16 - self.used).min(data.len());
+            let (head, rest) = data.split_at_mut(len);
+            xor_in_place(head, &self.register[self.used..]);
+            self.used += len;
+            data = rest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes256};
+    use hex_literal::hex;
+
+    const PLAINTEXT: [u8; 64] = hex!(
+        "6bc1bee22e409f96e93d7e117393172a"
+        "ae2d8a571e03ac9c9eb76fac45af8e51"
+        "30c81c46a35ce411e5fbc1191a0a52ef"
+        "f69f2445df4f9b17ad2b417be66c3710"
+    );
+    const IV: [u8; 16] = hex!("000102030405060708090a0b0c0d0e0f");
+
+    // NIST SP 800-38A F.4.1 and F.4.2.
+    #[test]
+    fn test_ofb_aes128() {
+        let aes = Aes128::new(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+        let expected = hex!(
+            "3b3fd92eb72dad20333449f8e83cfb4a"
+            "7789508d16918f03f53c52dac54ed825"
+            "9740051e9c5fecf64344f7a82260edcc"
+            "304c6528f659c77866a510d9c1d6ae5e"
+        );
+        let mut data = PLAINTEXT;
+        Ofb::new(aes.clone(), &IV).apply_keystream(&mut data);
+        assert_eq!(data, expected);
+        Ofb::new(aes, &IV).apply_keystream(&mut data);
+        assert_eq!(data, PLAINTEXT);
+    }
+
+    // NIST SP 800-38A F.4.5, processed in uneven pieces.
+    #[test]
+    fn test_ofb_aes256_pieces() {
+        let aes = Aes256::new(&hex!(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+        ));
+        let expected = hex!(
+            "dc7e84bfda79164b7ecd8486985d3860"
+            "4febdc6740d20b3ac88f6ad82a4fb08d"
+            "71ab47a086e86eedf39d1c5bba97c408"
+            "0126141d67f37be8538f5a8be740e484"
+        );
+        let mut data = PLAINTEXT;
+        let mut ofb = Ofb::new(aes, &IV);
+        let mut start = 0;
+        for end in [5, 16, 16, 40, 64] {
+            ofb.apply_keystream(&mut data[start..end]);
+            start = end;
+        }
+        assert_eq!(data, expected);
+    }
+}
@@ -0,0 +1,410 @@
+//! `std::io` adapters that encrypt or decrypt a stream as it passes through.
+//!
+//! A `Transform` turns input into output a piece at a time, holding back
+//! what it cannot process yet: CBC keeps a partial block, and CBC decryption
+//! also keeps the last whole block, whose padding is only known at the end.
+//! `CipherWriter` and `CipherReader` drive a transform from `Write` and
+//! `Read`, buffering at most `BUFFER_LEN` bytes of input at a time.
+//!
+//! The CBC transforms run over any `CbcChain`, so CBC built on a block cipher
+//! other than `Aes` can reuse the buffering and padding here.
+
+use super::cbc::{pkcs7_pad, pkcs7_unpadded_len};
+use super::{Cbc, Ctr, ModeError};
+use std::io::{self, Read, Write};
+
+/// How much input the adapters process per call.
+const BUFFER_LEN: usize = 8192;
+
+/// A mode that processes a stream in pieces of any length.
+pub trait Transform {
+    /// Processes `input`, appending the output that is ready to `output`.
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>);
+
+    /// Appends the output held back for the end of the stream. It is called
+    /// once, after the last `update`.
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), ModeError>;
+}
+
+/// CTR needs no buffering, and encrypts and decrypts alike.
+impl<const R: usize> Transform for Ctr<R> {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        let start = output.len();
+        output.extend_from_slice(input);
+        self.apply_keystream(&mut output[start..]);
+    }
+
+    fn finish(&mut self, _output: &mut Vec<u8>) -> Result<(), ModeError> {
+        Ok(())
+    }
+}
+
+/// Moves the whole blocks of `pending` followed by `input` to `output`,
+/// leaving the bytes after them in `pending`.
+fn take_blocks(pending: &mut Vec<u8>, mut input: &[u8], output: &mut Vec<u8>) {
+    if !pending.is_empty() {
+        let (head, rest) = input.split_at((16 - pending.len()).min(input.len()));
+        pending.extend_from_slice(head);
+        input = rest;
+        if pending.len() < 16 {
+            return;
+        }
+        output.append(pending);
+    }
+    let (blocks, rest) = input.as_chunks::<16>();
+    output.extend_from_slice(blocks.as_flattened());
+    pending.extend_from_slice(rest);
+}
+
+/// CBC over whole blocks, carrying the chaining value from call to call.
+pub trait CbcChain {
+    fn encrypt_blocks(&mut self, blocks: &mut [[u8; 16]]);
+    fn decrypt_blocks(&mut self, blocks: &mut [[u8; 16]]);
+}
+
+impl<const R: usize> CbcChain for Cbc<R> {
+    fn encrypt_blocks(&mut self, blocks: &mut [[u8; 16]]) {
+        Cbc::encrypt_blocks(self, blocks);
+    }
+
+    fn decrypt_blocks(&mut self, blocks: &mut [[u8; 16]]) {
+        Cbc::decrypt_blocks(self, blocks);
+    }
+}
+
+/// CBC encryption of a stream, padded with PKCS#7 at the end.
+pub struct CbcEncryptor<C: CbcChain> {
+    cbc: C,
+    pending: Vec<u8>,
+}
+
+impl<C: CbcChain> CbcEncryptor<C> {
+    pub fn new(cbc: C) -> Self {
+        Self {
+            cbc,
+            pending: Vec::with_capacity(16),
+        }
+    }
+}
+
+impl<C: CbcChain> Transform for CbcEncryptor<C> {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        let start = output.len();
+        take_blocks(&mut self.pending, input, output);
+        self.cbc
+            .encrypt_blocks(output[start..].as_chunks_mut::<16>().0);
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), ModeError> {
+        let mut block = pkcs7_pad(&self.pending);
+        self.pending.clear();
+        self.cbc.encrypt_blocks(std::slice::from_mut(&mut block));
+        output.extend_from_slice(&block);
+        Ok(())
+    }
+}
+
+/// CBC decryption of a stream padded with PKCS#7. The last block is held
+/// back until the end so that its padding can be removed; everything before
+/// it is released as soon as it is decrypted, before the padding is checked.
+pub struct CbcDecryptor<C: CbcChain> {
+    cbc: C,
+    pending: Vec<u8>,
+    last: Option<[u8; 16]>,
+}
+
+impl<C: CbcChain> CbcDecryptor<C> {
+    pub fn new(cbc: C) -> Self {
+        Self {
+            cbc,
+            pending: Vec::with_capacity(16),
+            last: None,
+        }
+    }
+}
+
+impl<C: CbcChain> Transform for CbcDecryptor<C> {
+    fn update(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        let start = output.len();
+        if let Some(last) = self.last.take() {
+            output.extend_from_slice(&last);
+        }
+        let blocks_start = output.len();
+        take_blocks(&mut self.pending, input, output);
+        self.cbc
+            .decrypt_blocks(output[blocks_start..].as_chunks_mut::<16>().0);
+        if output.len() > start {
+            let (_, last) = output.split_last_chunk::<16>().unwrap();
+            self.last = Some(*last);
+            output.truncate(output.len() - 16);
+        }
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), ModeError> {
+        let last = match self.last.take() {
+            Some(last) if self.pending.is_empty() => last,
+            _ => return Err(ModeError::InvalidLength),
+        };
+        output.extend_from_slice(&last[..pkcs7_unpadded_len(&last)?]);
+        Ok(())
+    }
+}
+
+fn invalid_data(error: ModeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Passes everything written to it through a `Transform` to `inner`.
+/// Call `finish` at the end: dropping the writer loses the output it still
+/// holds, including CBC's final block.
+pub struct CipherWriter<W: Write, T: Transform> {
+    inner: W,
+    transform: T,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write, T: Transform> CipherWriter<W, T> {
+    pub fn new(inner: W, transform: T) -> Self {
+        Self {
+            inner,
+            transform,
+            buffer: Vec::with_capacity(BUFFER_LEN + 16),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Ends the stream, writes the rest of the output and returns `inner`.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.transform
+            .finish(&mut self.buffer)
+            .map_err(invalid_data)?;
+        self.write_buffer()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Writes out the buffered output, keeping whatever `inner` did not take.
+    fn write_buffer(&mut self) -> io::Result<()> {
+        let mut written = 0;
+        let result = loop {
+            if written == self.buffer.len() {
+                break Ok(());
+            }
+            match self.inner.write(&self.buffer[written..]) {
+                Ok(0) => break Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        self.buffer.drain(..written);
+        result
+    }
+}
+
+impl<W: Write, T: Transform> Write for CipherWriter<W, T> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_buffer()?;
+        let data = &data[..data.len().min(BUFFER_LEN)];
+        self.transform.update(data, &mut self.buffer);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffer()?;
+        self.inner.flush()
+    }
+}
+
+/// Reads from `inner` through a `Transform`. Errors from `Transform::finish`,
+/// such as bad CBC padding, are returned as `io::ErrorKind::InvalidData`,
+/// from the read that reaches the end and every read after it.
+pub struct CipherReader<Rd: Read, T: Transform> {
+    inner: Rd,
+    transform: T,
+    buffer: Vec<u8>,
+    position: usize,
+    finished: Option<Result<(), ModeError>>,
+}
+
+impl<Rd: Read, T: Transform> CipherReader<Rd, T> {
+    pub fn new(inner: Rd, transform: T) -> Self {
+        Self {
+            inner,
+            transform,
+            buffer: Vec::with_capacity(BUFFER_LEN + 16),
+            position: 0,
+            finished: None,
+        }
+    }
+
+    pub fn get_ref(&self) -> &Rd {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Rd {
+        self.inner
+    }
+}
+
+impl<Rd: Read, T: Transform> Read for CipherReader<Rd, T> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+        // A transform may hold back all of a small read, so keep reading
+        // until there is output or the stream ends.
+        while self.position == self.buffer.len() {
+            match self.finished {
+                Some(Ok(())) => return Ok(0),
+                Some(Err(error)) => return Err(invalid_data(error)),
+                None => {}
+            }
+            self.buffer.clear();
+            self.position = 0;
+            let mut input = [0u8; BUFFER_LEN];
+            let n = self.inner.read(&mut input)?;
+            if n == 0 {
+                self.finished = Some(self.transform.finish(&mut self.buffer));
+            } else {
+                self.transform.update(&input[..n], &mut self.buffer);
+            }
+        }
+        let available = &self.buffer[self.position..];
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes256};
+
+    /// Reads at most `step` bytes at a time, to split blocks across reads.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            let n = self.data.len().min(out.len()).min(self.step);
+            out[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn write_in_pieces<T: Transform>(
+        transform: T,
+        data: &[u8],
+        step: usize,
+    ) -> io::Result<Vec<u8>> {
+        let mut writer = CipherWriter::new(Vec::new(), transform);
+        for piece in data.chunks(step) {
+            writer.write_all(piece)?;
+        }
+        writer.finish()
+    }
+
+    fn read_in_pieces<T: Transform>(transform: T, data: &[u8], step: usize) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        CipherReader::new(Trickle { data, step }, transform).read_to_end(&mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_ctr_stream() {
+        let aes = Aes256::new(&[9; 32]);
+        let plaintext: Vec<u8> = (0..BUFFER_LEN * 2 + 77).map(|i| i as u8).collect();
+        let mut expected = plaintext.clone();
+        Ctr::new(aes.clone(), &[1; 16]).apply_keystream(&mut expected);
+
+        for step in [1, 7, 16, 100, BUFFER_LEN + 5] {
+            let ctr = Ctr::new(aes.clone(), &[1; 16]);
+            assert_eq!(write_in_pieces(ctr, &plaintext, step).unwrap(), expected);
+            let ctr = Ctr::new(aes.clone(), &[1; 16]);
+            assert_eq!(read_in_pieces(ctr, &expected, step).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_cbc_stream() {
+        let aes = Aes128::new(&[3; 16]);
+        let iv = [4; 16];
+        for len in [0, 15, 16, 17, 100, BUFFER_LEN + 33] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let expected = Cbc::new(aes.clone(), &iv).encrypt_padded(&plaintext);
+            for step in [1, 13, 16, 1000] {
+                let encryptor = || CbcEncryptor::new(Cbc::new(aes.clone(), &iv));
+                let decryptor = || CbcDecryptor::new(Cbc::new(aes.clone(), &iv));
+                assert_eq!(
+                    write_in_pieces(encryptor(), &plaintext, step).unwrap(),
+                    expected
+                );
+                assert_eq!(
+                    read_in_pieces(encryptor(), &plaintext, step).unwrap(),
+                    expected
+                );
+                assert_eq!(
+                    write_in_pieces(decryptor(), &expected, step).unwrap(),
+                    plaintext
+                );
+                assert_eq!(
+                    read_in_pieces(decryptor(), &expected, step).unwrap(),
+                    plaintext
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cbc_stream_errors() {
+        let aes = Aes128::new(&[3; 16]);
+        let decryptor = || CbcDecryptor::new(Cbc::new(aes.clone(), &[0; 16]));
+        let mut ciphertext = Cbc::new(aes.clone(), &[0; 16]).encrypt_padded(b"attack at dawn");
+
+        let error = read_in_pieces(decryptor(), &ciphertext[..15], 4).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        // The error does not turn into a clean end of stream when read again.
+        let mut reader = CipherReader::new(&ciphertext[..15], decryptor());
+        let mut output = [0u8; 16];
+        for _ in 0..2 {
+            let error = reader.read(&mut output).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+        let error = write_in_pieces(decryptor(), &[], 4).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        ciphertext[0] ^= 1;
+        let error = write_in_pieces(decryptor(), &ciphertext, 5).unwrap_err();
+        assert_eq!(
+            error.into_inner().unwrap().downcast::<ModeError>().ok(),
+            Some(Box::new(ModeError::InvalidPadding))
+        );
+    }
+
+    #[test]
+    fn test_io_copy() {
+        let aes = Aes128::new(&[5; 16]);
+        let plaintext: Vec<u8> = (0..50_000).map(|i| (i % 253) as u8).collect();
+        let mut encrypted = CipherWriter::new(
+            Vec::new(),
+            CbcEncryptor::new(Cbc::new(aes.clone(), &[6; 16])),
+        );
+        io::copy(&mut &plaintext[..], &mut encrypted).unwrap();
+        let encrypted = encrypted.finish().unwrap();
+
+        let mut reader =
+            CipherReader::new(&encrypted[..], CbcDecryptor::new(Cbc::new(aes, &[6; 16])));
+        let mut decrypted = Vec::new();
+        io::copy(&mut reader, &mut decrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+}
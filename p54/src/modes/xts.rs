@@ -0,0 +1,234 @@
+use super::{xor_in_place, ModeError};
+use crate::Aes;
+
+/// XTS (XEX with ciphertext stealing) for storage encryption. Each data unit
+/// (a disk sector, say) is encrypted independently under its own tweak, and
+/// keeps its length: any length of at least one block is supported.
+#[derive(Clone)]
+pub struct Xts<const R: usize> {
+    data: Aes<R>,
+    tweak: Aes<R>,
+}
+
+/// Multiplies the tweak by `x` in GF(2^128), in the little-endian
+/// convention of IEEE 1619.
+fn next_tweak(tweak: &[u8; 16]) -> [u8; 16] {
+    let value = u128::from_le_bytes(*tweak);
+    let carry = (value >> 127) as u8;
+    ((value << 1) ^ (0x87 & 0u128.wrapping_sub(carry as u128))).to_le_bytes()
+}
+
+impl<const R: usize> Xts<R> {
+    /// Takes the two halves of an XTS key, expanded separately.
+    pub fn new(data: Aes<R>, tweak: Aes<R>) -> Self {
+        Self { data, tweak }
+    }
+
+    /// Encrypts one data unit in place. `tweak` is usually the data unit
+    /// number as 16 little-endian bytes.
+    pub fn encrypt_sector(&self, tweak: &[u8; 16], data: &mut [u8]) -> Result<(), ModeError> {
+        self.process(tweak, data, true)
+    }
+
+    /// Decrypts one data unit in place.
+    pub fn decrypt_sector(&self, tweak: &[u8; 16], data: &mut [u8]) -> Result<(), ModeError> {
+        self.process(tweak, data, false)
+    }
+
+    fn process(&self, tweak: &[u8; 16], data: &mut [u8], encrypt: bool) -> Result<(), ModeError> {
+        if data.len() < 16 {
+            return Err(ModeError::InvalidLength);
+        }
+        let mut tweak = *tweak;
+        self.tweak.encrypt_block(&mut tweak);
+
+        // With a partial last block, the last whole block is handled
+        // together with it by ciphertext stealing.
+        let partial = data.len() % 16;
+        let whole = data.len() / 16 - (partial != 0) as usize;
+        let (blocks, last) = data.split_at_mut(16 * whole);
+        let blocks = blocks.as_chunks_mut::<16>().0;
+        for chunk in blocks.chunks_mut(8) {
+            let mut tweaks = [[0u8; 16]; 8];
+            for (block, tweaks) in chunk.iter_mut().zip(tweaks.iter_mut()) {
+                *tweaks = tweak;
+                tweak = next_tweak(&tweak);
+                xor_in_place(block, tweaks);
+            }
+            if encrypt {
+                self.data.encrypt_blocks(chunk);
+            } else {
+                self.data.decrypt_blocks(chunk);
+            }
+            for (block, tweaks) in chunk.iter_mut().zip(tweaks.iter()) {
+                xor_in_place(block, tweaks);
+            }
+        }
+        if partial != 0 {
+            self.steal(tweak, last, encrypt);
+        }
+        Ok(())
+    }
+
+    /// Processes the last whole block and the partial block after it, where
+    /// `tweak` is the tweak of the last whole block.
+    fn steal(&self, tweak: [u8; 16], last: &mut [u8], encrypt: bool) {
+        let (whole, partial) = last.split_at_mut(16);
+        let whole: &mut [u8; 16] = whole.try_into().unwrap();
+        let next = next_tweak(&tweak);
+        // Decryption uses the two tweaks in the opposite order.
+        let (first, second) = if encrypt {
+            (tweak, next)
+        } else {
+            (next, tweak)
+        };
+
+        self.xex(whole, &first, encrypt);
+        // The partial block takes the front of the result, and the back
+        // fills out the partial block before it is processed in turn.
+        let mut stolen = *whole;
+        stolen[..partial.len()].copy_from_slice(partial);
+        partial.copy_from_slice(&whole[..partial.len()]);
+        self.xex(&mut stolen, &second, encrypt);
+        *whole = stolen;
+    }
+
+    fn xex(&self, block: &mut [u8; 16], tweak: &[u8; 16], encrypt: bool) {
+        xor_in_place(block, tweak);
+        if encrypt {
+            self.data.encrypt_block(block);
+        } else {
+            self.data.decrypt_block(block);
+        }
+        xor_in_place(block, tweak);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Aes128, Aes256};
+    use hex_literal::hex;
+
+    fn xts128(key: &[u8; 32]) -> Xts<11> {
+        let (data, tweak) = key.as_chunks::<16>().0.split_at(1);
+        Xts::new(Aes128::new(&data[0]), Aes128::new(&tweak[0]))
+    }
+
+    fn xts256(key: &[u8; 64]) -> Xts<15> {
+        let (data, tweak) = key.as_chunks::<32>().0.split_at(1);
+        Xts::new(Aes256::new(&data[0]), Aes256::new(&tweak[0]))
+    }
+
+    fn tweak(data_unit: u128) -> [u8; 16] {
+        data_unit.to_le_bytes()
+    }
+
+    fn check<const R: usize>(xts: &Xts<R>, tweak: &[u8; 16], plaintext: &[u8], expected: &[u8]) {
+        let mut data = plaintext.to_vec();
+        xts.encrypt_sector(tweak, &mut data).unwrap();
+        assert_eq!(data, expected);
+        xts.decrypt_sector(tweak, &mut data).unwrap();
+        assert_eq!(data, plaintext);
+    }
+
+    // IEEE 1619-2007 vectors 1 to 3.
+    #[test]
+    fn test_xts_aes128() {
+        check(
+            &xts128(&[0; 32]),
+            &tweak(0),
+            &[0; 32],
+            &hex!("917cf69ebd68b2ec9b9fe9a3eadda692cd43d2f59598ed858c02c2652fbf922e"),
+        );
+        check(
+            &xts128(&hex!(
+                "11111111111111111111111111111111"
+                "22222222222222222222222222222222"
+            )),
+            &tweak(0x3333333333),
+            &[0x44; 32],
+            &hex!("c454185e6a16936e39334038acef838bfb186fff7480adc4289382ecd6d394f0"),
+        );
+        check(
+            &xts128(&hex!(
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0"
+                "22222222222222222222222222222222"
+            )),
+            &tweak(0x3333333333),
+            &[0x44; 32],
+            &hex!("af85336b597afc1a900b2eb21ec949d292df4c047e0b21532186a5971a227a89"),
+        );
+    }
+
+    // IEEE 1619-2007 vectors 15 and 18, which use ciphertext stealing.
+    #[test]
+    fn test_xts_ciphertext_stealing() {
+        let xts = xts128(&hex!(
+            "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0"
+            "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0"
+        ));
+        let plaintext: Vec<u8> = (0..20).collect();
+        check(
+            &xts,
+            &tweak(0x123456789a),
+            &plaintext[..17],
+            &hex!("6c1625db4671522d3d7599601de7ca09ed"),
+        );
+        check(
+            &xts,
+            &tweak(0x123456789a),
+            &plaintext,
+            &hex!("9d84c813f719aa2c7be3f66171c7c5c2edbf9dac"),
+        );
+    }
+
+    // IEEE 1619-2007 vector 10, a 512-byte sector with AES-256, and its
+    // first 100 bytes.
+    #[test]
+    fn test_xts_aes256() {
+        let xts = xts256(&hex!(
+            "2718281828459045235360287471352662497757247093699959574966967627"
+            "3141592653589793238462643383279502884197169399375105820974944592"
+        ));
+        let plaintext: Vec<u8> = (0..512).map(|i| i as u8).collect();
+        let mut data = plaintext.clone();
+        xts.encrypt_sector(&tweak(0xff), &mut data).unwrap();
+        assert_eq!(
+            data[..32],
+            hex!("1c3b3a102f770386e4836c99e370cf9bea00803f5e482357a4ae12d414a3e63b")
+        );
+        assert_eq!(
+            data[480..],
+            hex!("773dad38014bd2092fa755c824bb5e54c4f36ffda9fcea70b9c6e693e148c151")
+        );
+        xts.decrypt_sector(&tweak(0xff), &mut data).unwrap();
+        assert_eq!(data, plaintext);
+
+        check(
+            &xts,
+            &tweak(0xff),
+            &plaintext[..100],
+            &hex!(
+                "1c3b3a102f770386e4836c99e370cf9bea00803f5e482357a4ae12d414a3e63b"
+                "5d31e276f8fe4a8d66b317f9ac683f44680a86ac35adfc3345befecb4bb188fd"
+                "5776926c49a3095eb108fd1098baec7042c9b5b4ac29dbf6d59b2c12ded9b654"
+                "aaa66999"
+            ),
+        );
+    }
+
+    #[test]
+    fn test_xts_short_sector() {
+        let xts = xts128(&[1; 32]);
+        let mut data = [0; 15];
+        assert_eq!(
+            xts.encrypt_sector(&tweak(0), &mut data),
+            Err(ModeError::InvalidLength)
+        );
+        assert_eq!(
+            xts.decrypt_sector(&tweak(0), &mut data),
+            Err(ModeError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! A container for expanded keys that wipes them when it is dropped.
+//!
+//! `SecretKeys` keeps the round keys in their own heap allocation, so moving
+//! the container does not leave copies of them behind, and overwrites them
+//! with volatile writes before freeing it. It is neither `Copy` nor printable
+//! with `Debug`. With the `mlock` feature on Unix, `SecretKeys::lock` also
+//! keeps the keys out of swap. Other crates can keep their own key types in
+//! it by implementing `KeyMaterial`. `SecretBytes` does the same for keys
+//! whose length is only known at run time, such as unwrapped ones.
+//!
+//! Wiping is best effort: an expanded key passed to `SecretKeys::new` by
+//! value is wiped, but temporaries the compiler left in registers or on the
+//! stack while computing it are not.
+
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Key schedules and other key-derived values `SecretKeys` can hold.
+/// # Safety
+/// Implementors must be plain data without padding, pointers or `Drop`, for
+/// which all zero bytes is a valid value, so that `wipe` can overwrite them
+/// and `SecretKeys` can move them bytewise. They need not be `Copy`, and
+/// should not be, so that keys are not copied by accident.
+pub unsafe trait KeyMaterial {}
+
+// SAFETY: arrays of integers and vectors are plain data, valid when all zero.
+unsafe impl KeyMaterial for [u8; 16] {}
+unsafe impl<const R: usize> KeyMaterial for [[u8; 16]; R] {}
+unsafe impl<const R: usize> KeyMaterial for [[u128; 8]; R] {}
+#[cfg(target_arch = "x86_64")]
+unsafe impl<const R: usize> KeyMaterial for [std::arch::x86_64::__m128i; R] {}
+
+/// Overwrites `value` with zeros in a way the compiler does not remove.
+pub fn wipe<T: KeyMaterial>(value: &mut T) {
+    // SAFETY: `value` is `size_of::<T>()` writable bytes without padding, and
+    // all zeros is a valid `T`.
+    wipe_bytes(unsafe { slice::from_raw_parts_mut((value as *mut T).cast(), size_of::<T>()) });
+}
+
+/// Overwrites `bytes` with zeros in a way the compiler does not remove.
+pub fn wipe_bytes(bytes: &mut [u8]) {
+    for byte in bytes {
+        // SAFETY: `byte` is a valid, writable reference.
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// A key of any length in a `Vec`, wiped when dropped. Unlike `SecretKeys`
+/// it cannot be locked, and growing it may leave copies behind, so it has
+/// no methods that grow it.
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Shortens the key to `len` bytes, wiping the rest.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.0.len() {
+            wipe_bytes(&mut self.0[len..]);
+            self.0.truncate(len);
+        }
+    }
+}
+
+/// Takes over `bytes` without copying them.
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        // Bytes cut off by `truncate` are already wiped.
+        wipe_bytes(&mut self.0);
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretBytes").finish_non_exhaustive()
+    }
+}
+
+/// An expanded key that is wiped when dropped.
+pub struct SecretKeys<T: KeyMaterial> {
+    keys: NonNull<T>,
+    layout: Layout,
+    locked: bool,
+}
+
+// SAFETY: `SecretKeys` owns its allocation like a `Box<T>` would.
+unsafe impl<T: KeyMaterial + Send> Send for SecretKeys<T> {}
+unsafe impl<T: KeyMaterial + Sync> Sync for SecretKeys<T> {}
+
+impl<T: KeyMaterial> SecretKeys<T> {
+    /// Moves `keys` into a new container and wipes the copy passed in.
+    pub fn new(mut keys: T) -> Self {
+        let secret = Self::zeroed();
+        // SAFETY: the allocation is valid for a `T` and does not overlap `keys`.
+        unsafe { ptr::copy_nonoverlapping(&keys, secret.keys.as_ptr(), 1) };
+        wipe(&mut keys);
+        secret
+    }
+
+    /// Returns a container of all-zero keys, for code that expands a key
+    /// through a pointer to fill in place with `expose_mut`.
+    pub fn zeroed() -> Self {
+        let layout = Layout::new::<T>();
+        let keys = allocate::<T>(layout);
+        // SAFETY: the allocation is valid for writes of a `T`, and all zeros
+        // is a valid `T`.
+        unsafe { keys.as_ptr().write_bytes(0, 1) };
+        Self {
+            keys,
+            layout,
+            locked: false,
+        }
+    }
+
+    /// Returns the keys, to pass to the functions that use them.
+    pub fn expose(&self) -> &T {
+        // SAFETY: the allocation holds a `T` from `zeroed` until `drop`.
+        unsafe { self.keys.as_ref() }
+    }
+
+    /// Returns the keys mutably, to expand a key in place.
+    pub fn expose_mut(&mut self) -> &mut T {
+        // SAFETY: the allocation holds a `T` from `zeroed` until `drop`.
+        unsafe { self.keys.as_mut() }
+    }
+
+    /// Returns whether `lock` has kept the keys out of swap.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Moves the keys to pages of their own and locks those into memory, so
+    /// that they are never written to swap. Locking the same container again
+    /// does nothing.
+    #[cfg(all(feature = "mlock", unix))]
+    pub fn lock(&mut self) -> std::io::Result<()> {
+        if self.locked {
+            return Ok(());
+        }
+        // SAFETY: `sysconf` has no preconditions.
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let layout = Layout::from_size_align(size_of::<T>().next_multiple_of(page), page)
+            .expect("Page size is a power of two");
+        let keys = allocate::<T>(layout);
+        // SAFETY: `keys` points to `layout.size()` bytes that nothing else uses.
+        if unsafe { libc::mlock(keys.as_ptr().cast(), layout.size()) } != 0 {
+            let error = std::io::Error::last_os_error();
+            // SAFETY: `keys` was allocated above with `layout`.
+            unsafe { alloc::dealloc(keys.as_ptr().cast(), layout) };
+            return Err(error);
+        }
+        // SAFETY: both allocations hold room for a `T`, and the old one is
+        // released only after the copy.
+        unsafe {
+            ptr::copy_nonoverlapping(self.keys.as_ptr(), keys.as_ptr(), 1);
+            self.release();
+        }
+        self.keys = keys;
+        self.layout = layout;
+        self.locked = true;
+        Ok(())
+    }
+
+    /// Wipes the keys, unlocks them if they were locked, and frees them.
+    /// # Safety
+    /// The container must not be used afterwards, other than to replace `keys`.
+    unsafe fn release(&mut self) {
+        wipe(self.keys.as_mut());
+        #[cfg(all(feature = "mlock", unix))]
+        if self.locked {
+            libc::munlock(self.keys.as_ptr().cast(), self.layout.size());
+        }
+        alloc::dealloc(self.keys.as_ptr().cast(), self.layout);
+    }
+}
+
+fn allocate<T>(layout: Layout) -> NonNull<T> {
+    // SAFETY: key schedules are never zero-sized.
+    let ptr = unsafe { alloc::alloc(layout) };
+    NonNull::new(ptr.cast()).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+}
+
+/// A clone is wiped when dropped too, but is not locked.
+impl<T: KeyMaterial> Clone for SecretKeys<T> {
+    fn clone(&self) -> Self {
+        let clone = Self::zeroed();
+        // SAFETY: both allocations hold a `T`, which `KeyMaterial` allows to
+        // be copied bytewise.
+        unsafe { ptr::copy_nonoverlapping(self.keys.as_ptr(), clone.keys.as_ptr(), 1) };
+        clone
+    }
+}
+
+impl<T: KeyMaterial> Drop for SecretKeys<T> {
+    fn drop(&mut self) {
+        // SAFETY: the container is not used after it is dropped.
+        unsafe { self.release() };
+    }
+}
+
+impl<T: KeyMaterial> fmt::Debug for SecretKeys<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretKeys").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soft;
+
+    #[test]
+    fn test_secret_keys() {
+        let keys = soft::expand_key(&[0x17; 16]);
+        let secret = SecretKeys::new(keys);
+        assert_eq!(*secret.expose(), keys);
+        assert_eq!(*secret.clone().expose(), keys);
+        assert!(!secret.is_locked());
+        assert_eq!(format!("{secret:?}"), "SecretKeys { .. }");
+    }
+
+    #[test]
+    fn test_zeroed() {
+        let mut secret = SecretKeys::<[[u8; 16]; 11]>::zeroed();
+        assert_eq!(*secret.expose(), [[0; 16]; 11]);
+        secret.expose_mut()[3] = [0x17; 16];
+        assert_eq!(secret.clone().expose()[3], [0x17; 16]);
+    }
+
+    #[test]
+    fn test_secret_bytes() {
+        let mut secret = SecretBytes::from(vec![0x17; 20]);
+        secret.truncate(5);
+        assert_eq!(secret.expose(), [0x17; 5]);
+        assert_eq!(format!("{secret:?}"), "SecretBytes { .. }");
+
+        let mut bytes = [0x17; 9];
+        wipe_bytes(&mut bytes[2..]);
+        assert_eq!(bytes, [0x17, 0x17, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_wipe() {
+        let mut keys = soft::expand_key(&[0x17; 32]);
+        wipe(&mut keys);
+        assert_eq!(keys, [[0; 16]; 15]);
+    }
+
+    #[cfg(all(feature = "mlock", unix))]
+    #[test]
+    fn test_lock() {
+        let keys = soft::expand_key(&[0x17; 24]);
+        let mut secret = SecretKeys::new(keys);
+        secret.lock().unwrap();
+        assert!(secret.is_locked());
+        secret.lock().unwrap();
+        assert_eq!(*secret.expose(), keys);
+        let clone = secret.clone();
+        assert!(!clone.is_locked());
+        assert_eq!(*clone.expose(), keys);
+    }
+}
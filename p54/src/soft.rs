@@ -0,0 +1,465 @@
+//! A portable, constant-time software AES, used when AES-NI is unavailable.
+//!
+//! The functions mirror the AES-NI ones, with round keys held as bytes. The
+//! cipher is bitsliced: bit `b` of every byte in a batch of up to eight
+//! blocks is packed into plane `b`, where each block is a 16-bit lane. A
+//! batch is packed once and stays in planes for all of its rounds. The
+//! GF(2^8) inverse in the S-box is computed as `x^254` with a boolean circuit
+//! over the planes, and ShiftRows and MixColumns are shifts and masks within
+//! the lanes. Nothing indexes a table or branches on secret data.
+//!
+//! It is still slow, on the order of 10 MB/s: the S-box circuit runs four
+//! full GF(2^8) multiplications per round for each batch, whether it holds
+//! one block or eight, and packing a batch into planes and back goes bit by
+//! bit.
+
+use crate::secret::wipe;
+use crate::{assert_round_key_count, assert_whole_blocks, AesKey};
+
+/// Bit `b` of byte `i` of a batch is bit `i` of plane `b`.
+type Planes = [u128; 8];
+
+/// Copies a 16-bit value into each of the eight 16-bit lanes of a plane.
+const LANES: u128 = 0x0001_0001_0001_0001_0001_0001_0001_0001;
+/// The bits of row `r` within each block: every fourth bit from bit `r`.
+const ROWS: u128 = 0x1111 * LANES;
+
+fn to_planes(bytes: &[u8]) -> Planes {
+    let mut planes = [0u128; 8];
+    for (i, byte) in bytes.iter().enumerate() {
+        for (b, plane) in planes.iter_mut().enumerate() {
+            *plane |= (((byte >> b) & 1) as u128) << i;
+        }
+    }
+    planes
+}
+
+fn from_planes(planes: &Planes, bytes: &mut [u8]) {
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = 0;
+        for (b, plane) in planes.iter().enumerate() {
+            *byte |= (((plane >> i) & 1) as u8) << b;
+        }
+    }
+}
+
+/// Multiplies in GF(2^8) modulo `x^8 + x^4 + x^3 + x + 1`.
+fn gf_mul(a: &Planes, b: &Planes) -> Planes {
+    let mut product = [0u128; 15];
+    for i in 0..8 {
+        for j in 0..8 {
+            product[i + j] ^= a[i] & b[j];
+        }
+    }
+    reduce(product)
+}
+
+/// Squares in GF(2^8). Squaring is linear in characteristic two, so it only
+/// spreads the bits out before reducing.
+fn gf_square(a: &Planes) -> Planes {
+    let mut product = [0u128; 15];
+    for i in 0..8 {
+        product[2 * i] = a[i];
+    }
+    reduce(product)
+}
+
+fn reduce(mut product: [u128; 15]) -> Planes {
+    // x^k = x^(k - 8) * (x^4 + x^3 + x + 1), folded from the top down
+    for k in (8..15).rev() {
+        let high = product[k];
+        product[k - 4] ^= high;
+        product[k - 5] ^= high;
+        product[k - 7] ^= high;
+        product[k - 8] ^= high;
+    }
+    product[..8].try_into().unwrap()
+}
+
+/// Returns `x^254`, the multiplicative inverse of `x`, or zero for zero.
+fn gf_inverse(x: &Planes) -> Planes {
+    let x2 = gf_square(x);
+    let x3 = gf_mul(&x2, x);
+    let x12 = gf_square(&gf_square(&x3));
+    let x15 = gf_mul(&x12, &x3);
+    let mut x240 = x15;
+    for _ in 0..4 {
+        x240 = gf_square(&x240);
+    }
+    let x252 = gf_mul(&x240, &x12);
+    gf_mul(&x252, &x2)
+}
+
+/// Applies the S-box to every byte of a batch.
+fn sub_planes(x: &Planes) -> Planes {
+    let x = gf_inverse(x);
+    let mut s = [0u128; 8];
+    for i in 0..8 {
+        s[i] = x[i] ^ x[(i + 4) % 8] ^ x[(i + 5) % 8] ^ x[(i + 6) % 8] ^ x[(i + 7) % 8];
+        if (0x63 >> i) & 1 == 1 {
+            s[i] = !s[i];
+        }
+    }
+    s
+}
+
+/// Applies the inverse S-box to every byte of a batch.
+fn inv_sub_planes(s: &Planes) -> Planes {
+    let mut x = [0u128; 8];
+    for i in 0..8 {
+        x[i] = s[(i + 2) % 8] ^ s[(i + 5) % 8] ^ s[(i + 7) % 8];
+        if (0x05 >> i) & 1 == 1 {
+            x[i] = !x[i];
+        }
+    }
+    gf_inverse(&x)
+}
+
+/// Applies the S-box to every byte of `bytes`, for the key schedule.
+fn sub_bytes(bytes: &mut [u8]) {
+    for batch in bytes.chunks_mut(128) {
+        from_planes(&sub_planes(&to_planes(batch)), batch);
+    }
+}
+
+/// Multiplies every byte by `x` in GF(2^8).
+fn xtime(x: &Planes) -> Planes {
+    [
+        x[7],
+        x[0] ^ x[7],
+        x[1],
+        x[2] ^ x[7],
+        x[3] ^ x[7],
+        x[4],
+        x[5],
+        x[6],
+    ]
+}
+
+fn xor(a: &Planes, b: &Planes) -> Planes {
+    std::array::from_fn(|i| a[i] ^ b[i])
+}
+
+fn add_round_key(state: &mut Planes, key: &Planes) {
+    *state = xor(state, key);
+}
+
+// Each block is a 16-bit lane of every plane, stored column by column, so
+// bit `4 * c + r` of a lane is row `r` of column `c`.
+
+/// Rotates each column of every block up by `k` rows: row `r` takes the
+/// byte of row `(r + k) % 4`.
+fn rotate_columns(x: &Planes, k: u32) -> Planes {
+    // Rows before `4 - k` take a byte from further down the same column,
+    // the others wrap around to its top.
+    let low = ROWS * ((1 << (4 - k)) - 1);
+    x.map(|plane| ((plane >> k) & low) | ((plane << (4 - k)) & !low))
+}
+
+/// Rotates row `r` of every block left by `r` columns, or right if `inverse`.
+fn shift_rows(x: &Planes, inverse: bool) -> Planes {
+    x.map(|plane| {
+        let mut result = plane & ROWS;
+        for r in 1..4 {
+            let shift = 4 * if inverse { 4 - r } else { r };
+            // Columns at or past `shift / 4` move down within the lane, the
+            // ones before it wrap around to the top.
+            let row = plane & (ROWS << r);
+            let high = row & (((0xffff << shift) & 0xffff) * LANES);
+            result |= (high >> shift) | ((row ^ high) << (16 - shift));
+        }
+        result
+    })
+}
+
+fn mix_columns(x: &Planes) -> Planes {
+    // Row `r` becomes `a_r ^ all ^ xtime(a_r ^ a_(r + 1))`, where `all` is
+    // the XOR of the whole column.
+    let pair = xor(x, &rotate_columns(x, 1));
+    let all = xor(&pair, &rotate_columns(&pair, 2));
+    xor(&xor(x, &all), &xtime(&pair))
+}
+
+fn inv_mix_columns(x: &Planes) -> Planes {
+    // InvMixColumns is MixColumns after multiplying rows 0 and 2 by
+    // x^2 (a0 ^ a2) and rows 1 and 3 by x^2 (a1 ^ a3).
+    let opposite = xor(x, &rotate_columns(x, 2));
+    mix_columns(&xor(x, &xtime(&xtime(&opposite))))
+}
+
+/// Spreads each round key over all eight lanes, so that one XOR adds it to
+/// a whole batch.
+fn key_planes<const R: usize>(keys: &[[u8; 16]; R]) -> [Planes; R] {
+    keys.map(|key| to_planes(&key).map(|plane| plane * LANES))
+}
+
+/// Encrypts the consecutive 16-byte blocks of `blocks` in place, eight at a
+/// time. Each batch stays in planes for all of its rounds.
+fn encrypt_blocks<const R: usize>(keys: &[[u8; 16]; R], blocks: &mut [u8]) {
+    const { assert_round_key_count(R) };
+    let mut keys = key_planes(keys);
+    for batch in blocks.chunks_mut(16 * 8) {
+        let mut state = to_planes(batch);
+        add_round_key(&mut state, &keys[0]);
+        for (round, key) in keys.iter().enumerate().skip(1) {
+            state = shift_rows(&sub_planes(&state), false);
+            if round != R - 1 {
+                state = mix_columns(&state);
+            }
+            add_round_key(&mut state, key);
+        }
+        from_planes(&state, batch);
+    }
+    wipe(&mut keys);
+}
+
+/// Decrypts the consecutive 16-byte blocks of `blocks` in place, eight at a
+/// time. Each batch stays in planes for all of its rounds.
+fn decrypt_blocks<const R: usize>(keys: &[[u8; 16]; R], blocks: &mut [u8]) {
+    const { assert_round_key_count(R) };
+    let mut keys = key_planes(keys);
+    for batch in blocks.chunks_mut(16 * 8) {
+        let mut state = to_planes(batch);
+        for (round, key) in keys.iter().enumerate().skip(1).rev() {
+            add_round_key(&mut state, key);
+            if round != R - 1 {
+                state = inv_mix_columns(&state);
+            }
+            state = inv_sub_planes(&shift_rows(&state, true));
+        }
+        add_round_key(&mut state, &keys[0]);
+        from_planes(&state, batch);
+    }
+    wipe(&mut keys);
+}
+
+/// Expands a key of `4 * NK` bytes to `R` round keys (FIPS-197 section 5.2).
+/// The words are built in the returned array itself, so no other copy of the
+/// schedule is left behind.
+fn expand<const NK: usize, const R: usize>(key: &[u8]) -> [[u8; 16]; R] {
+    const ROUND_CONSTANTS: [u8; 10] = [1, 2, 4, 8, 16, 32, 64, 128, 27, 54];
+    let mut round_keys = [[0u8; 16]; R];
+    let (words, _) = round_keys.as_flattened_mut().as_chunks_mut::<4>();
+    for (word, bytes) in words.iter_mut().zip(key.chunks_exact(4)) {
+        word.copy_from_slice(bytes);
+    }
+    for i in NK..4 * R {
+        let mut temp = words[i - 1];
+        if i % NK == 0 {
+            temp.rotate_left(1);
+            sub_bytes(&mut temp);
+            temp[0] ^= ROUND_CONSTANTS[i / NK - 1];
+        } else if NK > 6 && i % NK == 4 {
+            sub_bytes(&mut temp);
+        }
+        for (byte, prev) in temp.iter_mut().zip(words[i - NK]) {
+            *byte ^= prev;
+        }
+        words[i] = temp;
+    }
+    round_keys
+}
+
+/// This function expands AES keys of any size to round keys.
+pub fn expand_key<K: AesKey>(key: &K) -> K::SoftRoundKeys {
+    key.expand_soft()
+}
+
+/// This function expands 128-bit AES keys to round keys.
+pub fn expand_key_128(key: &[u8; 16]) -> [[u8; 16]; 11] {
+    expand::<4, 11>(key)
+}
+
+/// This function expands 192-bit AES keys to round keys.
+pub fn expand_key_192(key: &[u8; 24]) -> [[u8; 16]; 13] {
+    expand::<6, 13>(key)
+}
+
+/// This function expands 256-bit AES keys to round keys.
+pub fn expand_key_256(key: &[u8; 32]) -> [[u8; 16]; 15] {
+    expand::<8, 15>(key)
+}
+
+/// This function encrypts one 128-bit block using AES.
+pub fn encrypt1<const R: usize>(keys: &[[u8; 16]; R], block: &[u8; 16]) -> [u8; 16] {
+    let mut output = *block;
+    encrypt_blocks(keys, &mut output);
+    output
+}
+
+/// This function decrypts one 128-bit block using AES.
+pub fn decrypt1<const R: usize>(keys: &[[u8; 16]; R], block: &[u8; 16]) -> [u8; 16] {
+    let mut output = *block;
+    decrypt_blocks(keys, &mut output);
+    output
+}
+
+/// This function encrypts eight 128-bit blocks using AES, sharing each
+/// S-box evaluation between all of them.
+pub fn encrypt8<const R: usize>(keys: &[[u8; 16]; R], blocks: &[u8; 128]) -> [u8; 128] {
+    let mut output = *blocks;
+    encrypt_blocks(keys, &mut output);
+    output
+}
+
+/// This function decrypts eight 128-bit blocks using AES, sharing each
+/// S-box evaluation between all of them.
+pub fn decrypt8<const R: usize>(keys: &[[u8; 16]; R], blocks: &[u8; 128]) -> [u8; 128] {
+    let mut output = *blocks;
+    decrypt_blocks(keys, &mut output);
+    output
+}
+
+/// This function encrypts any whole number of 128-bit blocks in place using
+/// AES, eight at a time.
+/// # Panics
+/// If the length of `data` is not a multiple of 16.
+pub fn encrypt_in_place<const R: usize>(keys: &[[u8; 16]; R], data: &mut [u8]) {
+    assert_whole_blocks(data.len());
+    encrypt_blocks(keys, data);
+}
+
+/// This function encrypts any whole number of 128-bit blocks from `input`
+/// into `output` using AES, eight at a time.
+/// # Panics
+/// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+pub fn encrypt_into<const R: usize>(keys: &[[u8; 16]; R], input: &[u8], output: &mut [u8]) {
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Output length differs from input"
+    );
+    output.copy_from_slice(input);
+    encrypt_in_place(keys, output);
+}
+
+/// This function decrypts any whole number of 128-bit blocks in place using
+/// AES, eight at a time.
+/// # Panics
+/// If the length of `data` is not a multiple of 16.
+pub fn decrypt_in_place<const R: usize>(keys: &[[u8; 16]; R], data: &mut [u8]) {
+    assert_whole_blocks(data.len());
+    decrypt_blocks(keys, data);
+}
+
+/// This function decrypts any whole number of 128-bit blocks from `input`
+/// into `output` using AES, eight at a time.
+/// # Panics
+/// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+pub fn decrypt_into<const R: usize>(keys: &[[u8; 16]; R], input: &[u8], output: &mut [u8]) {
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Output length differs from input"
+    );
+    output.copy_from_slice(input);
+    decrypt_in_place(keys, output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_sbox() {
+        let mut bytes: [u8; 256] = std::array::from_fn(|i| i as u8);
+        sub_bytes(&mut bytes);
+        assert_eq!(bytes[..4], [0x63, 0x7c, 0x77, 0x7b]);
+        assert_eq!(bytes[0x53], 0xed);
+        assert_eq!(bytes[0xff], 0x16);
+        for batch in bytes.chunks_mut(128) {
+            from_planes(&inv_sub_planes(&to_planes(batch)), batch);
+        }
+        assert!(bytes.iter().enumerate().all(|(i, &b)| b == i as u8));
+    }
+
+    #[test]
+    fn test_expand_key_2() {
+        let key = hex!("5468617473206D79204B756E67204675");
+        let round_keys = expand_key(&key);
+        assert_eq!(round_keys[10], hex!("28FDDEF86DA4244ACCC0A4FE3B316F26"));
+    }
+
+    // FIPS-197 Appendix A: the last round key of each example key expansion.
+    #[test]
+    fn test_expand_key_fips197() {
+        let round_keys = expand_key(&hex!("2b7e151628aed2a6abf7158809cf4f3c"));
+        assert_eq!(round_keys[10], hex!("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+        let round_keys = expand_key(&hex!("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b"));
+        assert_eq!(round_keys[12], hex!("e98ba06f448c773c8ecc720401002202"));
+        let round_keys = expand_key(&hex!(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+        ));
+        assert_eq!(round_keys[14], hex!("fe4890d1e6188d0b046df344706c631e"));
+    }
+
+    #[test]
+    fn test_encrypt1_1() {
+        let round_keys = expand_key(&hex!("80000000000000000000000000000000"));
+        let ciphertext = encrypt1(&round_keys, &[0; 16]);
+        assert_eq!(ciphertext, hex!("0edd33d3c621e546455bd8ba1418bec8"));
+        assert_eq!(decrypt1(&round_keys, &ciphertext), [0; 16]);
+    }
+
+    // FIPS-197 Appendix C: the example vectors for all three key sizes.
+    #[test]
+    fn test_encrypt1_fips197() {
+        let plaintext = hex!("00112233445566778899aabbccddeeff");
+
+        let round_keys = expand_key(&hex!("000102030405060708090a0b0c0d0e0f"));
+        let ciphertext = encrypt1(&round_keys, &plaintext);
+        assert_eq!(ciphertext, hex!("69c4e0d86a7b0430d8cdb78070b4c55a"));
+        assert_eq!(decrypt1(&round_keys, &ciphertext), plaintext);
+
+        let round_keys = expand_key(&hex!("000102030405060708090a0b0c0d0e0f1011121314151617"));
+        let ciphertext = encrypt1(&round_keys, &plaintext);
+        assert_eq!(ciphertext, hex!("dda97ca4864cdfe06eaf70a0ec0d7191"));
+        assert_eq!(decrypt1(&round_keys, &ciphertext), plaintext);
+
+        let round_keys = expand_key(&hex!(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        ));
+        let ciphertext = encrypt1(&round_keys, &plaintext);
+        assert_eq!(ciphertext, hex!("8ea2b7ca516745bfeafc49904b496089"));
+        assert_eq!(decrypt1(&round_keys, &ciphertext), plaintext);
+    }
+
+    #[test]
+    fn test_encrypt8_decrypt8() {
+        let round_keys = expand_key(&hex!("0edd33d3c621e546455bd8ba1418bec8"));
+        let blocks: [u8; 128] = std::array::from_fn(|i| (i / 16) as u8);
+        let encrypted = encrypt8(&round_keys, &blocks);
+        for (i, block) in encrypted.chunks_exact(16).enumerate() {
+            assert_eq!(block, encrypt1(&round_keys, &[i as u8; 16]));
+        }
+        assert_eq!(decrypt8(&round_keys, &encrypted), blocks);
+    }
+
+    #[test]
+    fn test_encrypt_in_place() {
+        let round_keys = expand_key(&[0x2a; 32]);
+        for blocks in [0, 5, 8, 19] {
+            let plaintext: Vec<u8> = (0..16 * blocks).map(|i| (i * 7) as u8).collect();
+            let mut data = plaintext.clone();
+            encrypt_in_place(&round_keys, &mut data);
+            for (encrypted, block) in data.chunks(16).zip(plaintext.chunks(16)) {
+                assert_eq!(encrypted, encrypt1(&round_keys, block.try_into().unwrap()));
+            }
+            let mut output = vec![0; data.len()];
+            encrypt_into(&round_keys, &plaintext, &mut output);
+            assert_eq!(output, data);
+
+            decrypt_in_place(&round_keys, &mut data);
+            assert_eq!(data, plaintext);
+            decrypt_into(&round_keys, &output, &mut data);
+            assert_eq!(data, plaintext);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Data length is not a multiple of 16 bytes")]
+    fn test_decrypt_in_place_partial_block() {
+        decrypt_in_place(&expand_key(&[0; 16]), &mut [0; 17]);
+    }
+}
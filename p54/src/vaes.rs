@@ -0,0 +1,324 @@
+//! AES on x86_64 using VAES, which runs `aesenc` on every 128-bit lane of a
+//! 256- or 512-bit vector.
+//!
+//! The kernels keep eight vectors in flight, like the AES-NI ones keep eight
+//! blocks, so each step covers 16 blocks with 256-bit vectors and 32 with
+//! 512-bit ones. Whatever is left over goes through the AES-NI kernels, so
+//! both paths produce the same output for any input.
+
+use crate::aesni::{decrypt_states, encrypt_states, inverse_key_schedule, process_blocks};
+use crate::{assert_round_key_count, assert_whole_blocks, secret};
+use std::arch::x86_64::*;
+
+/// The vector width the VAES functions run at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Width {
+    /// 256-bit vectors, two blocks per instruction (VAES and AVX2).
+    Bits256,
+    /// 512-bit vectors, four blocks per instruction (VAES and AVX-512F).
+    Bits512,
+}
+
+impl Width {
+    /// Returns the widest vectors this CPU runs VAES on, if it has VAES.
+    pub fn detect() -> Option<Self> {
+        [Self::Bits512, Self::Bits256]
+            .into_iter()
+            .find(|width| width.is_available())
+    }
+
+    /// Returns whether this CPU supports the instructions this width uses.
+    pub fn is_available(self) -> bool {
+        let base = is_x86_feature_detected!("sse2")
+            && is_x86_feature_detected!("aes")
+            && is_x86_feature_detected!("avx2")
+            && is_x86_feature_detected!("vaes");
+        match self {
+            Self::Bits256 => base,
+            Self::Bits512 => base && is_x86_feature_detected!("avx512f"),
+        }
+    }
+}
+
+macro_rules! wide_kernel {
+    (
+        $name:ident, $features:literal, $vector:ty, $broadcast:ident, $load:ident, $store:ident,
+        $xor:ident, $round:ident, $last_round:ident, $finish:ident
+    ) => {
+        /// Runs the rounds over eight vectors at a time, and the AES-NI
+        /// kernel over the blocks that do not fill them.
+        #[target_feature(enable = $features)]
+        unsafe fn $name<const R: usize>(
+            keys: &[__m128i; R],
+            input: *const u8,
+            output: *mut u8,
+            len: usize,
+        ) {
+            const { assert_round_key_count(R) };
+            const STEP: usize = size_of::<$vector>() * 8;
+            let mut wide_keys = [$broadcast(keys[0]); R];
+            for (wide_key, key) in wide_keys.iter_mut().zip(keys) {
+                *wide_key = $broadcast(*key);
+            }
+            let mut start = 0;
+            while len - start >= STEP {
+                let mut states = [wide_keys[0]; 8];
+                for (i, state) in states.iter_mut().enumerate() {
+                    let offset = start + size_of::<$vector>() * i;
+                    *state = $xor($load(input.add(offset) as *const _), wide_keys[0]);
+                }
+                for key in &wide_keys[1..R - 1] {
+                    for state in states.iter_mut() {
+                        *state = $round(*state, *key);
+                    }
+                }
+                for (i, state) in states.iter().enumerate() {
+                    let offset = start + size_of::<$vector>() * i;
+                    $store(
+                        output.add(offset) as *mut _,
+                        $last_round(*state, wide_keys[R - 1]),
+                    );
+                }
+                start += STEP;
+            }
+            process_blocks(input.add(start), output.add(start), len - start, |states| {
+                $finish(keys, states)
+            });
+        }
+    };
+}
+
+wide_kernel!(
+    encrypt_256,
+    "aes,avx2,vaes",
+    __m256i,
+    _mm256_broadcastsi128_si256,
+    _mm256_loadu_si256,
+    _mm256_storeu_si256,
+    _mm256_xor_si256,
+    _mm256_aesenc_epi128,
+    _mm256_aesenclast_epi128,
+    encrypt_states
+);
+wide_kernel!(
+    decrypt_256,
+    "aes,avx2,vaes",
+    __m256i,
+    _mm256_broadcastsi128_si256,
+    _mm256_loadu_si256,
+    _mm256_storeu_si256,
+    _mm256_xor_si256,
+    _mm256_aesdec_epi128,
+    _mm256_aesdeclast_epi128,
+    decrypt_states
+);
+wide_kernel!(
+    encrypt_512,
+    "aes,avx512f,vaes",
+    __m512i,
+    _mm512_broadcast_i32x4,
+    _mm512_loadu_si512,
+    _mm512_storeu_si512,
+    _mm512_xor_si512,
+    _mm512_aesenc_epi128,
+    _mm512_aesenclast_epi128,
+    encrypt_states
+);
+wide_kernel!(
+    decrypt_512,
+    "aes,avx512f,vaes",
+    __m512i,
+    _mm512_broadcast_i32x4,
+    _mm512_loadu_si512,
+    _mm512_storeu_si512,
+    _mm512_xor_si512,
+    _mm512_aesdec_epi128,
+    _mm512_aesdeclast_epi128,
+    decrypt_states
+);
+
+unsafe fn encrypt_blocks<const R: usize>(
+    width: Width,
+    keys: &[__m128i; R],
+    input: *const u8,
+    output: *mut u8,
+    len: usize,
+) {
+    match width {
+        Width::Bits256 => encrypt_256(keys, input, output, len),
+        Width::Bits512 => encrypt_512(keys, input, output, len),
+    }
+}
+
+unsafe fn decrypt_blocks<const R: usize>(
+    width: Width,
+    inverse_keys: &[__m128i; R],
+    input: *const u8,
+    output: *mut u8,
+    len: usize,
+) {
+    match width {
+        Width::Bits256 => decrypt_256(inverse_keys, input, output, len),
+        Width::Bits512 => decrypt_512(inverse_keys, input, output, len),
+    }
+}
+
+/// This function encrypts any whole number of 128-bit blocks in place using
+/// AES with `width`-bit vectors.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the instructions `width` uses (see `Width::is_available`).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `data` is not a multiple of 16.
+pub unsafe fn encrypt_in_place<const R: usize>(width: Width, keys: &[__m128i; R], data: &mut [u8]) {
+    assert_whole_blocks(data.len());
+    let ptr = data.as_mut_ptr();
+    encrypt_blocks(width, keys, ptr, ptr, data.len());
+}
+
+/// This function encrypts any whole number of 128-bit blocks from `input`
+/// into `output` using AES with `width`-bit vectors.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the instructions `width` uses (see `Width::is_available`).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+pub unsafe fn encrypt_into<const R: usize>(
+    width: Width,
+    keys: &[__m128i; R],
+    input: &[u8],
+    output: &mut [u8],
+) {
+    assert_whole_blocks(input.len());
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Output length differs from input"
+    );
+    encrypt_blocks(
+        width,
+        keys,
+        input.as_ptr(),
+        output.as_mut_ptr(),
+        input.len(),
+    );
+}
+
+/// This function decrypts any whole number of 128-bit blocks in place using
+/// AES with `width`-bit vectors.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the instructions `width` uses (see `Width::is_available`).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `data` is not a multiple of 16.
+pub unsafe fn decrypt_in_place<const R: usize>(width: Width, keys: &[__m128i; R], data: &mut [u8]) {
+    let mut inverse_keys = inverse_key_schedule(keys);
+    decrypt_inverse_in_place(width, &inverse_keys, data);
+    secret::wipe(&mut inverse_keys);
+}
+
+/// This function decrypts any whole number of 128-bit blocks from `input`
+/// into `output` using AES with `width`-bit vectors.
+/// # Safety
+/// This function is unsafe because it uses SIMD intrinsics that require
+/// the target CPU to support them. Ensure that:
+/// 1. You are running on a CPU that supports the instructions `width` uses (see `Width::is_available`).
+/// 2. The `keys` parameter must be a valid reference to an array of 11, 13 or 15 128-bit keys.
+/// 3. The `keys` array must be generated using the `expand_key` function.
+/// # Panics
+/// If the length of `input` is not a multiple of 16, or differs from that of `output`.
+pub unsafe fn decrypt_into<const R: usize>(
+    width: Width,
+    keys: &[__m128i; R],
+    input: &[u8],
+    output: &mut [u8],
+) {
+    let mut inverse_keys = inverse_key_schedule(keys);
+    decrypt_inverse_into(width, &inverse_keys, input, output);
+    secret::wipe(&mut inverse_keys);
+}
+
+/// Like `decrypt_in_place`, with a schedule from `inverse_key_schedule`.
+pub(crate) unsafe fn decrypt_inverse_in_place<const R: usize>(
+    width: Width,
+    inverse_keys: &[__m128i; R],
+    data: &mut [u8],
+) {
+    assert_whole_blocks(data.len());
+    let ptr = data.as_mut_ptr();
+    decrypt_blocks(width, inverse_keys, ptr, ptr, data.len());
+}
+
+/// Like `decrypt_into`, with a schedule from `inverse_key_schedule`.
+pub(crate) unsafe fn decrypt_inverse_into<const R: usize>(
+    width: Width,
+    inverse_keys: &[__m128i; R],
+    input: &[u8],
+    output: &mut [u8],
+) {
+    assert_whole_blocks(input.len());
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Output length differs from input"
+    );
+    decrypt_blocks(
+        width,
+        inverse_keys,
+        input.as_ptr(),
+        output.as_mut_ptr(),
+        input.len(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aesni, expand_key};
+
+    fn check<const R: usize>(keys: &[__m128i; R]) {
+        // Whole steps of both widths, plus leftovers for the AES-NI kernels.
+        let plaintext: Vec<u8> = (0..16 * 77).map(|i| (i * 13 % 256) as u8).collect();
+        for width in [Width::Bits256, Width::Bits512] {
+            // Both widths need AES-NI too, so `keys` is valid whenever they run.
+            if !width.is_available() {
+                continue;
+            }
+            for len in [0, 16, 16 * 16, 16 * 32, 16 * 47, plaintext.len()] {
+                let mut expected = plaintext[..len].to_vec();
+                let mut data = expected.clone();
+                unsafe {
+                    aesni::encrypt_in_place(keys, &mut expected);
+                    encrypt_in_place(width, keys, &mut data);
+                }
+                assert_eq!(data, expected, "{width:?}, {len} bytes");
+
+                let mut output = vec![0; len];
+                unsafe { encrypt_into(width, keys, &plaintext[..len], &mut output) };
+                assert_eq!(output, expected);
+                unsafe { decrypt_in_place(width, keys, &mut data) };
+                assert_eq!(data, plaintext[..len]);
+                unsafe { decrypt_into(width, keys, &output, &mut data) };
+                assert_eq!(data, plaintext[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vaes_matches_aesni() {
+        unsafe {
+            check(&expand_key(&[0x42; 16]));
+            check(&expand_key(&[0x42; 24]));
+            check(&expand_key(&[0x42; 32]));
+        }
+    }
+}
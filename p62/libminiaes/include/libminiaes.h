@@ -33,3 +33,9 @@ void encrypt8(const struct RoundKeys *keys, const uint8_t (*input)[128], uint8_t
  * This function decrypts eight 128-bit blocks using AES.
  */
 void decrypt8(const struct RoundKeys *keys, const uint8_t (*input)[128], uint8_t (*output)[128]);
+
+/**
+ * This function overwrites round keys with zeros. Call it before freeing or
+ * reusing the memory that holds them. It does nothing if `rkeys` is null.
+ */
+void wipe_keys(struct RoundKeys *rkeys);
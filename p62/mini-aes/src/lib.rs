@@ -1,39 +1,70 @@
 use miniaes_sys as sys;
-use std::mem::MaybeUninit;
+use p54::secret::{KeyMaterial, SecretKeys};
+use std::fmt;
 
+pub mod stream;
+
+/// The round keys, in a type of this crate so that it can hold them in
+/// `SecretKeys`.
+#[repr(transparent)]
+struct RoundKeys(sys::RoundKeys);
+
+// SAFETY: `sys::RoundKeys` is an array of bytes, valid when all zero.
+unsafe impl KeyMaterial for RoundKeys {}
+
+/// An expanded AES-128 key. The round keys are kept in `SecretKeys`, so
+/// moving the wrapper does not copy them, and are wiped when it is dropped.
 pub struct AESWrapper {
-    keys: sys::RoundKeys,
+    keys: SecretKeys<RoundKeys>,
 }
 
 impl AESWrapper {
     pub fn new(key: &[u8; 16]) -> Self {
-        let mut keys = MaybeUninit::uninit();
-        unsafe {
-            sys::expand_key(key, keys.as_mut_ptr());
-            Self {
-                keys: keys.assume_init(),
-            }
-        }
+        let mut keys = SecretKeys::<RoundKeys>::zeroed();
+        unsafe { sys::expand_key(key, &mut keys.expose_mut().0) };
+        Self { keys }
+    }
+
+    fn keys(&self) -> &sys::RoundKeys {
+        &self.keys.expose().0
+    }
+
+    /// Returns whether `lock` has kept the round keys out of swap.
+    pub fn is_locked(&self) -> bool {
+        self.keys.is_locked()
+    }
+
+    /// Locks the round keys into memory, so that they are never written to
+    /// swap. See `SecretKeys::lock`.
+    #[cfg(all(feature = "mlock", unix))]
+    pub fn lock(&mut self) -> std::io::Result<()> {
+        self.keys.lock()
     }
 
     pub fn encrypt1(&self, input: &[u8; 16], output: &mut [u8; 16]) {
         unsafe {
-            sys::encrypt1(&self.keys, input, output);
+            sys::encrypt1(self.keys(), input, output);
         }
     }
     pub fn decrypt1(&self, input: &[u8; 16], output: &mut [u8; 16]) {
         unsafe {
-            sys::decrypt1(&self.keys, input, output);
+            sys::decrypt1(self.keys(), input, output);
         }
     }
     pub fn encrypt8(&self, input: &[u8; 128], output: &mut [u8; 128]) {
         unsafe {
-            sys::encrypt8(&self.keys, input, output);
+            sys::encrypt8(self.keys(), input, output);
         }
     }
     pub fn decrypt8(&self, input: &[u8; 128], output: &mut [u8; 128]) {
         unsafe {
-            sys::decrypt8(&self.keys, input, output);
+            sys::decrypt8(self.keys(), input, output);
         }
     }
 }
+
+impl fmt::Debug for AESWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AESWrapper").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,121 @@
+//! `std::io` adapters that encrypt or decrypt a stream with `AESWrapper` in
+//! CTR or CBC mode (NIST SP 800-38A) as it passes through.
+//!
+//! The buffering, the PKCS#7 padding and the `Read`/`Write` adapters are
+//! p54's, re-exported here. p54's own `Ctr` and `Cbc` are tied to its `Aes`,
+//! so this module supplies only the two modes over `AESWrapper`.
+
+use crate::AESWrapper;
+use p54::modes::ModeError;
+
+pub use p54::modes::{CbcChain, CbcDecryptor, CbcEncryptor, CipherReader, CipherWriter, Transform};
+
+fn xor_in_place(data: &mut [u8], other: &[u8]) {
+    for (byte, other) in data.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
+
+/// Counter mode, with the whole block as a big-endian counter. The same
+/// transform encrypts and decrypts.
+pub struct Ctr {
+    aes: AESWrapper,
+    counter: u128,
+    keystream: [u8; 128],
+    used: usize,
+}
+
+impl Ctr {
+    pub fn new(aes: AESWrapper, initial_counter: &[u8; 16]) -> Self {
+        Self {
+            aes,
+            counter: u128::from_be_bytes(*initial_counter),
+            keystream: [0; 128],
+            used: 128,
+        }
+    }
+
+    /// Fills `keystream` with the next eight counter blocks, encrypted.
+    fn refill(&mut self) {
+        let mut counters = [0u8; 128];
+        for block in counters.chunks_exact_mut(16) {
+            block.copy_from_slice(&self.counter.to_be_bytes());
+            self.counter = self.counter.wrapping_add(1);
+        }
+        self.aes.encrypt8(&counters, &mut self.keystream);
+        self.used = 0;
+    }
+}
+
+impl Transform for Ctr {
+    fn update(&mut self, mut input: &[u8], output: &mut Vec<u8>) {
+        while !input.is_empty() {
+            if self.used == self.keystream.len() {
+                self.refill();
+            }
+            let n = (self.keystream.len() - self.used).min(input.len());
+            let start = output.len();
+            output.extend_from_slice(&input[..n]);
+            xor_in_place(&mut output[start..], &self.keystream[self.used..]);
+            self.used += n;
+            input = &input[n..];
+        }
+    }
+
+    fn finish(&mut self, _output: &mut Vec<u8>) -> Result<(), ModeError> {
+        Ok(())
+    }
+}
+
+/// CBC chaining over `AESWrapper`, for `CbcEncryptor` and `CbcDecryptor`.
+pub struct Cbc {
+    aes: AESWrapper,
+    chain: [u8; 16],
+}
+
+impl Cbc {
+    pub fn new(aes: AESWrapper, iv: &[u8; 16]) -> Self {
+        Self { aes, chain: *iv }
+    }
+}
+
+impl CbcChain for Cbc {
+    fn encrypt_blocks(&mut self, blocks: &mut [[u8; 16]]) {
+        for block in blocks {
+            xor_in_place(block, &self.chain);
+            self.aes.encrypt1(&*block, &mut self.chain);
+            *block = self.chain;
+        }
+    }
+
+    /// Decrypts `blocks` in place, eight at a time where possible.
+    fn decrypt_blocks(&mut self, blocks: &mut [[u8; 16]]) {
+        for chunk in blocks.chunks_mut(8) {
+            let chunk = chunk.as_flattened_mut();
+            let mut ciphertext = [0u8; 128];
+            ciphertext[..chunk.len()].copy_from_slice(chunk);
+            if let Ok(output) = <&mut [u8; 128]>::try_from(&mut *chunk) {
+                self.aes.decrypt8(&ciphertext, output);
+            } else {
+                for (input, block) in ciphertext
+                    .as_chunks::<16>()
+                    .0
+                    .iter()
+                    .zip(chunk.as_chunks_mut::<16>().0)
+                {
+                    self.aes.decrypt1(input, block);
+                }
+            }
+            for (i, block) in chunk.chunks_exact_mut(16).enumerate() {
+                let previous = if i == 0 {
+                    &self.chain[..]
+                } else {
+                    &ciphertext[16 * (i - 1)..16 * i]
+                };
+                xor_in_place(block, previous);
+            }
+            self.chain
+                .copy_from_slice(&ciphertext[chunk.len() - 16..chunk.len()]);
+        }
+    }
+}
@@ -88,3 +88,122 @@ fn test_decrypt8() {
     aeswrapper.encrypt1(&ciphertext1, &mut plaintext1);
     assert_eq!(plaintext8[64..80], plaintext1);
 }
+
+// FIPS-197 Appendix C.1, shared with the AES-NI and software implementations.
+#[test]
+fn test_fips197() {
+    let key = hex!("000102030405060708090a0b0c0d0e0f");
+    let plaintext = hex!("00112233445566778899aabbccddeeff");
+    let expected_ciphertext = hex!("69c4e0d86a7b0430d8cdb78070b4c55a");
+    let mut ciphertext = [0u8; 16];
+    let mut decrypted = [0u8; 16];
+    let aeswrapper = AESWrapper::new(&key);
+    aeswrapper.encrypt1(&plaintext, &mut ciphertext);
+    aeswrapper.decrypt1(&ciphertext, &mut decrypted);
+    assert_eq!(ciphertext, expected_ciphertext);
+    assert_eq!(decrypted, plaintext);
+}
+
+mod stream {
+    // Only the CTR and CBC transforms over `AESWrapper` are this crate's; the
+    // buffering, padding and adapters are p54's and are tested there.
+    use hex_literal::hex;
+    use mini_aes::stream::{Cbc, CbcChain, Ctr, Transform};
+    use mini_aes::AESWrapper;
+
+    const KEY: [u8; 16] = hex!("2b7e151628aed2a6abf7158809cf4f3c");
+    const PLAINTEXT: [u8; 64] = hex!(
+        "6bc1bee22e409f96e93d7e117393172a"
+        "ae2d8a571e03ac9c9eb76fac45af8e51"
+        "30c81c46a35ce411e5fbc1191a0a52ef"
+        "f69f2445df4f9b17ad2b417be66c3710"
+    );
+
+    // NIST SP 800-38A F.5.1.
+    #[test]
+    fn test_ctr() {
+        let counter = hex!("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let expected = hex!(
+            "874d6191b620e3261bef6864990db6ce"
+            "9806f66b7970fdff8617187bb9fffdff"
+            "5ae4df3edbd5d35e5b4f09020db03eab"
+            "1e031dda2fbe03d1792170a0f3009cee"
+        );
+        let mut ctr = Ctr::new(AESWrapper::new(&KEY), &counter);
+        let mut output = Vec::new();
+        for piece in PLAINTEXT.chunks(5) {
+            ctr.update(piece, &mut output);
+        }
+        ctr.finish(&mut output).unwrap();
+        assert_eq!(output, expected);
+    }
+
+    // NIST SP 800-38A F.2.1 and F.2.2, with eight blocks to cover `decrypt8`.
+    #[test]
+    fn test_cbc() {
+        let iv = hex!("000102030405060708090a0b0c0d0e0f");
+        let expected = hex!(
+            "7649abac8119b246cee98e9b12e9197d"
+            "5086cb9b507219ee95db113a917678b2"
+            "73bed6b8e3c1743b7116e69e22229516"
+            "3ff1caa1681fac09120eca307586e1a7"
+        );
+        let mut blocks = *PLAINTEXT.as_chunks::<16>().0.first_chunk::<4>().unwrap();
+        Cbc::new(AESWrapper::new(&KEY), &iv).encrypt_blocks(&mut blocks);
+        assert_eq!(blocks.as_flattened(), expected);
+        Cbc::new(AESWrapper::new(&KEY), &iv).decrypt_blocks(&mut blocks);
+        assert_eq!(blocks.as_flattened(), PLAINTEXT);
+
+        let mut cbc = Cbc::new(AESWrapper::new(&KEY), &iv);
+        let mut ciphertext = [[0u8; 16]; 9];
+        cbc.encrypt_blocks(&mut ciphertext[..4]);
+        cbc.encrypt_blocks(&mut ciphertext[4..]);
+        let mut plaintext = ciphertext;
+        let mut cbc = Cbc::new(AESWrapper::new(&KEY), &iv);
+        cbc.decrypt_blocks(&mut plaintext[..1]);
+        cbc.decrypt_blocks(&mut plaintext[1..]);
+        assert_eq!(plaintext, [[0u8; 16]; 9]);
+    }
+}
+
+mod secrets {
+    use mini_aes::AESWrapper;
+    use miniaes_sys as sys;
+    use std::mem::MaybeUninit;
+
+    #[test]
+    fn test_wipe_keys() {
+        let mut keys = MaybeUninit::<sys::RoundKeys>::uninit();
+        let bytes = unsafe {
+            sys::expand_key(&[0x5a; 16], keys.as_mut_ptr());
+            assert_ne!(*keys.as_ptr().cast::<[u8; 176]>(), [0; 176]);
+            sys::wipe_keys(keys.as_mut_ptr());
+            *keys.as_ptr().cast::<[u8; 176]>()
+        };
+        assert_eq!(bytes, [0; 176]);
+        // A null pointer is ignored.
+        unsafe { sys::wipe_keys(std::ptr::null_mut()) };
+    }
+
+    #[test]
+    fn test_debug_hides_keys() {
+        let aeswrapper = AESWrapper::new(&[0x5a; 16]);
+        assert_eq!(format!("{aeswrapper:?}"), "AESWrapper { .. }");
+        assert!(!aeswrapper.is_locked());
+    }
+
+    #[cfg(all(feature = "mlock", unix))]
+    #[test]
+    fn test_lock() {
+        let plaintext = [0x11; 16];
+        let mut expected = [0u8; 16];
+        AESWrapper::new(&[0x5a; 16]).encrypt1(&plaintext, &mut expected);
+
+        let mut aeswrapper = AESWrapper::new(&[0x5a; 16]);
+        aeswrapper.lock().unwrap();
+        assert!(aeswrapper.is_locked());
+        let mut ciphertext = [0u8; 16];
+        aeswrapper.encrypt1(&plaintext, &mut ciphertext);
+        assert_eq!(ciphertext, expected);
+    }
+}
@@ -12,4 +12,5 @@ extern "C" {
     pub fn decrypt1(rkeys: &RoundKeys, input: &[u8; 16], output: &mut [u8; 16]);
     pub fn encrypt8(rkeys: &RoundKeys, input: &[u8; 128], output: &mut [u8; 128]);
     pub fn decrypt8(rkeys: &RoundKeys, input: &[u8; 128], output: &mut [u8; 128]);
+    pub fn wipe_keys(rkeys: *mut RoundKeys);
 }